@@ -0,0 +1,66 @@
+use std::{alloc::{GlobalAlloc, Layout, System}, sync::atomic::{AtomicUsize, Ordering}};
+
+use bevy::math::{IRect, IVec2};
+use powderkeg::area::{Area, StainOrder};
+use rand::{rngs::SmallRng, SeedableRng};
+
+/// Counts every allocation made through it.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const TICKS: usize = 1000;
+
+/// The allocations per tick of visiting `area` every tick with `visit`.
+fn allocations_per_tick(area: &Area, mut visit: impl FnMut(&Area, &mut SmallRng)) -> f64 {
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    // The first tick grows any reused buffer to size, as the first tick of a simulation would.
+    visit(area, &mut rng);
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+
+    for _ in 0..TICKS {
+        visit(area, &mut rng);
+    }
+
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / TICKS as f64
+}
+
+fn main() {
+    let stained = [
+        ("fully stained 64x64 chunk", Area::from(IRect::new(0, 0, 63, 63))),
+        ("64x64 chunk of overlapping rects", Area::Many((0..8).map(|i| IRect::new(i * 7, 0, i * 7 + 15, 63)).collect())),
+    ];
+
+    for (name, area) in stained.iter() {
+        let mut visited = 0;
+
+        let fresh = allocations_per_tick(area, |area, rng| area.apply_in_order(StainOrder::Random, rng, |_| visited += 1));
+
+        let mut scratch: Vec<IVec2> = Vec::new();
+
+        let reused = allocations_per_tick(area, |area, rng| area.apply_in_order_with(StainOrder::Random, rng, &mut scratch, |_| visited += 1));
+
+        println!("{name}: {fresh} allocations per tick allocating the points, {reused} reusing a buffer ({visited} points visited)");
+    }
+}
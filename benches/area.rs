@@ -0,0 +1,44 @@
+use bevy::math::{IRect, IVec2};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use powderkeg::area::Area;
+
+/// A chunk's worth of stain as many overlapping boxes, like those cells stain around themselves.
+fn stained_boxes() -> Area {
+    let mut rects = Vec::new();
+
+    for y in (0..64).step_by(3) {
+        for x in (0..64).step_by(5) {
+            rects.push(IRect::new(x, y, x + 5, y + 3));
+        }
+    }
+
+    Area::Many(rects)
+}
+
+/// How coverage was checked before, one point at a time.
+fn contains_rect_per_cell(area: &Area, rect: IRect) -> bool {
+    (rect.min.y..=rect.max.y).all(|y| (rect.min.x..=rect.max.x).all(|x| area.contains(IVec2::new(x, y))))
+}
+
+fn contains_rect(c: &mut Criterion) {
+    let area = stained_boxes();
+    let range = IRect::from_center_half_size(IVec2::splat(32), IVec2::splat(8));
+
+    assert_eq!(area.contains_rect(range), contains_rect_per_cell(&area, range));
+
+    let mut group = c.benchmark_group("16x16 range against overlapping boxes");
+
+    group.bench_function("per cell", |b| b.iter(|| contains_rect_per_cell(black_box(&area), black_box(range))));
+    group.bench_function("rect subtraction", |b| b.iter(|| black_box(&area).contains_rect(black_box(range))));
+
+    group.finish();
+}
+
+fn coalesce(c: &mut Criterion) {
+    let area = stained_boxes();
+
+    c.bench_function("coalesce overlapping boxes", |b| b.iter(|| black_box(area.clone()).coalesce()));
+}
+
+criterion_group!(benches, contains_rect, coalesce);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+use std::{convert::Infallible, mem::size_of_val};
+
+use bevy::math::{IRect, IVec2};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use powderkeg::{cell::{Cell, TickInput, TickSuccess}, chunk::Chunk, grid::Grid, palette::PalettedChunk, stain::Stainable, PowderkegError};
+
+const SIZE: i32 = 64;
+
+/// Five kinds of cell, one carrying data, mostly air as in a typical world.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Material {
+    #[default]
+    Air,
+    Sand,
+    Water,
+    Stone,
+    Fire { heat: u32 },
+}
+
+impl Cell for Material {
+    type State = ();
+    type Error = Infallible;
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+fn chunk() -> Chunk<Material, SIZE> {
+    let cells = (0..Chunk::<Material, SIZE>::volume())
+        .map(|index| match index % 16 {
+            0 => Material::Sand,
+            1 => Material::Water,
+            2 => Material::Stone,
+            3 => Material::Fire { heat: 100 },
+            _ => Material::Air,
+        })
+        .collect();
+
+    Chunk::new(cells, ())
+}
+
+fn memory(c: &mut Criterion) {
+    let chunk = chunk();
+    let paletted = PalettedChunk::from_chunk(&chunk);
+
+    println!(
+        "64x64 chunk of a 5-variant enum: {} bytes as cells, {} bytes paletted",
+        size_of_val(chunk.cells()),
+        paletted.heap_bytes(),
+    );
+
+    let mut group = c.benchmark_group("64x64 chunk of a 5-variant enum");
+
+    group.bench_function("palette a chunk", |b| b.iter(|| PalettedChunk::from_chunk(black_box(&chunk))));
+    group.bench_function("unpalette a chunk", |b| b.iter(|| black_box(&paletted).to_chunk()));
+
+    group.finish();
+}
+
+fn access(c: &mut Criterion) {
+    let mut chunk = chunk();
+    let mut paletted = PalettedChunk::from_chunk(&chunk);
+    let point = IVec2::new(17, 33);
+
+    let mut group = c.benchmark_group("64x64 chunk of a 5-variant enum");
+
+    group.bench_function("get_mut cells", |b| b.iter(|| *chunk.get_mut(black_box(point)).unwrap() = Material::Sand));
+    group.bench_function("get_mut paletted", |b| b.iter(|| *paletted.get_mut(black_box(point)).unwrap() = Material::Sand));
+
+    group.finish();
+}
+
+criterion_group!(benches, memory, access);
+criterion_main!(benches);
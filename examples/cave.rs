@@ -0,0 +1,228 @@
+use std::{collections::HashSet, convert::Infallible};
+
+use bevy::{core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping}, prelude::*, window::PrimaryWindow};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkCoords, SpawnChunkGrid}, lighting::{LightSettings, Luminous, PowderkegLightingPlugin}, simulation::PowderkegTickRate, stain::{ChangeKind, Stainable}, world::PowderkegWorld, PowderkegError, PowderkegPlugin};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+const CHUNK_SIZE: i32 = 32;
+const TUNNEL_STEPS: usize = 400;
+
+/// A wandering fire lighting up the tunnels of a cave, only the light around where it moved is recomputed.
+///
+/// Left click digs out rock and right click drops dust. The chunks track their changes, so digging relights the
+/// tunnels around the hole while dust, which light passes through, falls without relighting anything.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaveCell {
+    #[default]
+    Rock,
+    Air,
+    Fire,
+    Dust,
+}
+
+impl Cell for CaveCell {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() == CaveCell::Dust {
+            let below = input.origin + IVec2::NEG_Y;
+
+            if input.grid.get(below).is_ok_and(|cell| *cell == CaveCell::Air) {
+                input.grid.swap(input.origin, below)?;
+                input.grid.stain_around(input.origin, 1);
+
+                return Ok(TickSuccess::Unstable);
+            }
+
+            return Ok(TickSuccess::Stable);
+        }
+
+        if *input.this() != CaveCell::Fire {
+            return Ok(TickSuccess::Stable);
+        }
+
+        let mut offsets = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+        offsets.shuffle(&mut input.rng());
+
+        for offset in offsets {
+            let target = input.origin + offset;
+
+            if input.grid.get(target).is_ok_and(|cell| *cell == CaveCell::Air) {
+                input.grid.swap(input.origin, target)?;
+                input.grid.stain_around(input.origin, 1);
+
+                break;
+            }
+        }
+
+        input.grid.stain_point(input.origin);
+
+        Ok(TickSuccess::Unstable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-1, -1, 1, 1)
+    }
+}
+
+impl Renderable for CaveCell {
+    const EMISSIVE: bool = true;
+
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            CaveCell::Rock => Color::rgb(0.35, 0.3, 0.3),
+            CaveCell::Air => Color::rgb(0.6, 0.6, 0.7),
+            CaveCell::Fire => Color::ORANGE,
+            CaveCell::Dust => Color::rgb(0.8, 0.75, 0.6),
+        }
+    }
+
+    fn emissive(&self, _: IVec2) -> f32 {
+        match self {
+            CaveCell::Fire => 2.0,
+            _ => 0.0,
+        }
+    }
+}
+
+impl Luminous for CaveCell {
+    fn emission(&self) -> u8 {
+        match self {
+            CaveCell::Fire => u8::MAX,
+            _ => 0,
+        }
+    }
+
+    fn is_opaque(&self) -> bool {
+        *self == CaveCell::Rock
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: String::from("Powderkeg Cave Example"),
+                        ..default()
+                    }),
+                    ..default()
+                })
+        )
+        .add_plugins(PowderkegPlugin::<CaveCell, CHUNK_SIZE>::default())
+        .add_plugins(PowderkegLightingPlugin::<CaveCell, CHUNK_SIZE>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, dig)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+) {
+    // The fire glows past full brightness, HDR and bloom spread it over the cells around.
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera { hdr: true, ..default() },
+            tonemapping: Tonemapping::TonyMcMapface,
+            ..default()
+        },
+        BloomSettings::default(),
+    ));
+
+    commands.insert_resource(PowderkegTickRate(8.0));
+    commands.insert_resource(LightSettings { falloff: 24, ambient: 0.05 });
+
+    let tunnels = carve_tunnels();
+
+    commands.spawn_chunk_grid::<CaveCell, CHUNK_SIZE>(
+        -2..2,
+        -2..2,
+        Transform::default().with_scale(Vec3::splat(4.0)),
+        false,
+        |chunk_coords| {
+            let chunk_coords = ChunkCoords::<CHUNK_SIZE>(chunk_coords);
+
+            let mut chunk = Chunk::<CaveCell, CHUNK_SIZE>::default();
+
+            let filled = chunk.fill_with(Chunk::<CaveCell, CHUNK_SIZE>::area(), |local| {
+                let world = chunk_coords.local_to_world(local);
+
+                if world == IVec2::ZERO {
+                    CaveCell::Fire
+                } else if tunnels.contains(&world) {
+                    CaveCell::Air
+                } else {
+                    CaveCell::Rock
+                }
+            });
+
+            if let Err(error) = filled {
+                error!("Failed to carve chunk {}: {error}", chunk_coords.0);
+            }
+
+            chunk.track_changes(classify);
+
+            chunk
+        },
+    );
+}
+
+/// Only rock appearing or disappearing changes where light can reach.
+fn classify(old: &CaveCell, new: &CaveCell) -> Option<ChangeKind> {
+    match (old.is_opaque(), new.is_opaque()) {
+        (true, false) => Some(ChangeKind::Removed),
+        (false, true) => Some(ChangeKind::Created),
+        _ => None,
+    }
+}
+
+fn dig(
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut world: PowderkegWorld<CaveCell, CHUNK_SIZE>,
+) {
+    let (camera, camera_transform) = cameras.single();
+
+    let Some(position) = windows.single().cursor_position().and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor)) else {
+        return;
+    };
+
+    let Some(point) = world.world_point(position) else {
+        return;
+    };
+
+    if buttons.pressed(MouseButton::Left) {
+        world.paint_circle(point, 2, CaveCell::Air, |cell| *cell == CaveCell::Rock);
+    } else if buttons.pressed(MouseButton::Right) {
+        world.paint_circle(point, 1, CaveCell::Dust, |cell| *cell == CaveCell::Air);
+    }
+}
+
+/// Random walks from the origin, widened to tunnels two cells across.
+fn carve_tunnels() -> HashSet<IVec2> {
+    let mut rng = thread_rng();
+    let mut tunnels = HashSet::new();
+    let bound = 2 * CHUNK_SIZE - 2;
+
+    for _ in 0..4 {
+        let mut point = IVec2::ZERO;
+
+        for _ in 0..TUNNEL_STEPS {
+            for y in 0..2 {
+                for x in 0..2 {
+                    tunnels.insert(point + IVec2::new(x, y));
+                }
+            }
+
+            let step = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y][rng.gen_range(0..4)];
+
+            point = (point + step).clamp(IVec2::splat(-bound), IVec2::splat(bound - 1));
+        }
+    }
+
+    tunnels
+}
@@ -0,0 +1,160 @@
+use std::convert::Infallible;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkCoords, SpawnChunkGrid}, grid::{ClampedGrid, Grid}, simulation::{Gravity, PowderkegTick}, stain::Stainable, world::world_state_hash, PowderkegError, PowderkegPlugin, PowderkegSet};
+use rand::Rng;
+
+const CHUNK_SIZE: i32 = 64;
+
+/// The invisible container water is kept in, in the frame of the single chunk at the origin.
+const CONTAINER: IRect = IRect { min: IVec2::new(16, 8), max: IVec2::new(47, 40) };
+
+/// The initial pool of water, which settles the same way every run with the same seed until water is poured.
+const POOL: IRect = IRect { min: IVec2::new(20, 28), max: IVec2::new(43, 40) };
+const SEED: u64 = 0x5eed;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ContainerCell {
+    Water,
+    #[default]
+    Air,
+}
+
+impl Cell for ContainerCell {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() != ContainerCell::Water {
+            return Ok(TickSuccess::Stable);
+        }
+
+        let gravity = input.gravity();
+        let side = if input.rng().gen_bool(0.5) { gravity.perp() } else { -gravity.perp() };
+
+        // Writes past the container's walls fail, so the water only looks for room within them.
+        let grid = &mut ClampedGrid::new(input.grid, CONTAINER);
+
+        for offset in [gravity, gravity + side, gravity - side, side, -side] {
+            if grid.contains(input.origin + offset) && grid.get(input.origin + offset).is_ok_and(|cell| *cell == ContainerCell::Air) {
+                grid.swap(input.origin, input.origin + offset)?;
+                grid.stain_around(input.origin, 1);
+
+                return Ok(TickSuccess::Unstable);
+            }
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-1, -1, 1, 1)
+    }
+}
+
+impl Renderable for ContainerCell {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            ContainerCell::Water => Color::TEAL,
+            ContainerCell::Air => Color::BLACK,
+        }
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: String::from("Powderkeg Container Example"),
+                        ..default()
+                    }),
+                    ..default()
+                })
+        )
+        // One tick per frame at most, so the hash is logged at exactly every 256th tick.
+        .add_plugins(PowderkegPlugin::<ContainerCell, CHUNK_SIZE>::new().seed(SEED).max_ticks_per_frame(1))
+        .add_systems(Startup, setup)
+        .add_systems(Update, pour_water.before(PowderkegSet::Tick))
+        .add_systems(Update, log_state_hash.after(PowderkegSet::Tick))
+        .add_systems(Update, rotate_gravity.before(PowderkegSet::Tick))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    commands.spawn_chunk_grid::<ContainerCell, CHUNK_SIZE>(
+        0..1,
+        0..1,
+        Transform::default().with_scale(Vec3::splat(8.0)),
+        true,
+        |_| {
+            let mut chunk = Chunk::full_copied(ContainerCell::Air, ());
+
+            chunk.paint_rect(POOL, ContainerCell::Water, |_| true);
+
+            chunk
+        },
+    );
+}
+
+/// Logs a hash of the world every 256 ticks, runs with the same seed log the same hashes until water is poured or
+/// gravity rotated.
+fn log_state_hash(
+    tick: Res<PowderkegTick>,
+    chunks: Query<(&ChunkCoords<CHUNK_SIZE>, &Chunk<ContainerCell, CHUNK_SIZE>)>,
+) {
+    if tick.is_changed() && tick.0.is_multiple_of(256) {
+        info!("World hash at tick {}: {:016x}", tick.0, world_state_hash(chunks.iter(), false));
+    }
+}
+
+/// G turns gravity a quarter turn clockwise, waking all the settled water to flow the new way.
+fn rotate_gravity(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut gravity: ResMut<Gravity>,
+    mut chunks: Query<&mut Chunk<ContainerCell, CHUNK_SIZE>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyG) {
+        return;
+    }
+
+    gravity.0 = -gravity.0.perp();
+
+    for mut chunk in chunks.iter_mut() {
+        chunk.mark_dirty();
+    }
+}
+
+/// Pours water at the cursor while the left button is held, clamped so water is only poured inside the container.
+fn pour_water(
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut chunks: Query<(&mut Chunk<ContainerCell, CHUNK_SIZE>, &GlobalTransform)>,
+) {
+    if !buttons.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (camera, camera_transform) = cameras.single();
+
+    let Some(position) = windows.single().cursor_position().and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor)) else {
+        return;
+    };
+
+    for (mut chunk, transform) in chunks.iter_mut() {
+        let local = transform.affine().inverse().transform_point3(position.extend(0.0)).truncate().as_ivec2() + IVec2::splat(CHUNK_SIZE / 2);
+
+        let mut container = ClampedGrid::new(&mut *chunk, CONTAINER);
+
+        if container.paint_circle(local, 2, ContainerCell::Water, |cell| *cell == ContainerCell::Air) > 0 {
+            container.stain_around(local, 3);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, stain::Stainable, testing::TestGrid, PowderkegError};
+
+const CHUNK_SIZE: i32 = 16;
+
+/// Steps a cell rule tick by tick without opening a window, the way the tests do.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SandCell {
+    Sand,
+    #[default]
+    Air,
+}
+
+impl Cell for SandCell {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() != SandCell::Sand {
+            return Ok(TickSuccess::Stable);
+        }
+
+        let below = input.origin + IVec2::NEG_Y;
+
+        if input.grid.get(below).is_ok_and(|cell| *cell == SandCell::Air) {
+            input.grid.swap(input.origin, below)?;
+            input.grid.stain_around(input.origin, 1);
+
+            return Ok(TickSuccess::Unstable);
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, -1, 0, 0)
+    }
+}
+
+impl Renderable for SandCell {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            SandCell::Sand => Color::BEIGE,
+            SandCell::Air => Color::BLACK,
+        }
+    }
+}
+
+fn main() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    grid
+        .insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()))
+        .insert_chunk(IVec2::NEG_Y, Chunk::full_copied(SandCell::Air, ()));
+
+    grid.set(IVec2::new(5, 5), SandCell::Sand).unwrap();
+
+    // The grain falls a cell a tick, out of its chunk and into the one below, see `tests/test_grid.rs`.
+    while grid.get(IVec2::new(5, -CHUNK_SIZE)) != Some(&SandCell::Sand) {
+        for error in grid.step() {
+            error!("Error ticking {}: {}", error.point, error.error);
+        }
+    }
+
+    info!("sand reached the bottom after {} ticks", grid.tick());
+}
@@ -0,0 +1,191 @@
+use std::{collections::HashMap, convert::Infallible};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use powderkeg::{cell::{Cell, Renderable, SwapStates, TickInput, TickSuccess}, chunk::{Chunk, ChunkCoords, SpawnChunkGrid}, stain::Stainable, world::PowderkegWorld, PowderkegError, PowderkegPlugin, PowderkegSet};
+use rand::{thread_rng, Rng};
+
+const CHUNK_SIZE: i32 = 32;
+const ISLAND_RADIUS: i32 = 60;
+const PACKING_FALL: u32 = 8;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum IslandCell {
+    Sand,
+    /// Sand that landed after falling at least `PACKING_FALL` cells.
+    PackedSand,
+    #[default]
+    Air,
+}
+
+/// How far each falling grain of sand has fallen, keyed by its position in the chunk.
+#[derive(Default)]
+pub struct FallDistances(HashMap<IVec2, u32>);
+
+fn chunk_local(point: IVec2) -> IVec2 {
+    point.rem_euclid(IVec2::splat(CHUNK_SIZE))
+}
+
+impl Cell for IslandCell {
+    type Error = Infallible;
+    type State = FallDistances;
+
+    fn tick<G: Stainable<Cell = Self>>(mut input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() != IslandCell::Sand {
+            return Ok(TickSuccess::Stable);
+        }
+
+        for offset in [IVec2::new(0, -1), IVec2::new(-1, -1), IVec2::new(1, -1)] {
+            let target = input.origin + offset;
+
+            if input.grid.map_cell(target, |cell| *cell == IslandCell::Air)? {
+                // The grain's fall distance moves with it, even into the chunk below.
+                input.grid.swap_with_state(input.origin, target)?;
+                *input.grid.state_at(target).write().0.entry(chunk_local(target)).or_default() += 1;
+                input.grid.stain_around(input.origin, 1);
+
+                return Ok(TickSuccess::Unstable);
+            }
+        }
+
+        let fallen = input.state().write().0.remove(&chunk_local(input.origin)).unwrap_or_default();
+
+        if fallen >= PACKING_FALL {
+            *input.this_mut() = IslandCell::PackedSand;
+            input.grid.stain_point(input.origin);
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-1, -1, 1, 0)
+    }
+
+    fn swap_state(states: SwapStates<'_, FallDistances>, first: IVec2, second: IVec2) {
+        states.exchange(first, second, |state, point| state.0.remove(&point), |state, point, fallen| {
+            if let Some(fallen) = fallen {
+                state.0.insert(point, fallen);
+            }
+        });
+    }
+}
+
+impl Renderable for IslandCell {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            IslandCell::Sand => Color::BEIGE,
+            IslandCell::PackedSand => Color::rgb(0.7, 0.6, 0.4),
+            IslandCell::Air => Color::MIDNIGHT_BLUE,
+        }
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: String::from("Powderkeg Island Example"),
+                        ..default()
+                    }),
+                    ..default()
+                })
+        )
+        .add_plugins(PowderkegPlugin::<IslandCell, CHUNK_SIZE>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, paint_sand.before(PowderkegSet::Tick))
+        .add_systems(Update, loosen_packed_sand.before(PowderkegSet::Tick))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+) {
+    let mut rng = thread_rng();
+
+    commands.spawn(Camera2dBundle::default());
+
+    commands.spawn_chunk_grid::<IslandCell, CHUNK_SIZE>(
+        -2..2,
+        -2..2,
+        Transform::default().with_scale(Vec3::splat(4.0)),
+        false,
+        |chunk_coords| {
+            let chunk_coords = ChunkCoords::<CHUNK_SIZE>(chunk_coords);
+
+            let mut cells = Vec::with_capacity(Chunk::<IslandCell, CHUNK_SIZE>::volume());
+
+            for y in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let world = chunk_coords.local_to_world(IVec2::new(x, y));
+
+                    cells.push(if world.length_squared() > ISLAND_RADIUS * ISLAND_RADIUS {
+                        None
+                    } else if rng.gen_bool(0.3) {
+                        Some(IslandCell::Sand)
+                    } else {
+                        Some(IslandCell::Air)
+                    });
+                }
+            }
+
+            Chunk::sparse(cells, FallDistances::default())
+        },
+    );
+}
+
+fn paint_sand(
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut world: PowderkegWorld<IslandCell, CHUNK_SIZE>,
+    mut last: Local<Option<IVec2>>,
+) {
+    if !buttons.pressed(MouseButton::Left) {
+        *last = None;
+        return;
+    }
+
+    let (camera, camera_transform) = cameras.single();
+
+    let Some(point) = windows.single()
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+        .and_then(|position| world.world_point(position))
+    else {
+        return;
+    };
+
+    // Strokes from where the cursor was last frame, leaving no gaps however fast it moves.
+    world.paint_line(last.unwrap_or(point), point, 2, IslandCell::Sand, |old| *old == IslandCell::Air);
+
+    *last = Some(point);
+}
+
+/// Right clicking packed sand loosens the whole connected mound of it, across chunks, letting it fall again.
+fn loosen_packed_sand(
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut world: PowderkegWorld<IslandCell, CHUNK_SIZE>,
+) {
+    if !buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+
+    let (camera, camera_transform) = cameras.single();
+
+    let Some(point) = windows.single()
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+        .and_then(|position| world.world_point(position))
+    else {
+        return;
+    };
+
+    let loosened = world.flood_fill(point, IslandCell::Sand, |cell| *cell == IslandCell::PackedSand, Some(4096));
+
+    info!("Loosened {loosened} packed sand");
+}
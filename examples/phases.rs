@@ -0,0 +1,213 @@
+use std::{convert::Infallible, sync::{Arc, Mutex}};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, SpawnChunkGrid}, grid::Grid, simulation::{CellChanged, CellHistogram, EmitCellChanged, PowderkegTickRate}, stain::Stainable, viewer::RenderHook, PowderkegError, PowderkegPlugin, PowderkegSet};
+use rand::Rng;
+
+const CHUNK_SIZE: i32 = 32;
+const SPARK_LIFETIME: f32 = 0.25;
+
+/// Fire spreads through wood and leaves smoke that rises, when `PHASED` every fire ticks before any smoke moves
+/// so the fire front advances a whole step before the smoke it left behind reacts.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum FireCell<const PHASED: bool> {
+    Wood,
+    Fire,
+    Smoke,
+    #[default]
+    Air,
+}
+
+impl<const PHASED: bool> Cell for FireCell<PHASED> {
+    type Error = Infallible;
+    type State = ();
+
+    const VARIANT_COUNT: usize = 4;
+    const PHASES: u32 = if PHASED { 2 } else { 1 };
+
+    fn tick<G: Stainable<Cell = Self>>(mut input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        match input.this() {
+            FireCell::Fire => {
+                for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                    let neighbor = input.origin + offset;
+
+                    if input.grid.replace_if(neighbor, FireCell::Fire, |cell| *cell == FireCell::Wood)?.is_some() {
+                        input.grid.stain_point(neighbor);
+                    }
+                }
+
+                *input.this_mut() = FireCell::Smoke;
+                input.grid.stain_around(input.origin, 1);
+
+                Ok(TickSuccess::Unstable)
+            },
+            FireCell::Smoke => {
+                let above = input.origin + IVec2::Y;
+
+                if input.rng().gen_bool(0.02) {
+                    *input.this_mut() = FireCell::Air;
+                    input.grid.stain_point(input.origin);
+
+                    Ok(TickSuccess::Stable)
+                } else if input.grid.map_cell(above, |cell| *cell == FireCell::Air)? {
+                    input.grid.swap(input.origin, above)?;
+                    input.grid.stain_around(input.origin, 1);
+
+                    Ok(TickSuccess::Unstable)
+                } else {
+                    Ok(TickSuccess::Unstable)
+                }
+            },
+            FireCell::Wood | FireCell::Air => Ok(TickSuccess::Stable),
+        }
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-1, -1, 1, 1)
+    }
+
+    fn variant(&self) -> Option<usize> {
+        Some(*self as usize)
+    }
+
+    fn phase(&self) -> u32 {
+        match self {
+            FireCell::Fire if PHASED => 0,
+            _ if PHASED => 1,
+            _ => 0,
+        }
+    }
+}
+
+impl<const PHASED: bool> Renderable for FireCell<PHASED> {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            FireCell::Wood => Color::rgb(0.4, 0.25, 0.1),
+            FireCell::Fire => Color::ORANGE_RED,
+            FireCell::Smoke => Color::DARK_GRAY,
+            FireCell::Air => Color::BLACK,
+        }
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: String::from("Powderkeg Phases Example (left single phase, right phased)"),
+                        ..default()
+                    }),
+                    ..default()
+                })
+        )
+        .add_plugins(PowderkegPlugin::<FireCell<false>, CHUNK_SIZE>::default())
+        .add_plugins(PowderkegPlugin::<FireCell<true>, CHUNK_SIZE>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, draw_sparks.after(PowderkegSet::Render))
+        .add_systems(Update, count_fire.after(PowderkegSet::Tick))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    commands.insert_resource(PowderkegTickRate(8.0));
+    commands.insert_resource(CellHistogram::<FireCell<false>>::default());
+    commands.insert_resource(CellHistogram::<FireCell<true>>::default());
+    commands.insert_resource(EmitCellChanged::<FireCell<false>>::new(|from, to| *from == FireCell::Wood && *to == FireCell::Fire));
+
+    let lit = NewlyLit::default();
+    let queue = lit.0.clone();
+
+    commands.insert_resource(lit);
+    commands.insert_resource(RenderHook::<FireCell<true>>::new(move |point, cell| {
+        if *cell == FireCell::Fire {
+            queue.lock().unwrap().push(point);
+        }
+    }));
+
+    commands.spawn_chunk_grid::<FireCell<false>, CHUNK_SIZE>(
+        -2..0,
+        -1..1,
+        Transform::from_xyz(-8.0, 0.0, 0.0).with_scale(Vec3::splat(4.0)),
+        false,
+        |_| forest(),
+    );
+
+    commands.spawn_chunk_grid::<FireCell<true>, CHUNK_SIZE>(
+        0..2,
+        -1..1,
+        Transform::from_xyz(8.0, 0.0, 0.0).with_scale(Vec3::splat(4.0)),
+        false,
+        |_| forest(),
+    );
+}
+
+/// World positions of fire cells redrawn since the sparks last read them, filled by the render hook.
+#[derive(Resource, Default)]
+struct NewlyLit(Arc<Mutex<Vec<IVec2>>>);
+
+/// Draws a fading spark over each newly lit fire cell in the phased world.
+fn draw_sparks(
+    lit: Res<NewlyLit>,
+    time: Res<Time>,
+    mut sparks: Local<Vec<(Vec2, f32)>>,
+    mut gizmos: Gizmos,
+) {
+    let now = time.elapsed_seconds();
+
+    for point in lit.0.lock().unwrap().drain(..) {
+        let position = (point.as_vec2() + Vec2::splat(0.5 - CHUNK_SIZE as f32 / 2.0)) * 4.0 + Vec2::new(8.0, 0.0);
+
+        sparks.push((position, now));
+    }
+
+    sparks.retain(|(_, lit)| now - *lit < SPARK_LIFETIME);
+
+    for (position, lit) in sparks.iter() {
+        let fade = 1.0 - (now - lit) / SPARK_LIFETIME;
+
+        gizmos.circle_2d(*position, 3.0 * fade, Color::YELLOW.with_a(fade));
+    }
+}
+
+fn count_fire(
+    single: Res<CellHistogram<FireCell<false>>>,
+    phased: Res<CellHistogram<FireCell<true>>>,
+    mut ignited: EventReader<CellChanged<FireCell<false>>>,
+    mut ignitions: Local<usize>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    *ignitions += ignited.read().count();
+
+    if let Ok(mut window) = windows.get_single_mut() {
+        let fire = FireCell::<false>::Fire as usize;
+
+        window.title = format!(
+            "Powderkeg Phases Example (single phase {} fire after {} ignitions, phased {} fire)",
+            single.count(fire),
+            *ignitions,
+            phased.count(fire),
+        );
+    }
+}
+
+/// The same forest for both worlds, a block of wood with a fire lit at its bottom center.
+fn forest<const PHASED: bool>() -> Chunk<FireCell<PHASED>, CHUNK_SIZE> {
+    let mut chunk = Chunk::full_copied(FireCell::Air, ());
+
+    let planted = chunk
+        .fill_rect(IRect::new(0, 0, CHUNK_SIZE - 1, CHUNK_SIZE / 2 - 1), FireCell::Wood)
+        .and_then(|_| chunk.replace(IVec2::new(CHUNK_SIZE / 2, 0), FireCell::Fire));
+
+    if let Err(error) = planted {
+        error!("Failed to plant the forest: {error}");
+    }
+
+    chunk
+}
@@ -0,0 +1,259 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, SpawnChunkGrid}, simulation::{BorderBehavior, ChunkSpawner, OutOfWorldPolicy, PowderkegErrors, PowderkegTickRate}, stain::Stainable, world::PowderkegWorld, PowderkegError, PowderkegPlugin, PowderkegSet};
+use rand::{thread_rng, Rng};
+use thiserror::Error;
+
+const CHUNK_SIZE: i32 = 32;
+
+/// How far a bomb's blast reaches, past its range so blasts crossing into a neighboring chunk are deferred.
+const BLAST_RADIUS: i32 = 6;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReactorCell {
+    Reactor,
+    Sand,
+    /// Goes off once sand lands on it, clearing everything within [`BLAST_RADIUS`]. Bombs line the bottom of the
+    /// world, where the `OutOfWorldPolicy` decides whether the part of a blast outside is skipped, spawned or an error.
+    Bomb,
+    #[default]
+    Air,
+}
+
+#[derive(Debug, Error)]
+pub enum ReactorError {
+    #[error("reactors placed next to each other melted down")]
+    Meltdown,
+}
+
+/// Each meltdown in a chunk energizes it, widening the blast radius of its remaining reactors.
+#[derive(Default)]
+pub struct ReactorEnergy(i32);
+
+impl ReactorEnergy {
+    const MAX: i32 = 4;
+
+    fn radius(&self) -> i32 {
+        1 + self.0
+    }
+}
+
+impl Cell for ReactorCell {
+    type Error = ReactorError;
+    type State = ReactorEnergy;
+
+    fn tick<G: Stainable<Cell = Self>>(mut input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        match input.this() {
+            ReactorCell::Reactor => {
+                let state = input.state();
+                let radius = state.read().radius();
+
+                for y in -radius..=radius {
+                    for x in -radius..=radius {
+                        let offset = IVec2::new(x, y);
+
+                        if offset != IVec2::ZERO && input.grid.map_cell(input.origin + offset, |cell| *cell == ReactorCell::Reactor)? {
+                            input.grid.replace(input.origin, ReactorCell::Air)?;
+
+                            let mut energy = state.write();
+                            energy.0 = (energy.0 + 1).min(ReactorEnergy::MAX);
+
+                            return Err(PowderkegError::Cell(ReactorError::Meltdown));
+                        }
+                    }
+                }
+
+                Ok(TickSuccess::Stable)
+            },
+            ReactorCell::Bomb => {
+                if !input.grid.map_cell(input.origin + IVec2::Y, |cell| *cell == ReactorCell::Sand)? {
+                    return Ok(TickSuccess::Stable);
+                }
+
+                if !input.grid.covers().contains_rect(IRect::from_center_half_size(input.origin, IVec2::splat(BLAST_RADIUS))) && input.defer() {
+                    return Ok(TickSuccess::Stable);
+                }
+
+                for y in -BLAST_RADIUS..=BLAST_RADIUS {
+                    for x in -BLAST_RADIUS..=BLAST_RADIUS {
+                        input.grid.replace(input.origin + IVec2::new(x, y), ReactorCell::Air)?;
+                    }
+                }
+
+                Ok(TickSuccess::Stable)
+            },
+            ReactorCell::Sand => {
+                let below = input.origin + IVec2::new(0, -1);
+
+                if input.grid.map_cell(below, |cell| *cell == ReactorCell::Air)? {
+                    input.grid.swap(input.origin, below)?;
+                    input.grid.stain_around(input.origin, 1);
+
+                    Ok(TickSuccess::Unstable)
+                } else {
+                    Ok(TickSuccess::Stable)
+                }
+            },
+            ReactorCell::Air => Ok(TickSuccess::Stable),
+        }
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-1, -1, 1, 1)
+    }
+
+    fn range_with_state(&self, state: &ReactorEnergy) -> IRect {
+        match self {
+            ReactorCell::Reactor => IRect::from_center_half_size(IVec2::ZERO, IVec2::splat(state.radius())),
+            _ => self.range(),
+        }
+    }
+}
+
+impl Renderable for ReactorCell {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            ReactorCell::Reactor => Color::LIME_GREEN,
+            ReactorCell::Sand => Color::BEIGE,
+            ReactorCell::Bomb => Color::RED,
+            ReactorCell::Air => Color::BLACK,
+        }
+    }
+
+    /// Reactors in energized chunks glow from green towards yellow.
+    fn to_color_with_state(&self, point: IVec2, state: &ReactorEnergy) -> Color {
+        match self {
+            ReactorCell::Reactor => {
+                let heat = state.0 as f32 / ReactorEnergy::MAX as f32;
+
+                Color::rgb(0.2 + 0.8 * heat, 0.8, 0.2 * (1.0 - heat))
+            },
+            _ => self.to_color(point),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct MeltdownCount(usize);
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: String::from("Powderkeg Reactor Example"),
+                        ..default()
+                    }),
+                    ..default()
+                })
+        )
+        .add_plugins(PowderkegPlugin::<ReactorCell, CHUNK_SIZE>::default())
+        .init_resource::<MeltdownCount>()
+        .add_systems(Startup, setup)
+        .add_systems(Update, count_meltdowns.after(PowderkegSet::Tick))
+        .add_systems(Update, cycle_out_of_world_policy)
+        .add_systems(Update, toggle_border.before(PowderkegSet::Tick))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+) {
+    let mut rng = thread_rng();
+
+    commands.spawn(Camera2dBundle::default());
+
+    commands.insert_resource(PowderkegTickRate(32.0));
+
+    // Only spawns chunks for blasts under `OutOfWorldPolicy::Spawn`, no cell triggers it by reaching outside.
+    commands.insert_resource(ChunkSpawner::<ReactorCell, CHUNK_SIZE>::new(
+        |_| Chunk::full_copied(ReactorCell::Air, ReactorEnergy::default()),
+        |_| false,
+    ));
+
+    commands.spawn_chunk_grid::<ReactorCell, CHUNK_SIZE>(
+        -2..2,
+        -1..1,
+        Transform::default().with_scale(Vec3::splat(4.0)),
+        false,
+        |chunk_coords| {
+            let mut cells = vec![ReactorCell::Air; Chunk::<ReactorCell, CHUNK_SIZE>::volume()];
+
+            for cell in cells.iter_mut() {
+                *cell = match rng.gen_range(0..100) {
+                    0 => ReactorCell::Reactor,
+                    1..=30 => ReactorCell::Sand,
+                    _ => ReactorCell::Air,
+                };
+            }
+
+            if chunk_coords.y == -1 {
+                for x in (0..CHUNK_SIZE).step_by(8) {
+                    cells[x as usize] = ReactorCell::Bomb;
+                }
+            }
+
+            Chunk::new(cells, ReactorEnergy::default())
+        },
+    );
+}
+
+fn count_meltdowns(
+    errors: Res<PowderkegErrors<ReactorCell>>,
+    policy: Res<OutOfWorldPolicy>,
+    mut count: ResMut<MeltdownCount>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    world: PowderkegWorld<ReactorCell, CHUNK_SIZE>,
+) {
+    if !errors.is_changed() && !policy.is_changed() {
+        return;
+    }
+
+    for error in errors.errors.iter() {
+        if let PowderkegError::Cell(ReactorError::Meltdown) = error.error {
+            info!("Meltdown at {}", error.point);
+            count.0 += 1;
+        }
+    }
+
+    let reactors = world.count_matching(IRect::new(-2 * CHUNK_SIZE, -CHUNK_SIZE, 2 * CHUNK_SIZE - 1, CHUNK_SIZE - 1), |cell| *cell == ReactorCell::Reactor);
+
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.title = format!("Powderkeg Reactor Example ({reactors} reactors, {} meltdowns, blasts outside the world {:?}, P to change)", count.0, *policy);
+    }
+}
+
+/// O toggles an empty border of air around the world, sand falling off the bottom edge leaves the world through it.
+fn toggle_border(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut border: ResMut<BorderBehavior<ReactorCell>>,
+    mut chunks: Query<&mut Chunk<ReactorCell, CHUNK_SIZE>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyO) {
+        return;
+    }
+
+    *border = match *border {
+        BorderBehavior::Error => BorderBehavior::empty(ReactorCell::Air),
+        _ => BorderBehavior::Error,
+    };
+
+    // Sand resting on the bottom edge only notices the border once woken.
+    for mut chunk in chunks.iter_mut() {
+        chunk.mark_dirty();
+    }
+}
+
+fn cycle_out_of_world_policy(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut policy: ResMut<OutOfWorldPolicy>,
+) {
+    if keys.just_pressed(KeyCode::KeyP) {
+        *policy = match *policy {
+            OutOfWorldPolicy::Error => OutOfWorldPolicy::Skip,
+            OutOfWorldPolicy::Skip => OutOfWorldPolicy::Spawn,
+            OutOfWorldPolicy::Spawn => OutOfWorldPolicy::Error,
+        };
+    }
+}
@@ -0,0 +1,187 @@
+use std::convert::Infallible;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkCoords, SpawnChunkGrid}, grid::GridSnapshot, save::{load_world, save_world}, stain::Stainable, world::PowderkegWorld, PowderkegError, PowderkegPlugin, PowderkegSet};
+use image::Rgba;
+use serde::{Deserialize, Serialize};
+
+const CHUNK_SIZE: i32 = 32;
+const SAVE_PATH: &str = "powderkeg_save.ron";
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Renderable)]
+pub enum SaveCell {
+    #[color(Color::BEIGE)]
+    Sand,
+    #[default]
+    #[color(25, 25, 112)]
+    Air,
+}
+
+impl Cell for SaveCell {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() != SaveCell::Sand {
+            return Ok(TickSuccess::Stable);
+        }
+
+        let below = input.origin + IVec2::NEG_Y;
+
+        if input.grid.get(below).is_ok_and(|cell| *cell == SaveCell::Air) {
+            input.grid.swap(input.origin, below)?;
+            input.grid.stain_around(input.origin, 1);
+
+            return Ok(TickSuccess::Unstable);
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, -1, 0, 0)
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: String::from("Powderkeg Save Example (S to save, L to load, E to export images, Z to undo a stroke)"),
+                        ..default()
+                    }),
+                    ..default()
+                })
+        )
+        .add_plugins(PowderkegPlugin::<SaveCell, CHUNK_SIZE>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, ((undo_stroke, paint_sand).chain(), save_and_load, export_images, log_run_length_sizes).before(PowderkegSet::Tick))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    commands.spawn_chunk_grid::<SaveCell, CHUNK_SIZE>(
+        -1..1,
+        -1..1,
+        Transform::default().with_scale(Vec3::splat(8.0)),
+        false,
+        |coords| {
+            // Exported images can be edited and are loaded back at startup, bright pixels become sand.
+            let Ok(image) = image::open(image_path(coords)) else {
+                return Chunk::full_copied(SaveCell::Air, ());
+            };
+
+            Chunk::from_image(&image.to_rgba8(), |Rgba([red, ..])| {
+                if red > 128 { SaveCell::Sand } else { SaveCell::Air }
+            }, ()).unwrap_or_else(|error| {
+                error!("Failed to load {}: {error}", image_path(coords));
+                Chunk::full_copied(SaveCell::Air, ())
+            })
+        },
+    );
+}
+
+fn image_path(coords: IVec2) -> String {
+    format!("powderkeg_chunk_{}_{}.png", coords.x, coords.y)
+}
+
+fn export_images(
+    keys: Res<ButtonInput<KeyCode>>,
+    chunks: Query<(&ChunkCoords<CHUNK_SIZE>, &Chunk<SaveCell, CHUNK_SIZE>)>,
+) {
+    if !keys.just_pressed(KeyCode::KeyE) {
+        return;
+    }
+
+    for (coords, chunk) in chunks.iter() {
+        match chunk.to_image().save(image_path(coords.0)) {
+            Ok(()) => info!("Exported {}", image_path(coords.0)),
+            Err(error) => error!("Failed to export {}: {error}", image_path(coords.0)),
+        }
+    }
+}
+
+/// Logs how much smaller each chunk's cells are as runs when saving, mostly air chunks shrink the most.
+fn log_run_length_sizes(
+    keys: Res<ButtonInput<KeyCode>>,
+    chunks: Query<(&ChunkCoords<CHUNK_SIZE>, &Chunk<SaveCell, CHUNK_SIZE>)>,
+) {
+    if !keys.just_pressed(KeyCode::KeyS) {
+        return;
+    }
+
+    for (coords, chunk) in chunks.iter() {
+        let (Ok(cells), Ok(runs)) = (ron::to_string(chunk.cells()), ron::to_string(&chunk.to_rle())) else {
+            continue;
+        };
+
+        info!("Chunk {} cells take {} bytes, {} as runs", coords.0, cells.len(), runs.len());
+    }
+}
+
+/// Saves every chunk to a RON file, loading replaces the whole chunk grid with the saved one.
+fn save_and_load(world: &mut World) {
+    let keys = world.resource::<ButtonInput<KeyCode>>();
+    let (save, load) = (keys.just_pressed(KeyCode::KeyS), keys.just_pressed(KeyCode::KeyL));
+
+    if save {
+        match save_world::<SaveCell, CHUNK_SIZE>(world, SAVE_PATH) {
+            Ok(saved) => info!("Saved {saved} chunks to {SAVE_PATH}"),
+            Err(error) => error!("Failed to save: {error}"),
+        }
+    }
+
+    if load {
+        if let Err(error) = load_world::<SaveCell, CHUNK_SIZE>(world, SAVE_PATH) {
+            error!("Failed to load: {error}");
+        }
+    }
+}
+
+/// Keeps the world as it was before the last stroke started and puts it back on Z.
+fn undo_stroke(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut before: Local<Option<GridSnapshot<SaveCell>>>,
+    mut world: PowderkegWorld<SaveCell, CHUNK_SIZE>,
+) {
+    if buttons.just_pressed(MouseButton::Left) {
+        *before = Some(world.snapshot_region(IRect::new(-CHUNK_SIZE, -CHUNK_SIZE, CHUNK_SIZE - 1, CHUNK_SIZE - 1)));
+    }
+
+    if keys.just_pressed(KeyCode::KeyZ) {
+        if let Some(snapshot) = before.take() {
+            world.restore(&snapshot);
+        }
+    }
+}
+
+fn paint_sand(
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut world: PowderkegWorld<SaveCell, CHUNK_SIZE>,
+) {
+    if !buttons.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (camera, camera_transform) = cameras.single();
+
+    let Some(point) = windows.single()
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+        .and_then(|position| world.world_point(position))
+    else {
+        return;
+    };
+
+    world.paint_circle(point, 2, SaveCell::Sand, |old| *old == SaveCell::Air);
+}
@@ -1,14 +1,15 @@
-use std::convert::Infallible;
+use std::{convert::Infallible, time::Duration};
 
-use bevy::{prelude::*, diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin}, math::IVec2, render::color::Color, window::{PresentMode, PrimaryWindow}};
-use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkBundle, ChunkCoords}, grid::Grid, simulation::PowderkegTickRate, stain::Stainable, viewer::DrawStained, PowderkegError, PowderkegPlugin, PowderkegSet};
+use bevy::{prelude::*, diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin}, input::mouse::MouseWheel, math::IVec2, render::{color::Color, render_asset::RenderAssetUsages, render_resource::{Extent3d, TextureDimension, TextureFormat}, texture::ImageSampler}, window::{PresentMode, PrimaryWindow}};
+use powderkeg::{area::StainOrder, cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, SpawnChunkGrid}, grid::Grid, palette::PalettedChunk, simulation::{ActiveRegion, ASLEEP_CHUNKS, AWAKE_CHUNKS, DEFERRED_CELLS, ChunkSpawner, ConservationCheck, MaxTicksPerFrame, PowderkegPaused, PowderkegTickRate, SimulationMode, SimulationSchedule, StepOnce, TickReport, TickTimeBudget}, stain::Stainable, viewer::{ChunkLod, ChunkLodSettings, ChunkRenderConfig, HeatmapOverlay, RenderChannel, StainGizmoConfig, TEXTURE_UPLOAD_BYTES}, rules::{try_fall, HasDensity, Solid}, PowderkegError, PowderkegPlugin, PowderkegSet};
 use rand::{distributions::{Distribution, Uniform}, rngs::SmallRng, thread_rng, Rng, SeedableRng};
 
 const CHUNK_SIZE: i32 = 64;
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq)]
 pub enum SimpleSand {
     Sand,
+    Water,
     Stone,
     #[default]
     Air,
@@ -36,7 +37,7 @@ impl Distribution<SimpleSand> for SimpleSandDistribution {
             return SimpleSand::Stone;
         }
 
-        return SimpleSand::Air;
+        SimpleSand::Air
     }
 }
 
@@ -53,61 +54,82 @@ impl Cell for SimpleSand {
     type Error = Infallible;
     type State = SimpleState;
 
-    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+    const VARIANT_COUNT: usize = 4;
+
+    fn tick<G: Stainable<Cell = Self>>(mut input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
         match input.this() {
-            SimpleSand::Sand => {
+            SimpleSand::Sand | SimpleSand::Water => {
                 let mut rng = input.state().write_arc();
 
-                if input.grid.map_cell(input.origin + IVec2::new(0, -1), |cell| matches!(cell, Self::Air))? {
-                    input.grid.stain_around(input.origin, 3);
-                    if rng.gen_bool(0.1) {
-                        return Ok(TickSuccess::Unstable)
-                    } else {
-                        input.grid.swap(input.origin, input.origin + IVec2::new(0, -1))?;
-                        return Ok(TickSuccess::Unstable);
-                    }
+                if try_fall(&mut input, &mut rng.0)?.is_some() {
+                    return Ok(TickSuccess::Unstable);
                 }
 
-                let directions = if rng.gen_bool(0.5) {
-                    &[
-                        IVec2::new(-1, -1),
-                        IVec2::new(1, -1),
-                    ]
-                } else {
-                    &[
-                        IVec2::new(1, -1),
-                        IVec2::new(-1, -1),
-                    ]
-                };
-
-                for offset in directions.iter() {
-                    if input.grid.map_cell(input.origin + *offset, |cell| matches!(cell, Self::Air))? {
-                        input.grid.swap(input.origin, input.origin + *offset)?;
-                        input.grid.stain_around(input.origin, 1);
-                        return Ok(TickSuccess::Unstable);
+                if matches!(input.this(), SimpleSand::Water) {
+                    let side = if rng.gen_bool(0.5) { IVec2::X } else { IVec2::NEG_X };
+
+                    for offset in [side, -side] {
+                        if input.grid.map_cell(input.origin + offset, |cell| matches!(cell, Self::Air))? {
+                            input.grid.swap(input.origin, input.origin + offset)?;
+                            input.grid.stain_around(input.origin, 1);
+                            return Ok(TickSuccess::Unstable);
+                        }
                     }
                 }
 
                 Ok(TickSuccess::Stable)
             },
-            SimpleSand::Stone => Ok(TickSuccess::Stable),
-            SimpleSand::Air => Ok(TickSuccess::Stable),
+            SimpleSand::Stone | SimpleSand::Air => Ok(TickSuccess::Stable),
         }
     }
 
     fn range(&self) -> IRect {
         IRect::new(-1, -1, 1, 0)
     }
+
+    fn is_inert(&self) -> bool {
+        matches!(self, SimpleSand::Stone)
+    }
+}
+
+impl Solid for SimpleSand {
+    fn is_solid(&self) -> bool {
+        matches!(self, SimpleSand::Sand | SimpleSand::Stone)
+    }
+}
+
+impl HasDensity for SimpleSand {
+    fn density(&self) -> f32 {
+        match self {
+            SimpleSand::Air => 0.0,
+            SimpleSand::Water => 1.0,
+            SimpleSand::Sand => 2.0,
+            SimpleSand::Stone => 3.0,
+        }
+    }
 }
 
 impl Renderable for SimpleSand {
     fn to_color(&self, _: IVec2) -> Color {
         match self {
             SimpleSand::Sand => Color::BEIGE,
+            SimpleSand::Water => Color::TEAL,
             SimpleSand::Stone => Color::GRAY,
             SimpleSand::Air => Color::BLACK,
         }
     }
+
+    fn is_transparent(&self) -> bool {
+        matches!(self, SimpleSand::Air)
+    }
+
+    fn channel(&self, channel: RenderChannel, _: IVec2) -> Option<f32> {
+        match (channel, self) {
+            (RenderChannel::Temperature, SimpleSand::Sand) => Some(0.8),
+            (RenderChannel::Temperature, SimpleSand::Stone) => Some(0.2),
+            _ => None,
+        }
+    }
 }
 
 fn main() {
@@ -128,12 +150,27 @@ fn main() {
         .add_plugins(FrameTimeDiagnosticsPlugin)
         .add_systems(Startup, setup)
         .add_systems(Update, update_title)
+        .add_systems(Update, toggle_channel)
+        .add_systems(Update, cycle_stain_order)
+        .add_systems(Update, toggle_schedule)
+        .add_systems(Update, toggle_double_buffered)
+        .add_systems(Update, toggle_stain_gizmos)
+        .add_systems(Update, toggle_heatmap)
+        .add_systems(Update, toggle_time_budget)
+        .add_systems(Update, toggle_chunk_filtering)
+        .add_systems(Update, toggle_paused)
+        .add_systems(Update, zoom_camera)
+        .add_systems(Update, log_chunk_memory)
+        .add_systems(Update, melt_stone.before(PowderkegSet::Tick))
+        .add_systems(Update, clear_world.before(PowderkegSet::Tick))
+        .add_systems(Update, follow_camera.before(PowderkegSet::Tick))
         .add_systems(Update, paint_sand.before(PowderkegSet::Tick))
         .run();
 }
 
 fn setup(
     mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
 ) {
     let mut rng = thread_rng();
 
@@ -145,50 +182,328 @@ fn setup(
 
     commands.spawn(Camera2dBundle::default());
 
+    commands.spawn(SpriteBundle {
+        texture: images.add(starfield(&mut rng, 256)),
+        sprite: Sprite {
+            custom_size: Some(Vec2::splat(2048.0)),
+            ..default()
+        },
+        transform: Transform::from_xyz(0.0, 0.0, -1.0),
+        ..default()
+    });
+
+    // Faster than most frame rates, slow frames run up to three ticks to catch up.
     commands.insert_resource(PowderkegTickRate(64.0));
+    commands.insert_resource(MaxTicksPerFrame(3));
+    commands.insert_resource(ChunkLodSettings { min_pixels_per_cell: 1.0 });
 
-    commands
-        .spawn(SpatialBundle {
-            transform: Transform::default().with_scale(Vec3::splat(2.0)),
-            ..Default::default()
-        })
-        .with_children(|children| {
-            for cx in -3..=3 {
-                for cy in -3..=3 {
-                    let state = SimpleState(SmallRng::from_rng(&mut rng).unwrap());
-                    let chunk = Chunk::full_random(&mut rng, &distribution, state);
-
-                    let chunk_coords = IVec2::new(cx, cy);
-        
-                    children.spawn((
-                        ChunkBundle::<SimpleSand, CHUNK_SIZE> {
-                            chunk,
-                            coords: ChunkCoords(chunk_coords),
-                            transform: TransformBundle::from_transform(Transform::from_translation(chunk_coords.as_vec2().extend(0.0) * CHUNK_SIZE as f32)),
-                            ..default()
-                        },
-                        DrawStained,
-                    ));
-                }
+    if cfg!(debug_assertions) {
+        commands.insert_resource(ConservationCheck::<SimpleSand>::new(|cell| matches!(cell, SimpleSand::Sand | SimpleSand::Water)));
+    }
+
+    let world = commands.spawn_chunk_grid::<SimpleSand, CHUNK_SIZE>(
+        -3..=3,
+        -3..=3,
+        Transform::default().with_scale(Vec3::splat(2.0)),
+        true,
+        |chunk_coords| {
+            let state = SimpleState(SmallRng::from_rng(&mut rng).unwrap());
+
+            // A floor of stone a whole chunk deep, stone is inert so the sand piling up on it never wakes the chunks.
+            if chunk_coords.y == -3 {
+                return Chunk::full_copied(SimpleSand::Stone, state).without_initial_stain();
             }
-        });
+
+            Chunk::full_random(&mut rng, &distribution, state)
+        },
+    );
+
+    commands.insert_resource(
+        ChunkSpawner::<SimpleSand, CHUNK_SIZE>::new(
+            |chunk_coords| {
+                let cell = if chunk_coords.y < -6 { SimpleSand::Stone } else { SimpleSand::Air };
+
+                Chunk::full_copied(cell, SimpleState::default()).without_initial_stain()
+            },
+            |cell| matches!(cell, SimpleSand::Sand),
+        )
+        .with_parent(world)
+    );
+
+    commands.insert_resource(ChunkGridRoot(world));
+}
+
+/// The parent of every chunk, which places the grid in the world.
+#[derive(Resource)]
+struct ChunkGridRoot(Entity);
+
+/// Only ticks the chunks the camera can see, and a few cells around them.
+fn follow_camera(
+    mut commands: Commands,
+    region: Option<ResMut<ActiveRegion>>,
+    root: Option<Res<ChunkGridRoot>>,
+    transforms: Query<&GlobalTransform>,
+    cameras: Query<(&GlobalTransform, &OrthographicProjection), With<Camera>>,
+) {
+    let Some(grid_transform) = root.and_then(|root| transforms.get(root.0).ok()) else {
+        return;
+    };
+
+    let Ok((camera, projection)) = cameras.get_single() else {
+        return;
+    };
+
+    let center = camera.translation().truncate();
+    let view = Rect::from_corners(projection.area.min + center, projection.area.max + center);
+
+    let active = ActiveRegion::from_view::<CHUNK_SIZE>(view, grid_transform, 16);
+
+    match region {
+        Some(mut region) => {
+            region.set_if_neq(active);
+        },
+        None => commands.insert_resource(active),
+    }
+}
+
+fn starfield(rng: &mut impl Rng, size: u32) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 16, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    );
+
+    for _ in 0..size {
+        let index = rng.gen_range(0..(size * size) as usize);
+        let brightness = rng.gen_range(128..=255);
+
+        image.data[4 * index..4 * index + 3].fill(brightness);
+    }
+
+    image
 }
 
 fn update_title(
     diagnostics: Res<DiagnosticsStore>,
+    lods: Query<&ChunkLod>,
+    report: Res<TickReport<SimpleSand>>,
     mut windows: Query<&mut Window, With<PrimaryWindow>>,
 ) {
     if let Ok(mut window) = windows.get_single_mut() {
         if let Some(fps) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS) {
             if let Some(fps) = fps.smoothed() {
-                window.title = format!("Powderkeg Simple Example ({:.0} fps)", fps);
+                let reduced = lods.iter().filter(|lod| **lod == ChunkLod::Reduced).count();
+                let uploaded = diagnostics.get(&TEXTURE_UPLOAD_BYTES).and_then(|bytes| bytes.smoothed()).unwrap_or_default();
+                let awake = diagnostics.get(&AWAKE_CHUNKS).and_then(|chunks| chunks.value()).unwrap_or_default();
+                let asleep = diagnostics.get(&ASLEEP_CHUNKS).and_then(|chunks| chunks.value()).unwrap_or_default();
+                let deferred = diagnostics.get(&DEFERRED_CELLS).and_then(|cells| cells.smoothed()).unwrap_or_default();
+
+                window.title = format!(
+                    "Powderkeg Simple Example ({:.0} fps, {} cells ticked in {:.1?} with {:.0} across chunk edges, {}/{} chunks reduced, {} awake and {} asleep, {:.1} KiB uploaded per frame)",
+                    fps,
+                    report.cells_ticked,
+                    report.duration,
+                    deferred,
+                    reduced,
+                    lods.iter().len(),
+                    awake,
+                    asleep,
+                    uploaded / 1024.0,
+                );
             }
         }
     }
 }
 
+/// P pauses and resumes, space steps a single tick while paused.
+fn toggle_paused(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut paused: ResMut<PowderkegPaused>,
+    mut steps: EventWriter<StepOnce>,
+) {
+    if keys.just_pressed(KeyCode::KeyP) {
+        paused.0 = !paused.0;
+    }
+
+    if paused.0 && keys.just_pressed(KeyCode::Space) {
+        steps.send(StepOnce);
+    }
+}
+
+/// M logs how much memory the chunks would take stored with a palette rather than a cell each.
+fn log_chunk_memory(
+    keys: Res<ButtonInput<KeyCode>>,
+    chunks: Query<&Chunk<SimpleSand, CHUNK_SIZE>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyM) {
+        return;
+    }
+
+    let cells: usize = chunks.iter().map(|chunk| std::mem::size_of_val(chunk.cells())).sum();
+    let paletted: usize = chunks.iter().map(|chunk| PalettedChunk::from_chunk(chunk).heap_bytes()).sum();
+
+    info!("{} chunks take {} KiB of cells, {} KiB paletted", chunks.iter().len(), cells / 1024, paletted / 1024);
+}
+
+/// R melts every stone into water.
+fn melt_stone(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut chunks: Query<&mut Chunk<SimpleSand, CHUNK_SIZE>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyR) {
+        return;
+    }
+
+    for mut chunk in chunks.iter_mut() {
+        for (_, cell) in chunk.iter_mut() {
+            if *cell == SimpleSand::Stone {
+                *cell = SimpleSand::Water;
+            }
+        }
+    }
+}
+
+/// C clears every chunk to air.
+fn clear_world(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut chunks: Query<&mut Chunk<SimpleSand, CHUNK_SIZE>>,
+) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        for mut chunk in chunks.iter_mut() {
+            chunk.clear();
+        }
+    }
+}
+
+fn cycle_stain_order(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut order: ResMut<StainOrder>,
+) {
+    if keys.just_pressed(KeyCode::KeyO) {
+        *order = match *order {
+            StainOrder::Random => StainOrder::RowMajor,
+            StainOrder::RowMajor => StainOrder::BottomUp,
+            StainOrder::BottomUp => StainOrder::Random,
+        };
+
+        info!("Stain order {:?}", *order);
+    }
+}
+
+/// B switches to the checkerboard schedule and back, under which far fewer cells are left to tick across chunk edges.
+fn toggle_schedule(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut schedule: ResMut<SimulationSchedule>,
+) {
+    if keys.just_pressed(KeyCode::KeyB) {
+        *schedule = match *schedule {
+            SimulationSchedule::Deferred => SimulationSchedule::Checkerboard,
+            SimulationSchedule::Checkerboard => SimulationSchedule::Deferred,
+        };
+
+        info!("Simulation schedule {:?}", *schedule);
+    }
+}
+
+/// D switches between ticking cells in place and double-buffered, where every cell reads the world as the tick found it
+/// rather than what cells before it wrote.
+fn toggle_double_buffered(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mode: ResMut<SimulationMode<SimpleSand>>,
+) {
+    if keys.just_pressed(KeyCode::KeyD) {
+        *mode = match *mode {
+            SimulationMode::InPlace => SimulationMode::double_buffered(),
+            SimulationMode::DoubleBuffered(_) => SimulationMode::InPlace,
+        };
+
+        info!("Double buffered {}", matches!(*mode, SimulationMode::DoubleBuffered(_)));
+    }
+}
+
+/// G shows how busy each chunk is in place of its cells and back.
+fn toggle_heatmap(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut heatmap: ResMut<HeatmapOverlay>,
+) {
+    if keys.just_pressed(KeyCode::KeyG) {
+        heatmap.0 = !heatmap.0;
+    }
+}
+
+/// L limits the ticks of a frame to 8ms and lifts the limit again, leaving cells that miss out for the next frame.
+fn toggle_time_budget(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    budget: Option<Res<TickTimeBudget>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyL) {
+        return;
+    }
+
+    if budget.is_some() {
+        commands.remove_resource::<TickTimeBudget>();
+        info!("Tick time budget lifted");
+    } else {
+        commands.insert_resource(TickTimeBudget(Duration::from_millis(8)));
+        info!("Tick time budget 8ms");
+    }
+}
+
+/// F switches the chunks between crisp and smoothed cells, the rest of the app keeps nearest filtering.
+fn toggle_chunk_filtering(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut config: ResMut<ChunkRenderConfig>,
+    mut linear: Local<bool>,
+) {
+    if keys.just_pressed(KeyCode::KeyF) {
+        *linear = !*linear;
+        config.sampler = if *linear { ImageSampler::linear() } else { ImageSampler::nearest() };
+    }
+}
+
+/// H hides the stain overlay and shows it again.
+fn toggle_stain_gizmos(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut config: ResMut<StainGizmoConfig>,
+) {
+    if keys.just_pressed(KeyCode::KeyH) {
+        config.enabled = !config.enabled;
+    }
+}
+
+fn zoom_camera(
+    mut wheel: EventReader<MouseWheel>,
+    mut projections: Query<&mut OrthographicProjection>,
+) {
+    let scroll: f32 = wheel.read().map(|event| event.y).sum();
+
+    if scroll == 0.0 {
+        return;
+    }
+
+    for mut projection in projections.iter_mut() {
+        projection.scale = (projection.scale * 1.1f32.powf(-scroll)).clamp(0.25, 4.0);
+    }
+}
+
+fn toggle_channel(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut channel: ResMut<RenderChannel>,
+) {
+    if keys.just_pressed(KeyCode::KeyT) {
+        *channel = match *channel {
+            RenderChannel::Color => RenderChannel::Temperature,
+            _ => RenderChannel::Color,
+        };
+    }
+}
+
 fn paint_sand(
     buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
     windows: Query<&Window, With<PrimaryWindow>>,
     cameras: Query<(&Camera, &GlobalTransform)>,
     mut chunks: Query<(&mut Chunk<SimpleSand, CHUNK_SIZE>, &GlobalTransform)>,
@@ -199,12 +514,14 @@ fn paint_sand(
         return;
     };
 
-    let cell = if buttons.pressed(MouseButton::Left) {
-        SimpleSand::Sand
+    let (cell, only_air) = if buttons.pressed(MouseButton::Left) && keys.pressed(KeyCode::ShiftLeft) {
+        (SimpleSand::Water, true)
+    } else if buttons.pressed(MouseButton::Left) {
+        (SimpleSand::Sand, true)
     } else if buttons.pressed(MouseButton::Right) {
-        SimpleSand::Air
+        (SimpleSand::Air, false)
     } else if buttons.pressed(MouseButton::Middle) {
-        SimpleSand::Stone
+        (SimpleSand::Stone, false)
     } else {
         return;
     };
@@ -215,11 +532,7 @@ fn paint_sand(
         let local_rect = IRect::from_corners(local - 3, local + 3);
 
         if !Chunk::<SimpleSand, CHUNK_SIZE>::area().intersect(local_rect).is_empty() {
-            for x in (local.x - 3)..=(local.x + 3) {
-                for y in (local.y - 3)..=(local.y + 3) {
-                    chunk.map_cell_mut(IVec2::new(x, y), |old| *old = cell).ok();
-                }
-            }
+            chunk.paint_rect(local_rect, cell, |old| !only_air || matches!(old, SimpleSand::Air));
 
             chunk.stain_around(local, 5);
         }
@@ -0,0 +1,157 @@
+use std::{collections::HashMap, convert::Infallible, sync::{Arc, Mutex}};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, palette::PalettedChunk, stain::Stainable, streaming::ChunkStreamer, PowderkegError, PowderkegPlugin, PowderkegSet};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const CHUNK_SIZE: i32 = 32;
+const CAMERA_SPEED: f32 = 512.0;
+
+/// Sand settling on rolling hills that go on forever, chunks are streamed in around the camera as it moves with the
+/// arrow keys. Chunks left behind are kept in memory with a palette and come back as they were left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HillCell {
+    Sand,
+    Stone,
+    #[default]
+    Air,
+}
+
+impl Cell for HillCell {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() != HillCell::Sand {
+            return Ok(TickSuccess::Stable);
+        }
+
+        let below = input.origin + IVec2::NEG_Y;
+
+        if input.grid.get(below).is_ok_and(|cell| *cell == HillCell::Air) {
+            input.grid.swap(input.origin, below)?;
+            input.grid.stain_around(input.origin, 1);
+
+            return Ok(TickSuccess::Unstable);
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, -1, 0, 0)
+    }
+
+    fn is_inert(&self) -> bool {
+        *self == HillCell::Stone
+    }
+}
+
+impl Renderable for HillCell {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            HillCell::Sand => Color::BEIGE,
+            HillCell::Stone => Color::DARK_GRAY,
+            HillCell::Air => Color::MIDNIGHT_BLUE,
+        }
+    }
+}
+
+/// The chunks streamed out, by their coordinates.
+#[derive(Resource, Clone, Default)]
+struct StoredChunks(Arc<Mutex<HashMap<IVec2, PalettedChunk<HillCell, CHUNK_SIZE>>>>);
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: String::from("Powderkeg Stream Example"),
+                        ..default()
+                    }),
+                    ..default()
+                })
+        )
+        .add_plugins(PowderkegPlugin::<HillCell, CHUNK_SIZE>::new().tick_rate(32.0))
+        .add_systems(Startup, setup)
+        .add_systems(Update, move_camera.before(PowderkegSet::Tick))
+        .add_systems(Update, update_title.after(PowderkegSet::Tick))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+) {
+    let camera = commands.spawn(Camera2dBundle::default()).id();
+    let world = commands.spawn(SpatialBundle::from_transform(Transform::default().with_scale(Vec3::splat(4.0)))).id();
+
+    let stored = StoredChunks::default();
+    let (store, restore) = (stored.0.clone(), stored.0.clone());
+
+    commands.insert_resource(stored);
+    commands.insert_resource(
+        ChunkStreamer::<HillCell, CHUNK_SIZE>::new(camera, 4, hills)
+            .with_parent(world)
+            .with_storage(
+                move |coords, chunk| {
+                    store.lock().unwrap().insert(coords, PalettedChunk::from_chunk(chunk));
+                },
+                move |coords| restore.lock().unwrap().remove(&coords).map(|chunk| chunk.to_chunk()),
+            ),
+    );
+}
+
+/// Stone below a wave through the world, with a scattering of sand in the air above that falls once streamed in.
+fn hills(coords: IVec2) -> Chunk<HillCell, CHUNK_SIZE> {
+    let mut rng = SmallRng::seed_from_u64(((coords.x as u64) << 32) ^ coords.y as u32 as u64);
+    let mut chunk = Chunk::full_copied(HillCell::Air, ());
+
+    chunk.fill_with(Chunk::<HillCell, CHUNK_SIZE>::area(), |local| {
+        let world = coords * CHUNK_SIZE + local;
+        let ground = (world.x as f32 / 24.0).sin() * 12.0 + (world.x as f32 / 97.0).sin() * 40.0;
+
+        if (world.y as f32) < ground {
+            HillCell::Stone
+        } else if rng.gen_bool(0.02) {
+            HillCell::Sand
+        } else {
+            HillCell::Air
+        }
+    }).unwrap();
+
+    chunk
+}
+
+fn move_camera(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+) {
+    let mut direction = Vec2::ZERO;
+
+    for (key, step) in [(KeyCode::ArrowLeft, Vec2::NEG_X), (KeyCode::ArrowRight, Vec2::X), (KeyCode::ArrowDown, Vec2::NEG_Y), (KeyCode::ArrowUp, Vec2::Y)] {
+        if keys.pressed(key) {
+            direction += step;
+        }
+    }
+
+    for mut camera in cameras.iter_mut() {
+        camera.translation += (direction * CAMERA_SPEED * time.delta_seconds()).extend(0.0);
+    }
+}
+
+fn update_title(
+    chunks: Query<(), With<Chunk<HillCell, CHUNK_SIZE>>>,
+    stored: Res<StoredChunks>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.title = format!(
+            "Powderkeg Stream Example ({} chunks spawned, {} stored, arrow keys to move)",
+            chunks.iter().len(),
+            stored.0.lock().unwrap().len(),
+        );
+    }
+}
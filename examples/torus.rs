@@ -0,0 +1,113 @@
+use std::convert::Infallible;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, SpawnChunkGrid}, simulation::{ConservationCheck, PowderkegTickRate, WorldTopology}, stain::Stainable, PowderkegError, PowderkegPlugin, PowderkegSet};
+
+const CHUNK_SIZE: i32 = 32;
+
+/// Sand drifting down and to the right forever, falling off the bottom edge onto the top and off the right edge onto
+/// the left. None of it is lost on the way, which the conservation check confirms every tick.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum TorusCell {
+    Sand,
+    #[default]
+    Air,
+}
+
+impl Cell for TorusCell {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() != TorusCell::Sand {
+            return Ok(TickSuccess::Stable);
+        }
+
+        for offset in [IVec2::new(0, -1), IVec2::new(1, -1)] {
+            let target = input.origin + offset;
+
+            if input.grid.get(target).is_ok_and(|cell| *cell == TorusCell::Air) {
+                input.grid.swap(input.origin, target)?;
+                input.grid.stain_around(input.origin, 1);
+
+                return Ok(TickSuccess::Unstable);
+            }
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-1, -1, 1, 1)
+    }
+}
+
+impl Renderable for TorusCell {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            TorusCell::Sand => Color::BEIGE,
+            TorusCell::Air => Color::BLACK,
+        }
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: String::from("Powderkeg Torus Example"),
+                        ..default()
+                    }),
+                    ..default()
+                })
+        )
+        .add_plugins(PowderkegPlugin::<TorusCell, CHUNK_SIZE>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, report_violations.after(PowderkegSet::Tick))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    commands.insert_resource(PowderkegTickRate(32.0));
+    commands.insert_resource(WorldTopology::Toroidal { min: IVec2::new(-1, -1), max: IVec2::new(1, 1) });
+    commands.insert_resource(ConservationCheck::<TorusCell>::new(|cell| *cell == TorusCell::Sand));
+
+    commands.spawn_chunk_grid::<TorusCell, CHUNK_SIZE>(
+        -1..1,
+        -1..1,
+        Transform::default().with_scale(Vec3::splat(4.0)),
+        false,
+        |chunk_coords| {
+            let mut chunk = Chunk::full_copied(TorusCell::Air, ());
+
+            // A block of sand in the bottom left chunk, right up against the bottom edge of the world.
+            if chunk_coords == IVec2::new(-1, -1) {
+                if let Err(error) = chunk.fill_rect(IRect::new(CHUNK_SIZE / 4, 0, 3 * CHUNK_SIZE / 4 - 1, CHUNK_SIZE / 2 - 1), TorusCell::Sand) {
+                    error!("Failed to pour sand: {error}");
+                }
+            }
+
+            chunk
+        },
+    );
+}
+
+fn report_violations(
+    check: Res<ConservationCheck<TorusCell>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !check.is_changed() {
+        return;
+    }
+
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.title = format!("Powderkeg Torus Example ({} ticks lost or gained sand)", check.violations);
+    }
+}
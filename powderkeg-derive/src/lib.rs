@@ -0,0 +1,104 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, punctuated::Punctuated, spanned::Spanned, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit, Token, Variant};
+
+/// Derives `Renderable` for an enum from a `#[color(...)]` on every variant, holding one of:
+///
+/// - a color expression such as `Color::BEIGE` or `Color::rgb(0.2, 0.4, 0.8)`
+/// - three or four RGBA components, all integers for `0..=255` or all floats for `0.0..=1.0`, alpha defaulting to opaque
+/// - a closure taking references to the variant's fields in order, such as `|density: &u8| Color::rgb(...)`
+#[proc_macro_derive(Renderable, attributes(color))]
+pub fn derive_renderable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    renderable(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn renderable(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(input.span(), "Renderable can only be derived for enums"));
+    };
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let arms = data.variants.iter().map(variant_arm).collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        impl #impl_generics ::powderkeg::cell::Renderable for #name #type_generics #where_clause {
+            fn to_color(&self, _point: ::bevy::math::IVec2) -> ::bevy::render::color::Color {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+fn variant_arm(variant: &Variant) -> syn::Result<TokenStream2> {
+    let ident = &variant.ident;
+
+    let attribute = variant.attrs
+        .iter()
+        .find(|attribute| attribute.path().is_ident("color"))
+        .ok_or_else(|| syn::Error::new(variant.span(), "every variant needs a #[color(...)]"))?;
+
+    let args: Vec<_> = attribute.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?.into_iter().collect();
+
+    // Fields are only bound for closures, which are called with them in order.
+    let [Expr::Closure(closure)] = args.as_slice() else {
+        let pattern = match &variant.fields {
+            Fields::Named(_) => quote!(Self::#ident { .. }),
+            Fields::Unnamed(_) => quote!(Self::#ident(..)),
+            Fields::Unit => quote!(Self::#ident),
+        };
+
+        let color = color(&args, attribute)?;
+
+        return Ok(quote!(#pattern => #color,));
+    };
+
+    let (pattern, bindings) = match &variant.fields {
+        Fields::Named(fields) => {
+            let bindings: Vec<_> = fields.named.iter().map(|field| field.ident.clone().expect("named fields have names")).collect();
+
+            (quote!(Self::#ident { #(#bindings),* }), bindings)
+        },
+        Fields::Unnamed(fields) => {
+            let bindings: Vec<_> = (0..fields.unnamed.len()).map(|index| format_ident!("field_{index}")).collect();
+
+            (quote!(Self::#ident(#(#bindings),*)), bindings)
+        },
+        Fields::Unit => (quote!(Self::#ident), Vec::new()),
+    };
+
+    Ok(quote!(#pattern => (#closure)(#(#bindings),*),))
+}
+
+fn color(args: &[Expr], attribute: &Attribute) -> syn::Result<TokenStream2> {
+    if let [expr] = args {
+        if !matches!(expr, Expr::Lit(_)) {
+            return Ok(quote!(#expr));
+        }
+    }
+
+    let literals: Option<Vec<_>> = args
+        .iter()
+        .map(|arg| match arg {
+            Expr::Lit(ExprLit { lit, .. }) => Some(lit),
+            _ => None,
+        })
+        .collect();
+
+    match literals.as_deref().unwrap_or_default() {
+        [Lit::Int(r), Lit::Int(g), Lit::Int(b)] => Ok(quote!(::bevy::render::color::Color::rgba_u8(#r, #g, #b, 255))),
+        [Lit::Int(r), Lit::Int(g), Lit::Int(b), Lit::Int(a)] => Ok(quote!(::bevy::render::color::Color::rgba_u8(#r, #g, #b, #a))),
+        [Lit::Float(r), Lit::Float(g), Lit::Float(b)] => Ok(quote!(::bevy::render::color::Color::rgba(#r, #g, #b, 1.0))),
+        [Lit::Float(r), Lit::Float(g), Lit::Float(b), Lit::Float(a)] => Ok(quote!(::bevy::render::color::Color::rgba(#r, #g, #b, #a))),
+        _ => Err(syn::Error::new(
+            attribute.span(),
+            "expected a color, a closure over the variant's fields, or three or four integer or float RGBA components",
+        )),
+    }
+}
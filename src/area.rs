@@ -1,8 +1,25 @@
-use bevy::math::{IRect, IVec2};
+use bevy::{ecs::system::Resource, math::{IRect, IVec2}};
 use itertools::Itertools;
 use rand::{seq::SliceRandom, Rng};
 
+/// The order stained cells are visited within each chunk every tick.
+///
+/// This does not apply to cells whose range crosses their chunk's edge, which tick after the chunks in a serial pass
+/// that always visits them phase by phase in [`StainOrder::RowMajor`] order whatever is configured here. That pass
+/// decides which of two cells contending for each other's place across a seam wins, so its order is kept fixed.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StainOrder {
+    /// Shuffled every tick, avoiding directional bias.
+    #[default]
+    Random,
+    /// Rows from the lowest upwards, each left to right, independent of the rng which makes it useful for debugging.
+    RowMajor,
+    /// Rows from the lowest upwards with the cells of each row shuffled, so falling cells settle in one tick.
+    BottomUp,
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Area {
     Empty,
     Area(IRect),
@@ -11,10 +28,85 @@ pub enum Area {
 
 impl Area {
     pub fn is_empty(&self) -> bool {
+        matches!(self, Area::Empty)
+    }
+
+    pub fn rects(&self) -> &[IRect] {
         match self {
-            Area::Empty => true,
-            _ => false,
+            Area::Empty => &[],
+            Area::Area(area) => std::slice::from_ref(area),
+            Area::Many(areas) => areas.as_slice(),
+        }
+    }
+
+    pub fn bounding_rect(&self) -> Option<IRect> {
+        self.rects().iter().copied().reduce(|bounds, area| bounds.union(area))
+    }
+
+    pub fn push(&mut self, rect: IRect) {
+        match self {
+            Area::Empty => *self = Area::Area(rect),
+            Area::Area(area) => *self = Area::Many(vec![*area, rect]),
+            Area::Many(areas) => areas.push(rect),
+        }
+    }
+
+    pub fn intersect_rect(&self, rect: IRect) -> Area {
+        let mut intersection = Area::Empty;
+
+        for area in self.rects() {
+            let min = area.min.max(rect.min);
+            let max = area.max.min(rect.max);
+
+            if min.x <= max.x && min.y <= max.y {
+                intersection.push(IRect { min, max });
+            }
         }
+
+        intersection
+    }
+
+    /// The number of cells covered, overlapping rects are counted once for each rect they are in.
+    pub fn cell_count(&self) -> usize {
+        self.rects()
+            .iter()
+            .map(|area| (area.max.x - area.min.x + 1) as usize * (area.max.y - area.min.y + 1) as usize)
+            .sum()
+    }
+
+    /// Rewrites the rects as disjoint rects covering exactly the same cells, merging rects that share a whole edge,
+    /// so stains built from many small overlapping boxes are visited as a few larger ones, each cell once.
+    pub fn coalesce(&mut self) {
+        let Area::Many(areas) = self else {
+            return;
+        };
+
+        // Larger rects first, so the smaller ones overlapping them are cut down rather than cutting them up.
+        areas.sort_unstable_by_key(|rect| std::cmp::Reverse((rect.max.x - rect.min.x + 1) * (rect.max.y - rect.min.y + 1)));
+
+        let mut disjoint: Vec<IRect> = Vec::with_capacity(areas.len());
+
+        for rect in areas.drain(..) {
+            let mut pieces = vec![rect];
+
+            for other in disjoint.iter().filter(|other| overlaps(rect, **other)) {
+                pieces = pieces
+                    .into_iter()
+                    .flat_map(|piece| subtract_rect(piece, *other))
+                    .collect();
+
+                if pieces.is_empty() {
+                    break;
+                }
+            }
+
+            disjoint.extend(pieces);
+        }
+
+        // Merging can line rects up with others, so sweep both ways until neither merges anything.
+        while merge_along(&mut disjoint, true) | merge_along(&mut disjoint, false) {}
+
+        *self = Area::from_rects(disjoint);
     }
 
     pub fn translate(&mut self, offset: IVec2) {
@@ -33,52 +125,75 @@ impl Area {
         }
     }
 
-    pub fn apply_randomly(&self, rng: &mut impl Rng, mut f: impl FnMut(IVec2)) {
-        let mut choices: Vec<IVec2> = match self {
-            Area::Empty => return,
-            Area::Area(area) => {
-                (area.min.y..=area.max.y)
-                    .cartesian_product(area.min.x..=area.max.x)
-                    .map(|(y, x)| IVec2::new(x, y)).collect()
-            },
-            Area::Many(areas) => {
-                areas
-                    .iter()
-                    .flat_map(|area| 
-                        (area.min.y..=area.max.y)
-                            .cartesian_product(area.min.x..=area.max.x)
-                            .map(|(y, x)| IVec2::new(x, y))
-                    )
-                    .collect()
-            },
-        };
+    pub fn apply_randomly(&self, rng: &mut impl Rng, f: impl FnMut(IVec2)) {
+        self.apply_in_order(StainOrder::Random, rng, f)
+    }
 
-        choices.as_mut_slice().shuffle(rng);
+    /// As [`Area::apply_randomly`], reusing `scratch` for the points rather than allocating.
+    pub fn apply_randomly_with(&self, rng: &mut impl Rng, scratch: &mut Vec<IVec2>, f: impl FnMut(IVec2)) {
+        self.apply_in_order_with(StainOrder::Random, rng, scratch, f)
+    }
 
-        for point in choices {
-            f(point)
-        }
+    /// Visits every point in `order`, the rng is only used by orders that shuffle.
+    ///
+    /// Points covered by several overlapping rects are visited once, so no cell ticks twice in the same pass. The points
+    /// of several rects are sorted row by row before any shuffle, so the order a seed gives depends only on which points
+    /// are covered and not on how the rects split them.
+    pub fn apply_in_order(&self, order: StainOrder, rng: &mut impl Rng, f: impl FnMut(IVec2)) {
+        self.apply_in_order_with(order, rng, &mut Vec::new(), f)
     }
 
-    pub fn apply(&self, mut f: impl FnMut(IVec2)) {
-        match self {
-            Area::Area(area) => {
-                for y in area.min.y..=area.max.y {
-                    for x in area.min.x..=area.max.x {
-                        f(IVec2::new(x, y))
-                    }
-                }
-            },
-            Area::Many(areas) => {
-                for area in areas.iter() {
-                    for y in area.min.y..=area.max.y {
-                        for x in area.min.x..=area.max.x {
-                            f(IVec2::new(x, y))
-                        }
-                    }
-                }
+    /// As [`Area::apply_in_order`], but the points are collected into `scratch`, cleared first, so a caller visiting
+    /// areas every tick can keep one buffer rather than allocating each time.
+    pub fn apply_in_order_with(&self, order: StainOrder, rng: &mut impl Rng, scratch: &mut Vec<IVec2>, f: impl FnMut(IVec2)) {
+        self.collect_in_order(order, rng, scratch);
+        scratch.iter().copied().for_each(f)
+    }
+
+    pub fn apply(&self, f: impl FnMut(IVec2)) {
+        self.points().for_each(f)
+    }
+
+    /// Every point of every rect, rect by rect and row by row, points covered by several rects are repeated.
+    pub fn points(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.rects().iter().flat_map(|area| {
+            (area.min.y..=area.max.y)
+                .cartesian_product(area.min.x..=area.max.x)
+                .map(|(y, x)| IVec2::new(x, y))
+        })
+    }
+
+    /// Every point once, in a random order.
+    pub fn points_shuffled(&self, rng: &mut impl Rng) -> impl Iterator<Item = IVec2> {
+        self.points_in_order(StainOrder::Random, rng)
+    }
+
+    /// Every point once in `order`, as [`Area::apply_in_order`] visits them.
+    pub fn points_in_order(&self, order: StainOrder, rng: &mut impl Rng) -> impl Iterator<Item = IVec2> {
+        let mut choices = Vec::new();
+
+        self.collect_in_order(order, rng, &mut choices);
+
+        choices.into_iter()
+    }
+
+    fn collect_in_order(&self, order: StainOrder, rng: &mut impl Rng, choices: &mut Vec<IVec2>) {
+        choices.clear();
+        choices.extend(self.points());
+
+        // Sorting and deduplicating in place rather than through a set keeps overlapping rects allocation free too.
+        if let Area::Many(_) = self {
+            choices.sort_unstable_by_key(|point| (point.y, point.x));
+            choices.dedup();
+        }
+
+        match order {
+            StainOrder::Random => choices.as_mut_slice().shuffle(rng),
+            StainOrder::RowMajor => choices.sort_unstable_by_key(|point| (point.y, point.x)),
+            StainOrder::BottomUp => {
+                choices.as_mut_slice().shuffle(rng);
+                choices.sort_by_key(|point| point.y);
             },
-            _ => {},
         }
     }
 
@@ -90,6 +205,57 @@ impl Area {
         }
     }
 
+    /// The cells covered by both areas.
+    pub fn intersect(&self, other: &Area) -> Area {
+        let mut intersection = Vec::new();
+
+        for rect in other.rects() {
+            intersection.extend_from_slice(self.intersect_rect(*rect).rects());
+        }
+
+        Area::from_rects(intersection)
+    }
+
+    /// The cells covered by this area but not by `other`.
+    pub fn subtract(&self, other: &Area) -> Area {
+        let mut remaining = self.rects().to_vec();
+
+        for area in other.rects() {
+            remaining = remaining
+                .into_iter()
+                .flat_map(|piece| subtract_rect(piece, *area))
+                .collect();
+        }
+
+        Area::from_rects(remaining)
+    }
+
+    /// Whether every cell of `rect` is covered, found by cutting each rect out of `rect` rather than testing each cell.
+    pub fn contains_rect(&self, rect: IRect) -> bool {
+        let mut uncovered = vec![rect];
+
+        for area in self.rects() {
+            uncovered = uncovered
+                .into_iter()
+                .flat_map(|piece| subtract_rect(piece, *area))
+                .collect();
+
+            if uncovered.is_empty() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn from_rects(rects: Vec<IRect>) -> Self {
+        match rects.as_slice() {
+            [] => Area::Empty,
+            [rect] => Area::Area(*rect),
+            _ => Area::Many(rects),
+        }
+    }
+
     pub fn from_areas(stains: impl Iterator<Item = Self>) -> Self {
         let mut final_stains = Vec::new();
 
@@ -101,7 +267,7 @@ impl Area {
             }
         }
 
-        if final_stains.len() == 0 {
+        if final_stains.is_empty() {
             Self::Empty
         } else {
             Self::Many(final_stains)
@@ -109,6 +275,69 @@ impl Area {
     }
 }
 
+/// Whether inclusive rects share any cell.
+fn overlaps(a: IRect, b: IRect) -> bool {
+    a.min.x <= b.max.x && b.min.x <= a.max.x && a.min.y <= b.max.y && b.min.y <= a.max.y
+}
+
+/// Merges disjoint rects spanning the same columns and stacked one on the next when `vertical`, or spanning the same
+/// rows side by side otherwise, returning whether any were merged.
+fn merge_along(rects: &mut Vec<IRect>, vertical: bool) -> bool {
+    // The span the rects must share, then where each starts and ends along the other axis.
+    let axes = |rect: &IRect| match vertical {
+        true => (rect.min.x, rect.max.x, rect.min.y, rect.max.y),
+        false => (rect.min.y, rect.max.y, rect.min.x, rect.max.x),
+    };
+
+    let before = rects.len();
+
+    rects.sort_unstable_by_key(|rect| {
+        let (low, high, start, _) = axes(rect);
+
+        (low, high, start)
+    });
+
+    rects.dedup_by(|next, merged| {
+        let (low, high, start, _) = axes(next);
+        let (merged_low, merged_high, _, merged_end) = axes(merged);
+
+        if (low, high) != (merged_low, merged_high) || merged_end + 1 != start {
+            return false;
+        }
+
+        *merged = merged.union(*next);
+
+        true
+    });
+
+    rects.len() != before
+}
+
+/// The parts of inclusive `rect` outside `other`, as up to four disjoint rects: the rows below and above `other`
+/// and the cells either side of it in between.
+fn subtract_rect(rect: IRect, other: IRect) -> Vec<IRect> {
+    let min = rect.min.max(other.min);
+    let max = rect.max.min(other.max);
+
+    if min.x > max.x || min.y > max.y {
+        return vec![rect];
+    }
+
+    // Built from their corners directly, `IRect::new` would turn the empty pieces inside out.
+    let pieces = [
+        (rect.min, IVec2::new(rect.max.x, min.y - 1)),
+        (IVec2::new(rect.min.x, max.y + 1), rect.max),
+        (IVec2::new(rect.min.x, min.y), IVec2::new(min.x - 1, max.y)),
+        (IVec2::new(max.x + 1, min.y), IVec2::new(rect.max.x, max.y)),
+    ];
+
+    pieces
+        .into_iter()
+        .filter(|(min, max)| min.x <= max.x && min.y <= max.y)
+        .map(|(min, max)| IRect { min, max })
+        .collect()
+}
+
 impl From<Option<IRect>> for Area {
     fn from(value: Option<IRect>) -> Self {
         match value {
@@ -2,8 +2,9 @@ use std::sync::Arc;
 
 use bevy::prelude::*;
 use parking_lot::RwLock;
+use rand::{rngs::SmallRng, SeedableRng};
 
-use crate::{stain::Stainable, PowderkegError};
+use crate::{stain::Stainable, viewer::RenderChannel, PowderkegError};
 
 pub enum TickSuccess {
     Stable,
@@ -13,6 +14,11 @@ pub enum TickSuccess {
 pub struct TickInput<'g, T: Cell, G: Stainable<Cell = T>> {
     pub origin: IVec2,
     pub grid: &'g mut G,
+    /// Seeds [`TickInput::rng`], determined by the [`PowderkegRng`](crate::simulation::PowderkegRng) seed,
+    /// the tick and the origin.
+    pub seed: u64,
+    /// The [`Gravity`](crate::simulation::Gravity) of the simulation.
+    pub gravity: IVec2,
 }
 
 impl<'g, T, G> TickInput<'g, T, G> 
@@ -43,19 +49,173 @@ where
     pub fn state(&self) -> Arc<RwLock<T::State>> {
         self.grid.state_at(self.origin)
     }
+
+    /// Ticks this cell again once its neighboring chunks can be written, see [`Stainable::defer`]. A cell reaching
+    /// further than its range only some of the time can defer those ticks, returning `false` when already deferred.
+    pub fn defer(&mut self) -> bool {
+        self.grid.defer(self.origin)
+    }
+
+    /// The direction this cell should fall in, see [`Gravity`](crate::simulation::Gravity).
+    pub fn gravity(&self) -> IVec2 {
+        self.gravity
+    }
+
+    /// A random number generator for this cell's tick, the same for the same seed, tick and cell every run.
+    pub fn rng(&self) -> SmallRng {
+        SmallRng::seed_from_u64(self.seed)
+    }
+}
+
+/// The chunk states of two swapped cells, passed to [`Cell::swap_state`].
+pub enum SwapStates<'s, S> {
+    /// Both cells are in the same chunk.
+    Shared(&'s mut S),
+    /// The cells are in different chunks, `first` holding the state of the first cell's chunk.
+    Split { first: &'s mut S, second: &'s mut S },
+}
+
+impl<S> SwapStates<'_, S> {
+    /// Takes what the states keep for the cells at the chunk local `first` and `second` with `take`, then puts each
+    /// back at the other's point with `put`, wherever the two states are.
+    pub fn exchange<V>(self, first: IVec2, second: IVec2, mut take: impl FnMut(&mut S, IVec2) -> V, mut put: impl FnMut(&mut S, IVec2, V)) {
+        match self {
+            SwapStates::Shared(state) => {
+                let first_value = take(state, first);
+                let second_value = take(state, second);
+
+                put(state, second, first_value);
+                put(state, first, second_value);
+            },
+            SwapStates::Split { first: first_state, second: second_state } => {
+                let first_value = take(first_state, first);
+                let second_value = take(second_state, second);
+
+                put(second_state, second, first_value);
+                put(first_state, first, second_value);
+            },
+        }
+    }
 }
 
 pub trait Cell: Send + Sync + Sized + 'static {
     type State: Send + Sync + 'static;
     type Error: std::error::Error + Send + Sync + 'static;
 
+    /// The number of distinct variants this cell can take, used to size palettes. Defaults to unbounded.
+    const VARIANT_COUNT: usize = usize::MAX;
+
+    /// The number of phases a tick is split into, every cell in phase `0` ticks before any cell in phase `1` and so on.
+    const PHASES: u32 = 1;
+
+    /// Whether [`Cell::on_create`] and [`Cell::on_destroy`] are overridden. Writes only lock the chunk state to call
+    /// them when set, so cells without hooks write as if they did not exist.
+    const HOOKS: bool = false;
+
     fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>>;
     fn range(&self) -> IRect;
+
+    /// The range of a cell whose reach depends on its chunk's state, used instead of [`Cell::range`] to decide
+    /// whether a cell must be ticked across chunk boundaries.
+    fn range_with_state(&self, _state: &Self::State) -> IRect {
+        self.range()
+    }
+
+    /// Inert cells are never ticked, however often they are stained, until they are replaced by a cell that is not.
+    ///
+    /// Unlike returning [`TickSuccess::Stable`] this skips the cell before its range is even considered, and stains
+    /// reaching into a chunk from its neighbors only onto inert cells are dropped, so a chunk of nothing but inert
+    /// cells falls asleep and stays asleep however much moves next to it. This makes vast static regions such as
+    /// bedrock close to free.
+    fn is_inert(&self) -> bool {
+        false
+    }
+
+    /// Which of the [`Cell::VARIANT_COUNT`] variants this cell is, cells without one are not counted in histograms.
+    fn variant(&self) -> Option<usize> {
+        None
+    }
+
+    /// Moves the data the chunk states keep for the cells at the chunk local `first` and `second` after
+    /// [`Grid::swap_with_state`](crate::grid::Grid::swap_with_state) swapped them, usually through
+    /// [`SwapStates::exchange`].
+    ///
+    /// Chunk state is per chunk by default, nothing follows a cell unless this is implemented.
+    fn swap_state(_states: SwapStates<'_, Self::State>, _first: IVec2, _second: IVec2) {}
+
+    /// Called once the cell has been written at the chunk local `point` by [`Grid::replace`](crate::grid::Grid::replace)
+    /// or a fill, with the state of its chunk, for keeping counts of cells and the like in the state. Swapping cells
+    /// moves them rather than creating them, so it calls neither this nor [`Cell::on_destroy`], nor do writes through
+    /// [`Grid::get_mut`](crate::grid::Grid::get_mut) which change a cell in place.
+    ///
+    /// This runs during the simulation with the state locked for writing, locking it again from here deadlocks and so
+    /// does replacing a cell while holding the lock in [`Cell::tick`]. Only called when [`Cell::HOOKS`] is set.
+    fn on_create(&self, _point: IVec2, _state: &mut Self::State) {}
+
+    /// Called once the cell has been overwritten at the chunk local `point`, before the replacement's
+    /// [`Cell::on_create`], see there.
+    fn on_destroy(&self, _point: IVec2, _state: &mut Self::State) {}
+
+    /// The phase this cell ticks in, must be less than [`Cell::PHASES`]. Larger phases panic in debug builds and tick
+    /// in the last phase otherwise.
+    ///
+    /// A cell ticks in the phase of whatever was at its point when the tick began, so a cell that changes phase during
+    /// a tick, such as one transformed in phase `0` into a cell of phase `1`, waits for the next tick instead of ticking
+    /// twice.
+    fn phase(&self) -> u32 {
+        0
+    }
 }
 
+/// Derives [`Renderable`] for enums of cells from a `#[color(...)]` on each variant, see the `powderkeg-derive` crate.
+#[cfg(feature = "derive")]
+pub use powderkeg_derive::Renderable;
+
 pub trait Renderable
 where
     Self: Cell,
 {
+    /// Whether [`Renderable::emissive`] is overridden. Chunks of cells that never glow get no emissive texture, so
+    /// they draw as if glow did not exist.
+    const EMISSIVE: bool = false;
+
     fn to_color(&self, point: IVec2) -> Color;
+
+    /// The cell's color for cells whose look depends on their chunk's state, such as a glow from a heat field. Cells
+    /// are only redrawn once stained, so a change to the state shows as the cells around it change.
+    fn to_color_with_state(&self, point: IVec2, _state: &Self::State) -> Color {
+        self.to_color(point)
+    }
+
+    /// The cell's color as linear RGBA, this is what the chunk shader samples.
+    ///
+    /// Chunk images store it sRGB encoded and sampling decodes it back to linear, so the shader sees it as is and any
+    /// [`Color`] from `to_color_with_state` renders as specified whatever its color space.
+    fn to_linear_rgba(&self, point: IVec2, state: &Self::State) -> [f32; 4] {
+        self.to_color_with_state(point, state).as_linear_rgba_f32()
+    }
+
+    /// Transparent cells are rendered as holes, letting whatever is behind the chunk show through.
+    fn is_transparent(&self) -> bool {
+        false
+    }
+
+    /// How much the cell glows, brightening its color by that many times again up to
+    /// [`MAX_EMISSIVE`](crate::viewer::MAX_EMISSIVE). With an HDR camera and bloom glowing cells bloom. Only read
+    /// when [`Renderable::EMISSIVE`] is set.
+    fn emissive(&self, _point: IVec2) -> f32 {
+        0.0
+    }
+
+    /// The value of an auxiliary field normalized to `0.0..=1.0`, `None` if this cell does not track it.
+    fn channel(&self, _channel: RenderChannel, _point: IVec2) -> Option<f32> {
+        None
+    }
+}
+
+/// Checks a palette index against `T::VARIANT_COUNT`, in a const context this fails to compile when out of range.
+pub const fn checked_palette_index<T: Cell>(index: usize) -> usize {
+    assert!(index < T::VARIANT_COUNT, "palette index exceeds Cell::VARIANT_COUNT");
+
+    index
 }
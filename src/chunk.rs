@@ -1,19 +1,62 @@
-use std::{iter, sync::Arc};
+use std::{iter, mem, sync::Arc};
 
 use bevy::prelude::*;
+use crossbeam_channel::Sender;
 use parking_lot::RwLock;
-use rand::{distributions::Distribution, Rng};
+use image::{Rgba, RgbaImage};
+use rand::{distributions::Distribution, rngs::SmallRng, Rng, SeedableRng};
 
-use crate::{cell::{Cell, Renderable}, grid::Grid, stain::Stainable, area::Area, PowderkegError};
+use crate::{cell::{Cell, Renderable}, grid::{check_disjoint, DisjointGrid, Grid, OwnedGrid}, simulation::CellChanged, stain::{ChangeKind, StainPolicy, Stainable}, area::Area, viewer::{encode_srgba8, DrawStained, RenderChannel}, PowderkegError};
 
 #[derive(Component)]
 pub struct Chunk<T: Cell, const N: i32> {
-    data: Vec<T>,
-    pub(crate) stain: Option<IRect>,
+    /// Exactly `N * N` cells, boxed rather than a `Vec` as the length never changes.
+    data: Box<[T]>,
+    present: Option<Vec<bool>>,
+    pub(crate) stain: Area,
+    stain_policy: StainPolicy,
+    changes: Option<ChangeLog<T>>,
+    tick: u64,
+    last_modified: u64,
+    /// Consecutive ticks this chunk had nothing stained, reset by any stain.
+    idle_ticks: u32,
+    /// Cells ticked within the chunk during the current tick.
+    ticked: usize,
+    /// Local points of cells deferred to the serial pass by the cell being ticked.
+    deferred: Vec<IVec2>,
+    observer: Option<CellObserver<T>>,
+    front: Option<FrontBuffer<T>>,
     state: Arc<RwLock<T::State>>,
 }
 
+struct ChangeLog<T> {
+    classify: fn(&T, &T) -> Option<ChangeKind>,
+    changes: Vec<(IVec2, ChangeKind)>,
+}
+
+/// Decides whether a write from the first cell to the second is observed, returning copies of both if it is.
+pub(crate) type ObserveChange<T> = Arc<dyn Fn(&T, &T) -> Option<(T, T)> + Send + Sync>;
+
+/// The cells as a double-buffered tick found them, read in place of the chunk's own cells once the tick writes.
+struct FrontBuffer<T> {
+    copy: fn(&T) -> T,
+    cells: Vec<T>,
+    /// Whether a double-buffered tick is running.
+    armed: bool,
+    /// Whether `cells` was copied this tick, reads use it from the first write on.
+    current: bool,
+}
+
+/// Sends the observed writes of a tick to the simulation, which turns them into events once the tick is over.
+struct CellObserver<T: Cell> {
+    /// The world point of the chunk's origin.
+    origin: IVec2,
+    observe: ObserveChange<T>,
+    send: Sender<CellChanged<T>>,
+}
+
 #[derive(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkCoords<const N: i32>(pub IVec2);
 
 impl<const N: i32> ChunkCoords<N> {
@@ -32,6 +75,33 @@ impl<const N: i32> ChunkCoords<N> {
     pub fn world_to_chunk_and_local(world: IVec2) -> (IVec2, IVec2) {
         (world.div_euclid(IVec2::splat(N)), world.rem_euclid(IVec2::splat(N)))
     }
+
+    /// A random number generator determined entirely by `seed` and these coordinates.
+    pub fn rng(&self, seed: u64) -> SmallRng {
+        let coords = (self.0.x as u32 as u64) | ((self.0.y as u32 as u64) << 32);
+
+        SmallRng::seed_from_u64(seed ^ coords.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+    }
+}
+
+/// Splits a world rect into the coordinates of every chunk it overlaps and the part of it within that chunk,
+/// in that chunk's local frame.
+pub fn decompose_region<const N: i32>(rect: IRect) -> impl Iterator<Item = (IVec2, IRect)> {
+    let (min_chunk, _) = ChunkCoords::<N>::world_to_chunk_and_local(rect.min);
+    let (max_chunk, _) = ChunkCoords::<N>::world_to_chunk_and_local(rect.max);
+
+    (min_chunk.y..=max_chunk.y)
+        .flat_map(move |y| (min_chunk.x..=max_chunk.x).map(move |x| IVec2::new(x, y)))
+        .map(move |chunk| {
+            let offset = N * chunk;
+
+            let local = IRect {
+                min: (rect.min - offset).max(IVec2::ZERO),
+                max: (rect.max - offset).min(IVec2::splat(N - 1)),
+            };
+
+            (chunk, local)
+        })
 }
 
 #[derive(Bundle)]
@@ -46,6 +116,19 @@ where
     pub visibility: VisibilityBundle,
 }
 
+impl<T, const N: i32> ChunkBundle<T, N>
+where
+    T: Renderable + Send + Sync + 'static,
+    T::State: Send + Sync + 'static,
+{
+    /// A bundle for `chunk` placed at `coords` relative to its parent.
+    pub fn new(chunk: Chunk<T, N>, coords: ChunkCoords<N>) -> Self {
+        let transform = Transform::from_translation(coords.0.as_vec2().extend(0.0) * N as f32);
+
+        Self { chunk, coords, transform: TransformBundle::from_transform(transform), visibility: default() }
+    }
+}
+
 impl<T, const N: i32> Default for ChunkBundle<T, N>
 where
     T: Renderable + Default + Send + Sync + 'static,
@@ -56,6 +139,51 @@ where
     }
 }
 
+pub trait SpawnChunkGrid {
+    /// Spawns a parent with `parent_transform` holding a chunk for every coordinate in the ranges, returning the parent.
+    fn spawn_chunk_grid<T, const N: i32>(
+        &mut self,
+        range_x: impl Iterator<Item = i32>,
+        range_y: impl Iterator<Item = i32> + Clone,
+        parent_transform: Transform,
+        draw_stained: bool,
+        init: impl FnMut(IVec2) -> Chunk<T, N>,
+    ) -> Entity
+    where
+        T: Renderable;
+}
+
+impl SpawnChunkGrid for Commands<'_, '_> {
+    fn spawn_chunk_grid<T, const N: i32>(
+        &mut self,
+        range_x: impl Iterator<Item = i32>,
+        range_y: impl Iterator<Item = i32> + Clone,
+        parent_transform: Transform,
+        draw_stained: bool,
+        mut init: impl FnMut(IVec2) -> Chunk<T, N>,
+    ) -> Entity
+    where
+        T: Renderable,
+    {
+        self
+            .spawn(SpatialBundle::from_transform(parent_transform))
+            .with_children(|children| {
+                for cx in range_x {
+                    for cy in range_y.clone() {
+                        let chunk_coords = IVec2::new(cx, cy);
+
+                        let mut chunk = children.spawn(ChunkBundle::new(init(chunk_coords), ChunkCoords::<N>(chunk_coords)));
+
+                        if draw_stained {
+                            chunk.insert(DrawStained);
+                        }
+                    }
+                }
+            })
+            .id()
+    }
+}
+
 impl<T, const N: i32> Chunk<T, N>
 where
     T: Cell,
@@ -63,28 +191,517 @@ where
     pub fn new(data: Vec<T>, state: T::State) -> Self {
         assert_eq!(data.len(), N as usize * N as usize);
 
-        Self { data, stain: Some(Self::area()), state: Arc::new(RwLock::new(state)) }
+        Self::from_shared(data, Arc::new(RwLock::new(state)), Self::area().into(), StainPolicy::default())
+    }
+
+    /// A dense chunk sharing `state` with whatever it was taken from.
+    pub(crate) fn from_shared(data: Vec<T>, state: Arc<RwLock<T::State>>, stain: Area, stain_policy: StainPolicy) -> Self {
+        assert_eq!(data.len(), N as usize * N as usize);
+
+        Self { data: data.into_boxed_slice(), present: None, stain, stain_policy, changes: None, tick: 0, last_modified: 0, idle_ticks: 0, ticked: 0, deferred: Vec::new(), observer: None, front: None, state }
+    }
+
+    /// Creates a chunk where `None` cells are absent, they are not simulated, render transparent and
+    /// behave like the edge of the world.
+    pub fn sparse(cells: Vec<Option<T>>, state: T::State) -> Self
+    where
+        T: Default,
+    {
+        let present = cells.iter().map(Option::is_some).collect();
+        let data = cells.into_iter().map(Option::unwrap_or_default).collect();
+
+        Self { present: Some(present), ..Self::new(data, state) }
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        self.present.is_some()
+    }
+
+    pub fn is_present(&self, point: IVec2) -> bool {
+        self.index(point).is_some()
+    }
+
+    /// Whether every point of `rect` is within the chunk and present.
+    pub fn contains_rect(&self, rect: IRect) -> bool {
+        let area = Self::area();
+
+        if !(area.min.x <= rect.min.x && rect.max.x <= area.max.x && area.min.y <= rect.min.y && rect.max.y <= area.max.y) {
+            return false;
+        }
+
+        match &self.present {
+            None => true,
+            Some(present) => (rect.min.y..=rect.max.y).all(|y| (rect.min.x..=rect.max.x).all(|x| present[(N * y + x) as usize])),
+        }
+    }
+
+    /// Starts the chunk with an empty stain instead of a fully stained one, its image is still drawn in full when it
+    /// is first rendered but its cells are not ticked until something stains them.
+    pub fn without_initial_stain(mut self) -> Self {
+        self.stain = Area::Empty;
+        self
+    }
+
+    pub fn with_stain_policy(mut self, stain_policy: StainPolicy) -> Self {
+        self.stain_policy = stain_policy;
+        self
+    }
+
+    pub fn set_stain_policy(&mut self, stain_policy: StainPolicy) {
+        self.stain_policy = stain_policy;
+    }
+
+    /// Starts recording what kind of change each write made, swaps are recorded as [`ChangeKind::Moved`],
+    /// replacements are classified by `classify(old, new)` and writes through `get_mut` are recorded as
+    /// [`ChangeKind::Modified`].
+    ///
+    /// Changes accumulate until taken with [`Chunk::take_changes`]. The lighting plugin takes them every frame,
+    /// recomputing the light only around the changes that can affect it.
+    pub fn track_changes(&mut self, classify: fn(&T, &T) -> Option<ChangeKind>) {
+        self.changes = Some(ChangeLog { classify, changes: Vec::new() });
+    }
+
+    pub fn is_tracking_changes(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    pub fn changes(&self) -> &[(IVec2, ChangeKind)] {
+        self.changes.as_ref().map_or(&[], |log| log.changes.as_slice())
+    }
+
+    pub fn take_changes(&mut self) -> Vec<(IVec2, ChangeKind)> {
+        self.changes.as_mut().map_or_else(Vec::new, |log| mem::take(&mut log.changes))
+    }
+
+    pub(crate) fn record_change(&mut self, point: IVec2, kind: ChangeKind) {
+        if let Some(log) = &mut self.changes {
+            log.changes.push((point, kind));
+        }
+    }
+
+    /// Starts sending a [`CellChanged`] through `send` for each observed replacement or swap, until
+    /// [`Chunk::stop_observing`]. Writes through `get_mut` are not observed.
+    pub(crate) fn observe_changes(&mut self, coords: &ChunkCoords<N>, observe: ObserveChange<T>, send: Sender<CellChanged<T>>) {
+        self.observer = Some(CellObserver { origin: coords.0 * N, observe, send });
+    }
+
+    pub(crate) fn stop_observing(&mut self) {
+        self.observer = None;
+    }
+
+    /// Sends the write at `point` from `from` to `to` if it is observed.
+    pub(crate) fn observe(&self, point: IVec2, from: &T, to: &T) {
+        let Some(observer) = &self.observer else {
+            return;
+        };
+
+        if let Some((from, to)) = (observer.observe)(from, to) {
+            observer.send.send(CellChanged { world: observer.origin + point, from, to }).expect("channel unexpectedly closed");
+        }
+    }
+
+    pub fn state(&self) -> &Arc<RwLock<T::State>> {
+        &self.state
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Like [`DisjointGrid::get_many_mut`] for however many `points`, in the same order.
+    pub(crate) fn get_disjoint_mut(&mut self, points: &[IVec2]) -> Result<Vec<&mut T>, PowderkegError<T>> {
+        check_disjoint(points)?;
+
+        let mut indices = points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| self.index(*point).map(|index| (index, i)).ok_or(PowderkegError::LocalOutOfBounds(*point)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for point in points {
+            self.stain_point(*point);
+        }
+
+        self.touch();
+
+        indices.sort_unstable();
+
+        // Splitting off each cell in index order hands out every reference without borrowing the cells twice.
+        let mut cells: Vec<Option<&mut T>> = points.iter().map(|_| None).collect();
+        let mut rest = &mut self.data[..];
+        let mut start = 0;
+
+        for (index, i) in indices {
+            let (cell, tail) = mem::take(&mut rest)[index - start..].split_first_mut().expect("index is within the chunk");
+
+            cells[i] = Some(cell);
+            rest = tail;
+            start = index + 1;
+        }
+
+        Ok(cells.into_iter().flatten().collect())
+    }
+
+    /// The cells [`Grid::get`] reads, the front buffer while it is current.
+    fn readable(&self) -> &[T] {
+        match &self.front {
+            Some(front) if front.current => &front.cells,
+            _ => &self.data,
+        }
+    }
+
+    /// Mutable access to the raw cells, writes through this are not stained, see [`Chunk::mark_dirty`].
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        self.touch();
+        &mut self.data
+    }
+
+    /// The cells, absent ones included, as runs of equal cells row by row from the bottom, each the cell and how many
+    /// times it repeats. Far smaller than the cells themselves for chunks that are mostly one cell.
+    pub fn to_rle(&self) -> Vec<(T, u32)>
+    where
+        T: Clone + PartialEq,
+    {
+        let mut runs: Vec<(T, u32)> = Vec::new();
+
+        for cell in self.data.iter() {
+            match runs.last_mut() {
+                Some((last, length)) if last == cell => *length += 1,
+                _ => runs.push((cell.clone(), 1)),
+            }
+        }
+
+        runs
+    }
+
+    /// A dense chunk from runs made by [`Chunk::to_rle`], or `None` if the runs do not add up to exactly `N * N` cells.
+    pub fn from_rle(runs: impl IntoIterator<Item = (T, u32)>, state: T::State) -> Option<Self>
+    where
+        T: Clone,
+    {
+        let mut data = Vec::with_capacity(Self::volume());
+
+        for (cell, length) in runs {
+            if data.len() + length as usize > Self::volume() {
+                return None;
+            }
+
+            data.extend(iter::repeat_n(cell, length as usize));
+        }
+
+        (data.len() == Self::volume()).then(|| Self::new(data, state))
+    }
+
+    /// Every present cell with its local point, row by row from the bottom.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        let present = self.present.as_deref();
+
+        self.data
+            .iter()
+            .enumerate()
+            .filter(move |(index, _)| present.is_none_or(|present| present[*index]))
+            .map(|(index, cell)| (Self::point(index), cell))
+    }
+
+    /// Every present cell with its local point, row by row from the bottom. As any of them may be written the whole
+    /// chunk is stained up front, whether or not the iterator is run to the end.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (IVec2, &mut T)> {
+        self.mark_dirty();
+        self.iter_mut_unstained()
+    }
+
+    /// Like [`Chunk::iter_mut`] but stains nothing, for bulk edits where the caller stains what changed afterwards.
+    pub fn iter_mut_unstained(&mut self) -> impl Iterator<Item = (IVec2, &mut T)> {
+        self.touch();
+
+        let present = self.present.as_deref();
+
+        self.data
+            .iter_mut()
+            .enumerate()
+            .filter(move |(index, _)| present.is_none_or(|present| present[*index]))
+            .map(|(index, cell)| (Self::point(index), cell))
+    }
+
+    /// The [`PowderkegTick`](crate::simulation::PowderkegTick) of the last tick to write to this chunk, `0` if it has
+    /// not been written since it was created. Writes between ticks, such as edits from other systems, count towards
+    /// the tick before them.
+    ///
+    /// Anything handing out cells mutably counts as a write whether or not it changes them, such as
+    /// [`Grid::get_mut`](crate::grid::Grid::get_mut), [`Chunk::cells_mut`] and [`Chunk::iter_mut`]. Staining does not.
+    pub fn last_modified(&self) -> u64 {
+        self.last_modified
+    }
+
+    /// Advances the tick writes are recorded against and starts counting the cells it ticks again, the simulation
+    /// calls this for every chunk each tick.
+    pub(crate) fn set_tick(&mut self, tick: u64) {
+        self.tick = tick;
+        self.ticked = 0;
+        self.deferred.clear();
+    }
+
+    /// How many cells the last tick ticked within this chunk, none while it is asleep. Cells reaching past the chunk
+    /// that waited for the serial pass are not counted.
+    pub fn cells_ticked(&self) -> usize {
+        self.ticked
+    }
+
+    pub(crate) fn record_ticked(&mut self, ticked: usize) {
+        self.ticked += ticked;
+    }
+
+    pub(crate) fn drain_deferred(&mut self) -> std::vec::Drain<'_, IVec2> {
+        self.deferred.drain(..)
+    }
+
+    /// Whether the chunk has been idle for at least `threshold` ticks, asleep chunks are skipped by the simulation
+    /// until something stains them. A `threshold` of `0` never sleeps.
+    pub fn is_asleep(&self, threshold: u32) -> bool {
+        threshold > 0 && self.idle_ticks >= threshold
+    }
+
+    pub(crate) fn record_idle_tick(&mut self) {
+        self.idle_ticks = self.idle_ticks.saturating_add(1);
+    }
+
+    fn touch(&mut self) {
+        self.last_modified = self.tick;
+
+        if let Some(front) = self.front.as_mut().filter(|front| front.armed && !front.current) {
+            front.cells.clear();
+            front.cells.extend(self.data.iter().map(front.copy));
+            front.current = true;
+        }
+    }
+
+    /// Starts a tick where reads see the cells as the tick started while writes go to the chunk, `copy` taking the
+    /// snapshot read from on the first write. `None` reads and writes in place.
+    pub(crate) fn double_buffer(&mut self, copy: Option<fn(&T) -> T>) {
+        let Some(copy) = copy else {
+            self.front = None;
+            return;
+        };
+
+        let front = self.front.get_or_insert_with(|| FrontBuffer { copy, cells: Vec::new(), armed: false, current: false });
+
+        front.copy = copy;
+        front.armed = true;
+        front.current = false;
+    }
+
+    /// Ends a double-buffered tick, the cells written become those read.
+    pub(crate) fn swap_buffers(&mut self) {
+        if let Some(front) = &mut self.front {
+            front.armed = false;
+            front.current = false;
+        }
+    }
+
+    /// The cell at `point` for a write the caller records in the change log itself, staining it.
+    pub(crate) fn write(&mut self, point: IVec2) -> Result<&mut T, PowderkegError<T>> {
+        let index = self.write_index(point)?;
+
+        Ok(&mut self.data[index])
+    }
+
+    /// Like [`Chunk::write`], returning the index of the cell instead.
+    fn write_index(&mut self, point: IVec2) -> Result<usize, PowderkegError<T>> {
+        let index = self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+
+        self.stain_point(point);
+        self.touch();
+
+        Ok(index)
+    }
+
+    /// The cell at `point` as last written, unlike [`Grid::get`] which reads the front buffer during a double-buffered
+    /// tick. `None` if `point` is absent.
+    pub(crate) fn written(&self, point: IVec2) -> Option<&T> {
+        self.index(point).map(|index| &self.data[index])
+    }
+
+    /// Stains the whole chunk, forcing it to be redrawn and ticked.
+    pub fn mark_dirty(&mut self) {
+        self.stain(Self::area());
+    }
+
+    /// Sets every cell to `cell`, absent cells included though only present ones run the [hooks](Cell::HOOKS), and
+    /// stains the whole chunk.
+    pub fn fill(&mut self, cell: T)
+    where
+        T: Clone,
+    {
+        self.fill_cells(|| cell.clone());
+    }
+
+    /// Sets every cell to the default, absent cells included though only present ones run the [hooks](Cell::HOOKS),
+    /// and stains the whole chunk.
+    pub fn clear(&mut self)
+    where
+        T: Default,
+    {
+        self.fill_cells(T::default);
+    }
+
+    fn fill_cells(&mut self, mut cell: impl FnMut() -> T) {
+        self.touch();
+
+        if !T::HOOKS {
+            self.data.fill_with(cell);
+            self.mark_dirty();
+            return;
+        }
+
+        let mut state = self.state.write();
+
+        for (index, old) in self.data.iter_mut().enumerate() {
+            let point = Self::point(index);
+            let new = cell();
+
+            if self.present.as_ref().is_none_or(|present| present[index]) {
+                old.on_destroy(point, &mut state);
+                new.on_create(point, &mut state);
+            }
+
+            *old = new;
+        }
+
+        drop(state);
+
+        self.mark_dirty();
+    }
+
+    /// Reverses the columns of the chunk, absent cells included, and stains it entirely.
+    pub fn mirror_x(&mut self) {
+        for row in self.data.chunks_exact_mut(N as usize) {
+            row.reverse();
+        }
+
+        if let Some(present) = &mut self.present {
+            for row in present.chunks_exact_mut(N as usize) {
+                row.reverse();
+            }
+        }
+
+        self.touch();
+        self.mark_dirty();
+    }
+
+    /// Reverses the rows of the chunk, absent cells included, and stains it entirely.
+    pub fn mirror_y(&mut self) {
+        mirror_rows(&mut self.data, N as usize);
+
+        if let Some(present) = &mut self.present {
+            mirror_rows(present, N as usize);
+        }
+
+        self.touch();
+        self.mark_dirty();
+    }
+
+    pub fn mark_dirty_rect(&mut self, rect: IRect) {
+        if let Some(rect) = Area::from(rect).intersect_rect(Self::area()).bounding_rect() {
+            self.stain(rect);
+        }
+    }
+
+    /// Stains the part of a neighbor's stain that falls within this chunk, where `neighbor_coord_delta` is the
+    /// neighbor's chunk coordinates minus this chunk's and `neighbor_stain` is in the neighbor's local frame.
+    pub fn stain_from_neighbor(&mut self, neighbor_coord_delta: IVec2, neighbor_stain: &Area) {
+        let mut stain = neighbor_stain.clone();
+
+        stain.translate(N * neighbor_coord_delta);
+
+        for rect in stain.intersect_rect(Self::area()).rects() {
+            self.stain(*rect);
+        }
+    }
+
+    /// Rasterizes the in-bounds stain into `N * N` row-major bits packed into words,
+    /// the cell at `(x, y)` is bit `i % 64` of word `i / 64` where `i = N * y + x`.
+    pub fn stain_bitmask(&self) -> Vec<u64> {
+        let mut bits = vec![0u64; Self::volume().div_ceil(64)];
+
+        for point in self.stained().points() {
+            let index = (N * point.y + point.x) as usize;
+
+            bits[index / 64] |= 1 << (index % 64);
+        }
+
+        bits
     }
 
     pub const fn area() -> IRect {
         IRect { min: IVec2::splat(0), max: IVec2::splat(N - 1) }
     }
 
+    /// Clips a stain to the chunk and a chunk's width around it, keeping the stored stain bounded however large the
+    /// stain while still reaching the neighboring chunks it spills into. `None` if nothing is left.
+    pub(crate) fn clamp_stain(rect: IRect) -> Option<IRect> {
+        let (min, max) = (rect.min.max(IVec2::splat(-N)), rect.max.min(IVec2::splat(2 * N - 1)));
+
+        (min.x <= max.x && min.y <= max.y).then_some(IRect { min, max })
+    }
+
+    /// Whether every cell of the chunk within the chunk local `rect` is [inert](Cell::is_inert), staining only those
+    /// would tick nothing.
+    pub(crate) fn is_inert_within(&self, rect: IRect) -> bool {
+        let (min, max) = (rect.min.max(IVec2::ZERO), rect.max.min(IVec2::splat(N - 1)));
+
+        (min.y..=max.y).all(|y| (min.x..=max.x).all(|x| self.get(IVec2::new(x, y)).map_or(true, |cell| cell.is_inert())))
+    }
+
     pub const fn volume() -> usize {
         N as usize * N as usize
     }
 
+    fn point(index: usize) -> IVec2 {
+        IVec2::new(index as i32 % N, index as i32 / N)
+    }
+
     pub fn index(&self, point: IVec2) -> Option<usize> {
         let area = Self::area();
 
         if !(area.min.x <= point.x && point.x <= area.max.x && area.min.y <= point.y && point.y <= area.max.y) {
-            None
-        } else {
-            Some((N * point.y + point.x) as usize)
+            return None;
+        }
+
+        let index = (N * point.y + point.x) as usize;
+
+        match &self.present {
+            Some(present) if !present[index] => None,
+            _ => Some(index),
         }
     }
 }
 
+impl<T, const N: i32> Chunk<T, N>
+where
+    T: Cell + Clone,
+    T::State: Clone,
+{
+    /// Copies the cells and state into a grid detached from this chunk, absent cells stay absent.
+    pub fn snapshot(&self) -> OwnedGrid<T> {
+        let grid = OwnedGrid::new(IVec2::splat(N), self.data.to_vec(), Arc::new(RwLock::new(self.state.read().clone())));
+
+        match &self.present {
+            Some(present) => grid.with_present(present.clone()),
+            None => grid,
+        }
+    }
+}
+
+impl<T, const N: i32> From<&Chunk<T, N>> for OwnedGrid<T>
+where
+    T: Cell + Clone,
+    T::State: Clone,
+{
+    fn from(chunk: &Chunk<T, N>) -> Self {
+        chunk.snapshot()
+    }
+}
+
 impl<T, const N: i32> Chunk<T, N> 
 where
     T: Cell + Copy,
@@ -101,6 +718,46 @@ where
     pub fn full_random<R: Rng, D: Distribution<T>>(rng: &mut R, distribution: D, state: T::State) -> Self {
         Self::new(rng.sample_iter(distribution).take(Self::volume()).collect(), state)
     }
+
+    /// Like [`Chunk::full_random`] but reproducible, the same `seed` and `coords` always build the same chunk.
+    pub fn full_random_for_coord<D: Distribution<T>>(seed: u64, coords: IVec2, distribution: D, state: T::State) -> Self {
+        Self::full_random(&mut ChunkCoords::<N>(coords).rng(seed), distribution, state)
+    }
+
+    /// Builds a chunk from an `N` by `N` image with `map` turning each pixel into a cell, the top row of the image
+    /// is the top row of the chunk as in [`Chunk::to_image`].
+    pub fn from_image(image: &RgbaImage, map: impl Fn(Rgba<u8>) -> T, state: T::State) -> Result<Self, PowderkegError<T>> {
+        if image.width() != N as u32 || image.height() != N as u32 {
+            return Err(PowderkegError::ImageSize { width: image.width(), height: image.height(), size: N });
+        }
+
+        let data = (0..N)
+            .flat_map(|y| (0..N).map(move |x| (x, y)))
+            .map(|(x, y)| map(*image.get_pixel(x as u32, (N - 1 - y) as u32)))
+            .collect();
+
+        Ok(Self::new(data, state))
+    }
+}
+
+impl<T, const N: i32> Chunk<T, N>
+where
+    T: Renderable,
+{
+    /// Renders the cells' colors into an `N` by `N` image, top row first so it reads the way the chunk is drawn.
+    /// Absent and transparent cells are fully transparent. The pixels are the sRGB bytes the chunk's texture holds.
+    pub fn to_image(&self) -> RgbaImage {
+        let state = self.state.read();
+
+        RgbaImage::from_fn(N as u32, N as u32, |x, row| {
+            let local = IVec2::new(x as i32, N - 1 - row as i32);
+
+            match self.get(local) {
+                Ok(cell) => Rgba(encode_srgba8(RenderChannel::Color.color(cell, local, &state))),
+                Err(_) => Rgba([0; 4]),
+            }
+        })
+    }
 }
 
 impl<T, const N: i32> Default for Chunk<T, N> 
@@ -122,15 +779,35 @@ where
     fn get(&self, point: IVec2) -> Result<&Self::Cell, PowderkegError<Self::Cell>> {
         let index = self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
 
-        Ok(self.data.get(index).expect("chunk does not have enough cells"))
+        Ok(self.readable().get(index).expect("chunk does not have enough cells"))
+    }
+
+    fn count_matching(&self, region: IRect, pred: impl Fn(&Self::Cell) -> bool) -> usize {
+        // Not `IRect::intersect`, which turns a disjoint intersection into a single point rather than an empty rect.
+        let (min, max) = (region.min.max(IVec2::ZERO), region.max.min(IVec2::splat(N - 1)));
+        let cells = self.readable();
+
+        if min.x > max.x || min.y > max.y {
+            return 0;
+        }
+
+        (min.y..=max.y)
+            .map(|y| {
+                let start = (N * y + min.x) as usize;
+                let end = (N * y + max.x) as usize;
+
+                match &self.present {
+                    Some(present) => (start..=end).filter(|index| present[*index] && pred(&cells[*index])).count(),
+                    None => cells[start..=end].iter().filter(|cell| pred(cell)).count(),
+                }
+            })
+            .sum()
     }
 
     fn get_mut(&mut self, point: IVec2) -> Result<&mut Self::Cell, PowderkegError<Self::Cell>> {
-        let index = self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
-        
-        self.stain_point(point);
+        self.record_change(point, ChangeKind::Modified);
 
-        Ok(self.data.get_mut(index).expect("chunk does not have enough cells"))
+        self.write(point)
     }
 
     fn swap(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<Self::Cell>> {
@@ -139,14 +816,43 @@ where
 
         self.stain_point(first);
         self.stain_point(second);
+        self.touch();
 
         self.data.swap(first_index, second_index);
 
+        self.record_change(first, ChangeKind::Moved);
+        self.record_change(second, ChangeKind::Moved);
+
+        self.observe(first, &self.data[second_index], &self.data[first_index]);
+        self.observe(second, &self.data[first_index], &self.data[second_index]);
+
         Ok(())
     }
+
+    fn replace(&mut self, point: IVec2, cell: T) -> Result<T, PowderkegError<T>> {
+        let index = self.write_index(point)?;
+        let old = mem::replace(&mut self.data[index], cell);
+
+        if T::HOOKS {
+            let mut state = self.state.write();
+
+            old.on_destroy(point, &mut state);
+            self.data[index].on_create(point, &mut state);
+        }
+
+        if let Some(classify) = self.changes.as_ref().map(|log| log.classify) {
+            if let Some(kind) = classify(&old, &self.data[index]) {
+                self.record_change(point, kind);
+            }
+        }
+
+        self.observe(point, &old, &self.data[index]);
+
+        Ok(old)
+    }
     
     fn get_state(&self, point: IVec2) -> Result<Arc<RwLock<<T as Cell>::State>>, PowderkegError<Self::Cell>> {
-        if self.covers().contains(point) {
+        if self.is_present(point) {
             Ok(self.state.clone())
         } else {
             Err(PowderkegError::LocalOutOfBounds(point))
@@ -154,7 +860,55 @@ where
     }
     
     fn covers(&self) -> Area {
-        Self::area().into()
+        let Some(present) = &self.present else {
+            return Self::area().into();
+        };
+
+        let mut covers = Area::Empty;
+
+        for y in 0..N {
+            let mut x = 0;
+
+            while x < N {
+                if present[(N * y + x) as usize] {
+                    let start = x;
+
+                    while x < N && present[(N * y + x) as usize] {
+                        x += 1;
+                    }
+
+                    covers.push(IRect::new(start, y, x - 1, y));
+                } else {
+                    x += 1;
+                }
+            }
+        }
+
+        covers
+    }
+}
+
+impl<T, const N: i32> DisjointGrid for Chunk<T, N>
+where
+    T: Cell,
+{
+    fn get_many_mut<const K: usize>(&mut self, points: [IVec2; K]) -> Result<[&mut T; K], PowderkegError<T>> {
+        check_disjoint(&points)?;
+
+        let mut indices = [0; K];
+
+        for (index, point) in indices.iter_mut().zip(points) {
+            *index = self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+        }
+
+        for point in points {
+            self.stain_point(point);
+            self.record_change(point, ChangeKind::Modified);
+        }
+
+        self.touch();
+
+        Ok(self.data.get_disjoint_mut(indices).expect("points were checked to be disjoint"))
     }
 }
 
@@ -163,27 +917,219 @@ where
     T: Cell,
 {
     fn stained(&self) -> Area {
-        match self.stain {
-            Some(area) => area.intersect(Self::area()).into(),
-            None => Area::Empty,
-        }
+        self.stain.intersect_rect(Self::area())
     }
 
     fn clear_stain(&mut self) {
-        self.stain = None;
+        self.stain = Area::Empty;
     }
 
     fn stain(&mut self, area: IRect) {
-        match &mut self.stain {
-            Some(stain) => *stain = stain.union(area),
-            stain @ None => *stain = Some(area),
+        self.idle_ticks = 0;
+
+        if let Some(area) = Self::clamp_stain(area) {
+            self.stain_policy.accumulate(&mut self.stain, area, Self::area());
         }
     }
 
     fn stain_point(&mut self, point: IVec2) {
-        match &mut self.stain {
-            Some(stain) => *stain = stain.union_point(point),
-            stain @ None => *stain = Some(IRect::from_corners(point, point)),
+        self.stain(IRect::from_corners(point, point));
+    }
+
+    fn stain_policy(&self) -> StainPolicy {
+        self.stain_policy
+    }
+
+    fn defer(&mut self, point: IVec2) -> bool {
+        if !self.is_present(point) {
+            return false;
+        }
+
+        self.deferred.push(point);
+
+        true
+    }
+
+    /// Writes the cells directly and stains each covered rect once, instead of every cell as it is written.
+    fn fill_with(&mut self, rect: IRect, mut f: impl FnMut(IVec2) -> T) -> Result<(), PowderkegError<T>> {
+        let filled = self.covers().intersect_rect(rect);
+        let classify = self.changes.as_ref().map(|log| log.classify);
+
+        if !filled.is_empty() {
+            self.touch();
+        }
+
+        for area in filled.rects() {
+            for y in area.min.y..=area.max.y {
+                for x in area.min.x..=area.max.x {
+                    let point = IVec2::new(x, y);
+                    let index = self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+
+                    let old = mem::replace(&mut self.data[index], f(point));
+
+                    if T::HOOKS {
+                        let mut state = self.state.write();
+
+                        old.on_destroy(point, &mut state);
+                        self.data[index].on_create(point, &mut state);
+                    }
+
+                    if let Some(kind) = classify.and_then(|classify| classify(&old, &self.data[index])) {
+                        self.record_change(point, kind);
+                    }
+
+                    self.observe(point, &old, &self.data[index]);
+                }
+            }
+        }
+
+        for area in filled.rects() {
+            self.stain(*area);
         }
+
+        Ok(())
+    }
+}
+
+fn mirror_rows<T>(data: &mut [T], width: usize) {
+    let height = data.len() / width;
+
+    for y in 0..height / 2 {
+        let (top, bottom) = data.split_at_mut((height - 1 - y) * width);
+
+        top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+    }
+}
+
+/// Chunks serialize their cells, which are present, stain and state. Change tracking and tick bookkeeping are not
+/// saved, a deserialized chunk starts with neither and a fresh lock around its state.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct ChunkRef<'c, T, S> {
+        data: &'c [T],
+        present: &'c Option<Vec<bool>>,
+        stain: &'c Area,
+        stain_policy: StainPolicy,
+        state: &'c S,
+    }
+
+    #[derive(Deserialize)]
+    struct ChunkOwned<T, S> {
+        data: Vec<T>,
+        present: Option<Vec<bool>>,
+        stain: Area,
+        stain_policy: StainPolicy,
+        state: S,
+    }
+
+    impl<T, const N: i32> Serialize for Chunk<T, N>
+    where
+        T: Cell + Serialize,
+        T::State: Serialize,
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            ChunkRef {
+                data: &self.data,
+                present: &self.present,
+                stain: &self.stain,
+                stain_policy: self.stain_policy,
+                state: &*self.state.read(),
+            }.serialize(serializer)
+        }
+    }
+
+    impl<'de, T, const N: i32> Deserialize<'de> for Chunk<T, N>
+    where
+        T: Cell + Deserialize<'de>,
+        T::State: Deserialize<'de>,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let chunk = ChunkOwned::<T, T::State>::deserialize(deserializer)?;
+
+            if chunk.data.len() != Self::volume() {
+                return Err(D::Error::custom(format!("expected {} cells, found {}", Self::volume(), chunk.data.len())));
+            }
+
+            if chunk.present.as_ref().is_some_and(|present| present.len() != Self::volume()) {
+                return Err(D::Error::custom(format!("expected {} present flags", Self::volume())));
+            }
+
+            Ok(Self {
+                present: chunk.present,
+                stain: chunk.stain,
+                stain_policy: chunk.stain_policy,
+                ..Self::new(chunk.data, chunk.state)
+            })
+        }
+    }
+}
+
+/// Serializes chunks as [`Chunk`] itself does but with the cells as runs from [`Chunk::to_rle`], for fields of
+/// mostly uniform chunks marked `#[serde(with = "powderkeg::chunk::rle")]`.
+#[cfg(feature = "serde")]
+pub mod rle {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct RunsRef<'c, T, S> {
+        runs: Vec<(T, u32)>,
+        present: &'c Option<Vec<bool>>,
+        stain: &'c Area,
+        stain_policy: StainPolicy,
+        state: &'c S,
+    }
+
+    #[derive(Deserialize)]
+    struct RunsOwned<T, S> {
+        runs: Vec<(T, u32)>,
+        present: Option<Vec<bool>>,
+        stain: Area,
+        stain_policy: StainPolicy,
+        state: S,
+    }
+
+    pub fn serialize<T, S, const N: i32>(chunk: &Chunk<T, N>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Cell + Clone + PartialEq + Serialize,
+        T::State: Serialize,
+        S: Serializer,
+    {
+        RunsRef {
+            runs: chunk.to_rle(),
+            present: &chunk.present,
+            stain: &chunk.stain,
+            stain_policy: chunk.stain_policy,
+            state: &*chunk.state.read(),
+        }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D, const N: i32>(deserializer: D) -> Result<Chunk<T, N>, D::Error>
+    where
+        T: Cell + Clone + Deserialize<'de>,
+        T::State: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let chunk = RunsOwned::<T, T::State>::deserialize(deserializer)?;
+
+        if chunk.present.as_ref().is_some_and(|present| present.len() != Chunk::<T, N>::volume()) {
+            return Err(D::Error::custom(format!("expected {} present flags", Chunk::<T, N>::volume())));
+        }
+
+        let cells = Chunk::from_rle(chunk.runs, chunk.state)
+            .ok_or_else(|| D::Error::custom(format!("expected runs of {} cells", Chunk::<T, N>::volume())))?;
+
+        Ok(Chunk {
+            present: chunk.present,
+            stain: chunk.stain,
+            stain_policy: chunk.stain_policy,
+            ..cells
+        })
     }
 }
@@ -2,9 +2,29 @@
 
 @group(2) @binding(0) var chunk_texture: texture_2d<f32>;
 @group(2) @binding(1) var chunk_texture_sampler: sampler;
+#ifdef EMISSIVE
+@group(2) @binding(2) var emissive_texture: texture_2d<f32>;
+@group(2) @binding(3) var emissive_texture_sampler: sampler;
+
+// Matches `MAX_EMISSIVE` in viewer.rs.
+const MAX_EMISSIVE: f32 = 4.0;
+#endif
 
 @fragment
 fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
     let uv = vec2<f32>(mesh.uv.x, 1.0 - mesh.uv.y);
-    return textureSample(chunk_texture, chunk_texture_sampler, uv);
+    let color = textureSample(chunk_texture, chunk_texture_sampler, uv);
+
+    if color.a == 0.0 {
+        discard;
+    }
+
+#ifdef EMISSIVE
+    // Values past one only show with an HDR camera, where bloom spreads them.
+    let emissive = textureSample(emissive_texture, emissive_texture_sampler, uv).r * MAX_EMISSIVE;
+
+    return vec4<f32>(color.rgb * (1.0 + emissive), color.a);
+#else
+    return color;
+#endif
 }
@@ -1,23 +1,59 @@
-use std::{mem::replace, sync::Arc};
+use std::{collections::{HashSet, VecDeque}, mem::replace, sync::Arc};
 
-use bevy::math::IVec2;
+use bevy::math::{IRect, IVec2};
 use parking_lot::RwLock;
 
-use crate::{area::Area, cell::Cell, PowderkegError};
+use crate::{area::Area, cell::{Cell, SwapStates}, prefab::{Prefab, PrefabTransform}, stain::{StainPolicy, Stainable}, PowderkegError};
 
 pub trait Grid {
     type Cell: Cell;
 
     fn get(&self, point: IVec2) -> Result<&Self::Cell, PowderkegError<Self::Cell>>;
     fn get_mut(&mut self, point: IVec2) ->Result<&mut Self::Cell, PowderkegError<Self::Cell>>;
+    /// Swaps only the cells, anything their chunk state keeps for them stays where it was,
+    /// see [`Grid::swap_with_state`].
     fn swap(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<Self::Cell>>;
 
+    #[allow(clippy::type_complexity)]
     fn get_state(&self, point: IVec2) -> Result<Arc<RwLock<<Self::Cell as Cell>::State>>, PowderkegError<Self::Cell>>;
 
     fn covers(&self) -> Area;
 
+    /// Writes `cell` at `point` and returns the cell it replaced, calling [`Cell::on_destroy`] and [`Cell::on_create`].
     fn replace(&mut self, point: IVec2, cell: Self::Cell) -> Result<Self::Cell, PowderkegError<Self::Cell>> {
-        Ok(replace(self.get_mut(point)?, cell))
+        if !Self::Cell::HOOKS {
+            return Ok(replace(self.get_mut(point)?, cell));
+        }
+
+        // Fetched before writing, so a point without state fails with nothing written.
+        let state = self.get_state(point)?;
+        let old = replace(self.get_mut(point)?, cell);
+        let mut state = state.write();
+
+        old.on_destroy(point, &mut state);
+        self.get(point)?.on_create(point, &mut state);
+
+        Ok(old)
+    }
+
+    /// Replaces the cell at `point` only if it passes `only_if`, returning the replaced cell or `None` when it was left
+    /// alone, in which case nothing is written or stained.
+    fn replace_if(&mut self, point: IVec2, cell: Self::Cell, only_if: impl FnOnce(&Self::Cell) -> bool) -> Result<Option<Self::Cell>, PowderkegError<Self::Cell>> {
+        if only_if(self.get(point)?) {
+            self.replace(point, cell).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Swaps the cells at `first` and `second` and then, with [`Cell::swap_state`], whatever their chunk states keep
+    /// for them, so a cell's data follows it even across chunk boundaries.
+    fn swap_with_state(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<Self::Cell>> {
+        self.swap(first, second)?;
+
+        swap_cell_state::<Self::Cell>(&self.get_state(first)?, first, &self.get_state(second)?, second);
+
+        Ok(())
     }
 
     fn map_cell<T>(&self, point: IVec2, f: impl FnOnce(&Self::Cell) -> T) -> Result<T, PowderkegError<Self::Cell>> {
@@ -28,6 +64,127 @@ pub trait Grid {
         self.get_mut(point).map(f)
     }
 
+    /// Writes every unmasked cell of `prefab` with its transformed footprint's minimum corner at `origin`.
+    fn stamp_prefab(&mut self, origin: IVec2, prefab: &Prefab<Self::Cell>, transform: PrefabTransform) -> Result<(), PowderkegError<Self::Cell>>
+    where
+        Self::Cell: Clone,
+    {
+        for (local, cell) in prefab.iter() {
+            self.replace(origin + transform.apply(local, prefab.size()), cell.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `cell` over every point of `rect` whose current cell passes `only_if`, skipping points outside the grid.
+    /// Only written cells are stained, returns how many were written.
+    fn paint_rect(&mut self, rect: IRect, cell: Self::Cell, only_if: impl Fn(&Self::Cell) -> bool) -> usize
+    where
+        Self::Cell: Clone,
+    {
+        let mut painted = 0;
+
+        for y in rect.min.y..=rect.max.y {
+            for x in rect.min.x..=rect.max.x {
+                let point = IVec2::new(x, y);
+
+                if self.get(point).is_ok_and(&only_if) && self.replace(point, cell.clone()).is_ok() {
+                    painted += 1;
+                }
+            }
+        }
+
+        painted
+    }
+
+    /// Like [`Grid::paint_rect`] but over the points within `radius` of `center`.
+    fn paint_circle(&mut self, center: IVec2, radius: i32, cell: Self::Cell, only_if: impl Fn(&Self::Cell) -> bool) -> usize
+    where
+        Self::Cell: Clone,
+    {
+        let bounds = IRect::from_center_half_size(center, IVec2::splat(radius));
+        let mut painted = 0;
+
+        for y in bounds.min.y..=bounds.max.y {
+            for x in bounds.min.x..=bounds.max.x {
+                let point = IVec2::new(x, y);
+
+                if (point - center).length_squared() <= radius * radius
+                    && self.get(point).is_ok_and(&only_if)
+                    && self.replace(point, cell.clone()).is_ok()
+                {
+                    painted += 1;
+                }
+            }
+        }
+
+        painted
+    }
+
+    /// Writes `cell` over every point of the line from `a` to `b` whose current cell passes `only_if`, like
+    /// [`Grid::paint_rect`].
+    fn paint_line(&mut self, a: IVec2, b: IVec2, cell: Self::Cell, only_if: impl Fn(&Self::Cell) -> bool) -> usize
+    where
+        Self::Cell: Clone,
+    {
+        line(a, b)
+            .filter(|point| self.get(*point).is_ok_and(&only_if) && self.replace(*point, cell.clone()).is_ok())
+            .count()
+    }
+
+    /// Writes `replacement` over the 4-connected region of cells passing `matches` around `start`, at most `limit` of
+    /// them. Each point is visited once so a replacement that still matches does not loop forever, written cells are
+    /// stained as any write is. Returns how many were written.
+    fn flood_fill(&mut self, start: IVec2, replacement: Self::Cell, matches: impl Fn(&Self::Cell) -> bool, limit: Option<usize>) -> usize
+    where
+        Self::Cell: Clone,
+    {
+        flood(start, limit, |point| self.get(point).is_ok_and(&matches) && self.replace(point, replacement.clone()).is_ok())
+    }
+
+    /// How many cells of `region` pass `pred`, points the grid cannot read are not counted.
+    fn count_matching(&self, region: IRect, pred: impl Fn(&Self::Cell) -> bool) -> usize {
+        (region.min.y..=region.max.y)
+            .flat_map(|y| (region.min.x..=region.max.x).map(move |x| IVec2::new(x, y)))
+            .filter(|point| self.get(*point).is_ok_and(&pred))
+            .count()
+    }
+
+    /// Copies the cells of `region` and the state of every chunk they are in, leaving out the points the grid cannot
+    /// read, see [`Stainable::restore`].
+    fn snapshot_region(&self, region: IRect) -> GridSnapshot<Self::Cell>
+    where
+        Self::Cell: Clone,
+        <Self::Cell as Cell>::State: Clone,
+    {
+        GridSnapshot::new(region, |point| Some((self.get(point).ok()?.clone(), self.get_state(point).ok()?)))
+    }
+
+    /// The points of the line from `a` to `b`, see [`line`].
+    fn line(&self, a: IVec2, b: IVec2) -> impl Iterator<Item = IVec2> {
+        line(a, b)
+    }
+
+    /// The four cells orthogonally adjacent to `point`, skipping any the grid cannot read.
+    fn neighbors_von_neumann(&self, point: IVec2) -> impl Iterator<Item = (IVec2, &Self::Cell)> {
+        [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+            .into_iter()
+            .filter_map(move |offset| self.get(point + offset).ok().map(|cell| (point + offset, cell)))
+    }
+
+    /// The eight cells around `point`, skipping any the grid cannot read.
+    fn neighbors_moore(&self, point: IVec2) -> impl Iterator<Item = (IVec2, &Self::Cell)> {
+        self.neighbors_within(point, 1)
+    }
+
+    /// Every cell within `radius` of `point` on both axes except `point` itself, skipping any the grid cannot read.
+    fn neighbors_within(&self, point: IVec2, radius: i32) -> impl Iterator<Item = (IVec2, &Self::Cell)> {
+        (-radius..=radius)
+            .flat_map(move |y| (-radius..=radius).map(move |x| IVec2::new(x, y)))
+            .filter(|offset| *offset != IVec2::ZERO)
+            .filter_map(move |offset| self.get(point + offset).ok().map(|cell| (point + offset, cell)))
+    }
+
     fn at(&self, point: IVec2) -> &Self::Cell {
         self.get(point).unwrap_or_else(|e| panic!("error at {point}: {e}"))
     }
@@ -40,3 +197,429 @@ pub trait Grid {
         self.get_state(point).unwrap_or_else(|e| panic!("error at {point}: {e}"))
     }
 }
+
+/// A grid that can lend several of its cells mutably at once, every [`Stainable`] grid cells tick on is one.
+pub trait DisjointGrid: Grid {
+    /// Mutable references to the cells at every one of `points` at once, for rules updating several cells together.
+    /// Errors if any point cannot be written or is given twice, grids that stain on writes stain every point.
+    fn get_many_mut<const K: usize>(&mut self, points: [IVec2; K]) -> Result<[&mut Self::Cell; K], PowderkegError<Self::Cell>>;
+}
+
+/// Errors with [`PowderkegError::DuplicatePoint`] for the first of `points` given more than once.
+pub(crate) fn check_disjoint<T: Cell>(points: &[IVec2]) -> Result<(), PowderkegError<T>> {
+    match points.iter().enumerate().find(|(i, point)| points[..*i].contains(point)) {
+        Some((_, point)) => Err(PowderkegError::DuplicatePoint(*point)),
+        None => Ok(()),
+    }
+}
+
+/// The points of the Bresenham line from `a` to `b`, both included, each a single step from the last along either
+/// axis or diagonally. Purely coordinate based, any of the points may be outside a grid.
+pub fn line(a: IVec2, b: IVec2) -> impl Iterator<Item = IVec2> {
+    let delta = (b - a).abs() * IVec2::new(1, -1);
+    let step = (b - a).signum();
+
+    let mut point = a;
+    let mut error = delta.x + delta.y;
+    let mut done = false;
+
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+
+        let current = point;
+
+        if point == b {
+            done = true;
+            return Some(current);
+        }
+
+        let doubled = 2 * error;
+
+        if doubled >= delta.y {
+            error += delta.y;
+            point.x += step.x;
+        }
+
+        if doubled <= delta.x {
+            error += delta.x;
+            point.y += step.y;
+        }
+
+        Some(current)
+    })
+}
+
+/// Visits the points 4-connected to `start` breadth first, spreading from those `fill` returns `true` for until `limit`
+/// have been filled. Returns how many were filled.
+pub(crate) fn flood(start: IVec2, limit: Option<usize>, mut fill: impl FnMut(IVec2) -> bool) -> usize {
+    let mut queue = VecDeque::from([start]);
+    let mut visited = HashSet::from([start]);
+    let mut filled = 0;
+
+    while let Some(point) = queue.pop_front() {
+        if limit.is_some_and(|limit| filled >= limit) {
+            break;
+        }
+
+        if !fill(point) {
+            continue;
+        }
+
+        filled += 1;
+
+        for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+            if visited.insert(point + offset) {
+                queue.push_back(point + offset);
+            }
+        }
+    }
+
+    filled
+}
+
+/// Calls [`Cell::swap_state`] for cells at the chunk local `first` and `second`, locking each distinct state once.
+pub(crate) fn swap_cell_state<T: Cell>(first_state: &Arc<RwLock<T::State>>, first: IVec2, second_state: &Arc<RwLock<T::State>>, second: IVec2) {
+    if Arc::ptr_eq(first_state, second_state) {
+        T::swap_state(SwapStates::Shared(&mut first_state.write()), first, second);
+    } else {
+        T::swap_state(SwapStates::Split { first: &mut first_state.write(), second: &mut second_state.write() }, first, second);
+    }
+}
+
+/// The cells of a rect of a grid and the state of the chunks holding them, taken by [`Grid::snapshot_region`]. Points
+/// the grid could not read are left out and skipped when restoring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+    serialize = "T: serde::Serialize, T::State: serde::Serialize",
+    deserialize = "T: serde::Deserialize<'de>, T::State: serde::Deserialize<'de>",
+)))]
+pub struct GridSnapshot<T: Cell> {
+    origin: IVec2,
+    size: IVec2,
+    cells: Vec<Option<T>>,
+    /// Each distinct state read, with the first point of the region it was read through.
+    states: Vec<(IVec2, T::State)>,
+}
+
+impl<T> GridSnapshot<T>
+where
+    T: Cell,
+{
+    pub(crate) fn new(region: IRect, mut f: impl FnMut(IVec2) -> Option<(T, Arc<RwLock<T::State>>)>) -> Self
+    where
+        T::State: Clone,
+    {
+        let size = (region.size() + IVec2::ONE).max(IVec2::ZERO);
+        let mut locks: Vec<Arc<RwLock<T::State>>> = Vec::new();
+        let mut states = Vec::new();
+
+        let cells = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| region.min + IVec2::new(x, y)))
+            .map(|point| {
+                let (cell, state) = f(point)?;
+
+                if !locks.iter().any(|lock| Arc::ptr_eq(lock, &state)) {
+                    states.push((point, state.read().clone()));
+                    locks.push(state);
+                }
+
+                Some(cell)
+            })
+            .collect();
+
+        Self { origin: region.min, size, cells, states }
+    }
+
+    /// The minimum corner of the region in the grid it was taken from.
+    pub fn origin(&self) -> IVec2 {
+        self.origin
+    }
+
+    pub fn region(&self) -> IRect {
+        IRect { min: self.origin, max: self.origin + self.size - IVec2::ONE }
+    }
+
+    /// The captured cell at the grid `point`, `None` outside the region or where the grid could not be read.
+    pub fn get(&self, point: IVec2) -> Option<&T> {
+        let local = point - self.origin;
+
+        if local.x < 0 || local.y < 0 || local.x >= self.size.x || local.y >= self.size.y {
+            return None;
+        }
+
+        self.cells[(local.y * self.size.x + local.x) as usize].as_ref()
+    }
+
+    /// Every captured cell with its grid point.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        self.cells.iter().enumerate().filter_map(|(i, cell)| {
+            let i = i as i32;
+
+            cell.as_ref().map(|cell| (self.origin + IVec2::new(i % self.size.x, i / self.size.x), cell))
+        })
+    }
+
+    /// Every captured chunk state with a grid point of the region it was read through.
+    pub fn states(&self) -> impl Iterator<Item = (IVec2, &T::State)> {
+        self.states.iter().map(|(point, state)| (*point, state))
+    }
+}
+
+/// A standalone grid of owned cells, independent of the ECS.
+pub struct OwnedGrid<T: Cell> {
+    size: IVec2,
+    data: Vec<T>,
+    /// Which cells exist, every cell when `None`. Absent cells behave like the edge of the grid.
+    present: Option<Vec<bool>>,
+    state: Arc<RwLock<T::State>>,
+}
+
+impl<T: Cell> OwnedGrid<T> {
+    pub fn new(size: IVec2, data: Vec<T>, state: Arc<RwLock<T::State>>) -> Self {
+        assert!(size.x >= 0 && size.y >= 0);
+        assert_eq!(data.len(), size.x as usize * size.y as usize);
+
+        Self { size, data, present: None, state }
+    }
+
+    /// Marks the cells that exist, row by row from the bottom, the rest are absent as in a sparse chunk.
+    pub fn with_present(mut self, present: Vec<bool>) -> Self {
+        assert_eq!(present.len(), self.data.len());
+
+        self.present = Some(present);
+        self
+    }
+
+    pub fn size(&self) -> IVec2 {
+        self.size
+    }
+
+    pub fn area(&self) -> IRect {
+        IRect { min: IVec2::ZERO, max: self.size - IVec2::ONE }
+    }
+
+    pub fn index(&self, point: IVec2) -> Option<usize> {
+        if point.x < 0 || point.y < 0 || point.x >= self.size.x || point.y >= self.size.y {
+            return None;
+        }
+
+        let index = (self.size.x * point.y + point.x) as usize;
+
+        match &self.present {
+            Some(present) if !present[index] => None,
+            _ => Some(index),
+        }
+    }
+
+    pub fn is_present(&self, point: IVec2) -> bool {
+        self.index(point).is_some()
+    }
+}
+
+impl<T: Cell> Grid for OwnedGrid<T> {
+    type Cell = T;
+
+    fn get(&self, point: IVec2) -> Result<&T, PowderkegError<T>> {
+        let index = self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+
+        Ok(&self.data[index])
+    }
+
+    fn get_mut(&mut self, point: IVec2) -> Result<&mut T, PowderkegError<T>> {
+        let index = self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+
+        Ok(&mut self.data[index])
+    }
+
+    fn swap(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<T>> {
+        let first_index = self.index(first).ok_or(PowderkegError::LocalOutOfBounds(first))?;
+        let second_index = self.index(second).ok_or(PowderkegError::LocalOutOfBounds(second))?;
+
+        self.data.swap(first_index, second_index);
+
+        Ok(())
+    }
+
+    fn get_state(&self, point: IVec2) -> Result<Arc<RwLock<T::State>>, PowderkegError<T>> {
+        self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+
+        Ok(self.state.clone())
+    }
+
+    fn covers(&self) -> Area {
+        if self.data.is_empty() {
+            return Area::Empty;
+        }
+
+        let Some(present) = &self.present else {
+            return self.area().into();
+        };
+
+        let mut covers = Area::Empty;
+
+        for (y, row) in present.chunks_exact(self.size.x as usize).enumerate() {
+            let mut x = 0;
+
+            while x < row.len() {
+                if row[x] {
+                    let start = x;
+
+                    while x < row.len() && row[x] {
+                        x += 1;
+                    }
+
+                    covers.push(IRect::new(start as i32, y as i32, x as i32 - 1, y as i32));
+                } else {
+                    x += 1;
+                }
+            }
+        }
+
+        covers
+    }
+}
+
+impl<T: Cell> DisjointGrid for OwnedGrid<T> {
+    fn get_many_mut<const K: usize>(&mut self, points: [IVec2; K]) -> Result<[&mut T; K], PowderkegError<T>> {
+        check_disjoint(&points)?;
+
+        let mut indices = [0; K];
+
+        for (index, point) in indices.iter_mut().zip(points) {
+            *index = self.index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+        }
+
+        Ok(self.data.get_disjoint_mut(indices).expect("points were checked to be disjoint"))
+    }
+}
+
+/// Restricts a grid to `bounds`. Points outside read as the nearest cell on the edge of the bounds, so cells see the
+/// edge extend outwards as a solid boundary. Writing to or mutably borrowing a point outside fails with
+/// [`PowderkegError::LocalOutOfBounds`] and leaves the grid untouched, stains are clipped to the bounds.
+///
+/// Wrapping a tick's grid contains whatever the cell does to the bounds without building walls of cells.
+pub struct ClampedGrid<'g, G> {
+    grid: &'g mut G,
+    bounds: IRect,
+}
+
+impl<'g, G: Grid> ClampedGrid<'g, G> {
+    pub fn new(grid: &'g mut G, bounds: IRect) -> Self {
+        Self { grid, bounds }
+    }
+
+    pub fn bounds(&self) -> IRect {
+        self.bounds
+    }
+
+    pub fn contains(&self, point: IVec2) -> bool {
+        self.bounds.min.x <= point.x && point.x <= self.bounds.max.x && self.bounds.min.y <= point.y && point.y <= self.bounds.max.y
+    }
+
+    /// The point within the bounds nearest to `point`, which reads in its place.
+    pub fn clamp(&self, point: IVec2) -> IVec2 {
+        point.clamp(self.bounds.min, self.bounds.max)
+    }
+
+    fn check(&self, point: IVec2) -> Result<(), PowderkegError<G::Cell>> {
+        if self.contains(point) {
+            Ok(())
+        } else {
+            Err(PowderkegError::LocalOutOfBounds(point))
+        }
+    }
+}
+
+impl<'g, G: Grid> Grid for ClampedGrid<'g, G> {
+    type Cell = G::Cell;
+
+    /// Points outside the bounds read the nearest cell on their edge.
+    fn get(&self, point: IVec2) -> Result<&Self::Cell, PowderkegError<Self::Cell>> {
+        self.grid.get(self.clamp(point))
+    }
+
+    fn get_mut(&mut self, point: IVec2) -> Result<&mut Self::Cell, PowderkegError<Self::Cell>> {
+        self.check(point)?;
+        self.grid.get_mut(point)
+    }
+
+    fn swap(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<Self::Cell>> {
+        self.check(first)?;
+        self.check(second)?;
+        self.grid.swap(first, second)
+    }
+
+    fn swap_with_state(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<Self::Cell>> {
+        self.check(first)?;
+        self.check(second)?;
+        self.grid.swap_with_state(first, second)
+    }
+
+    fn replace(&mut self, point: IVec2, cell: Self::Cell) -> Result<Self::Cell, PowderkegError<Self::Cell>> {
+        self.check(point)?;
+        self.grid.replace(point, cell)
+    }
+
+    fn replace_if(&mut self, point: IVec2, cell: Self::Cell, only_if: impl FnOnce(&Self::Cell) -> bool) -> Result<Option<Self::Cell>, PowderkegError<Self::Cell>> {
+        self.check(point)?;
+        self.grid.replace_if(point, cell, only_if)
+    }
+
+    fn get_state(&self, point: IVec2) -> Result<Arc<RwLock<<Self::Cell as Cell>::State>>, PowderkegError<Self::Cell>> {
+        self.grid.get_state(self.clamp(point))
+    }
+
+    fn covers(&self) -> Area {
+        self.grid.covers().intersect_rect(self.bounds)
+    }
+}
+
+impl<'g, G: DisjointGrid> DisjointGrid for ClampedGrid<'g, G> {
+    fn get_many_mut<const K: usize>(&mut self, points: [IVec2; K]) -> Result<[&mut Self::Cell; K], PowderkegError<Self::Cell>> {
+        for point in points {
+            self.check(point)?;
+        }
+
+        self.grid.get_many_mut(points)
+    }
+}
+
+impl<'g, G: Stainable> Stainable for ClampedGrid<'g, G> {
+    fn stained(&self) -> Area {
+        self.grid.stained().intersect_rect(self.bounds)
+    }
+
+    fn stain(&mut self, area: IRect) {
+        for rect in Area::from(area).intersect_rect(self.bounds).rects() {
+            self.grid.stain(*rect);
+        }
+    }
+
+    fn stain_point(&mut self, point: IVec2) {
+        if self.contains(point) {
+            self.grid.stain_point(point);
+        }
+    }
+
+    /// Clears only the stain within the bounds, whatever the inner grid has stained outside them stays.
+    fn clear_stain(&mut self) {
+        let outside = self.grid.stained().subtract(&self.bounds.into());
+
+        self.grid.clear_stain();
+
+        for rect in outside.rects() {
+            self.grid.stain(*rect);
+        }
+    }
+
+    fn stain_policy(&self) -> StainPolicy {
+        self.grid.stain_policy()
+    }
+
+    fn defer(&mut self, point: IVec2) -> bool {
+        self.contains(point) && self.grid.defer(point)
+    }
+}
@@ -5,12 +5,22 @@ pub mod cell;
 pub mod simulation;
 pub mod viewer;
 pub mod area;
+pub mod prefab;
+pub mod rules;
+pub mod world;
+pub mod lighting;
+pub mod palette;
+#[cfg(feature = "serde")]
+pub mod save;
+pub mod streaming;
+pub mod testing;
 
 use std::marker::PhantomData;
 
+use area::StainOrder;
 use bevy::prelude::*;
 use cell::{Cell, Renderable};
-use simulation::PowderkegSimulationPlugin;
+use simulation::{Gravity, MaxTicksPerFrame, PowderkegRng, PowderkegSimulationPlugin, PowderkegTickRate, SimulationSchedule, WorldTopology};
 use thiserror::Error;
 use viewer::PowderkegViewPlugin;
 
@@ -22,21 +32,104 @@ pub enum PowderkegError<T: Cell> {
     LocalOutOfBounds(IVec2),
     #[error("chunk at {0} out of bounds")]
     ChunkOutOfBounds(IVec2),
+    #[error("{0} given more than once")]
+    DuplicatePoint(IVec2),
     #[error("chunks not found when swapping {first} -> {second}")]
     SwapOutOfBounds {
         first: IVec2,
         second: IVec2,
     },
+    #[error("image is {width}x{height} but chunks are {size}x{size}")]
+    ImageSize {
+        width: u32,
+        height: u32,
+        size: i32,
+    },
+}
+
+/// Adds the simulation and rendering of chunks of `T` cells `N` wide. Settings left unset keep their resources'
+/// defaults, or whatever was inserted before the plugin.
+///
+/// The settings are inserted as resources shared by every cell type, so adding a second plugin overwrites whatever
+/// the first one set, such as its seed and tick rate.
+pub struct PowderkegPlugin<T, const N: i32> {
+    tick_rate: Option<f32>,
+    max_ticks: Option<u32>,
+    seed: Option<u64>,
+    schedule: Option<SimulationSchedule>,
+    order: Option<StainOrder>,
+    gravity: Option<IVec2>,
+    topology: Option<WorldTopology>,
+    _cell: PhantomData<T>,
 }
 
-pub struct PowderkegPlugin<T, const N: i32>(PhantomData<T>);
+impl<T, const N: i32> PowderkegPlugin<T, N>
+where
+    T: Renderable,
+{
+    /// A plugin that leaves every setting unset.
+    pub fn new() -> Self {
+        Self {
+            tick_rate: None,
+            max_ticks: None,
+            seed: None,
+            schedule: None,
+            order: None,
+            gravity: None,
+            topology: None,
+            _cell: PhantomData,
+        }
+    }
+
+    /// Sets the [`PowderkegTickRate`](simulation::PowderkegTickRate), in ticks per second.
+    pub fn tick_rate(mut self, tick_rate: f32) -> Self {
+        self.tick_rate = Some(tick_rate);
+        self
+    }
+
+    /// Sets [`MaxTicksPerFrame`](simulation::MaxTicksPerFrame).
+    pub fn max_ticks_per_frame(mut self, max_ticks: u32) -> Self {
+        self.max_ticks = Some(max_ticks);
+        self
+    }
+
+    /// Seeds the [`PowderkegRng`](simulation::PowderkegRng), making the simulation reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the [`SimulationSchedule`](simulation::SimulationSchedule).
+    pub fn schedule(mut self, schedule: SimulationSchedule) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    /// Sets the [`StainOrder`](area::StainOrder) stained points are ticked in.
+    pub fn stain_order(mut self, order: StainOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    /// Sets the [`Gravity`](simulation::Gravity) direction.
+    pub fn gravity(mut self, gravity: IVec2) -> Self {
+        self.gravity = Some(gravity);
+        self
+    }
+
+    /// Sets the [`WorldTopology`](simulation::WorldTopology).
+    pub fn topology(mut self, topology: WorldTopology) -> Self {
+        self.topology = Some(topology);
+        self
+    }
+}
 
 impl<T, const N: i32> Default for PowderkegPlugin<T, N>
 where
     T: Renderable,
 {
     fn default() -> Self {
-        Self(PhantomData)
+        Self::new()
     }
 }
 
@@ -49,6 +142,34 @@ where
             .add_plugins(PowderkegViewPlugin::<T, N>::default())
             .add_plugins(PowderkegSimulationPlugin::<T, N>::default())
             .configure_sets(Update, (PowderkegSet::Tick, PowderkegSet::Render).chain()); 
+
+        if let Some(tick_rate) = self.tick_rate {
+            app.insert_resource(PowderkegTickRate(tick_rate));
+        }
+
+        if let Some(max_ticks) = self.max_ticks {
+            app.insert_resource(MaxTicksPerFrame(max_ticks));
+        }
+
+        if let Some(seed) = self.seed {
+            app.insert_resource(PowderkegRng::new(seed));
+        }
+
+        if let Some(schedule) = self.schedule {
+            app.insert_resource(schedule);
+        }
+
+        if let Some(order) = self.order {
+            app.insert_resource(order);
+        }
+
+        if let Some(gravity) = self.gravity {
+            app.insert_resource(Gravity(gravity));
+        }
+
+        if let Some(topology) = self.topology {
+            app.insert_resource(topology);
+        }
     }
 }
 
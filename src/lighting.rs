@@ -0,0 +1,324 @@
+use std::{collections::{BinaryHeap, HashMap}, marker::PhantomData};
+
+use bevy::prelude::*;
+
+use crate::{cell::Renderable, chunk::{Chunk, ChunkCoords}, grid::Grid, stain::{ChangeKind, StainPolicy, Stainable}, area::Area, PowderkegSet};
+
+const NEIGHBORS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+/// Propagates light from emissive cells into a [`LightMap`] on every chunk, which the viewer multiplies into the
+/// color channel. Only the surroundings of stained cells are recomputed each frame, or of the changes that can affect
+/// the light on chunks that [track changes](Chunk::track_changes).
+pub struct PowderkegLightingPlugin<T, const N: i32>(PhantomData<T>);
+
+impl<T, const N: i32> Default for PowderkegLightingPlugin<T, N>
+where
+    T: Luminous,
+{
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, const N: i32> Plugin for PowderkegLightingPlugin<T, N>
+where
+    T: Luminous,
+{
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LightSettings>()
+            .add_systems(Update, (
+                attach_light_maps::<T, N>,
+                take_light_changes::<T, N>,
+                propagate_light::<T, N>,
+            ).chain().after(PowderkegSet::Tick).before(PowderkegSet::Render));
+    }
+}
+
+/// Cells that emit or block light. Each cell is lit by the brightest of its own emission and the light its
+/// neighbors pass on, less [`LightSettings::falloff`].
+pub trait Luminous: Renderable {
+    /// The light this cell emits, `0` for none.
+    fn emission(&self) -> u8 {
+        0
+    }
+
+    /// Opaque cells are lit by their neighbors but only pass on their own emission.
+    fn is_opaque(&self) -> bool {
+        false
+    }
+}
+
+/// How light spreads and how it shows, changing it relights every chunk.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct LightSettings {
+    /// The light lost for every cell travelled.
+    pub falloff: u8,
+    /// The brightness of unlit cells, from `0.0` to `1.0`.
+    pub ambient: f32,
+}
+
+impl Default for LightSettings {
+    fn default() -> Self {
+        Self { falloff: 16, ambient: 0.1 }
+    }
+}
+
+impl LightSettings {
+    /// The furthest a change can affect the light, in cells.
+    pub fn reach(&self) -> i32 {
+        let falloff = self.falloff.max(1) as i32;
+
+        (u8::MAX as i32 + falloff - 1) / falloff
+    }
+
+    /// How much of a cell's color shows at the light `level`.
+    pub fn brightness(&self, level: u8) -> f32 {
+        self.ambient + (1.0 - self.ambient) * level as f32 / u8::MAX as f32
+    }
+}
+
+/// The light level of every cell of a chunk.
+#[derive(Component)]
+pub struct LightMap<const N: i32> {
+    levels: Vec<u8>,
+    changed: Area,
+    /// The local cells whose changes can affect the light, recomputed in place of the stain when the chunk tracks
+    /// changes.
+    pending: Option<Area>,
+}
+
+impl<const N: i32> Default for LightMap<N> {
+    fn default() -> Self {
+        Self { levels: vec![0; N as usize * N as usize], changed: Area::Empty, pending: None }
+    }
+}
+
+impl<const N: i32> LightMap<N> {
+    /// The light level at the chunk local `local`, from `0` for unlit to `255`, `0` outside the chunk.
+    pub fn level(&self, local: IVec2) -> u8 {
+        if local.x < 0 || local.y < 0 || local.x >= N || local.y >= N {
+            return 0;
+        }
+
+        self.levels[(N * local.y + local.x) as usize]
+    }
+
+    /// The local cells whose light changed this frame.
+    pub fn changed(&self) -> &Area {
+        &self.changed
+    }
+}
+
+fn attach_light_maps<T, const N: i32>(
+    mut commands: Commands,
+    chunks: Query<Entity, (With<Chunk<T, N>>, Without<LightMap<N>>)>,
+) where
+    T: Luminous,
+{
+    for entity in chunks.iter() {
+        commands.entity(entity).insert(LightMap::<N>::default());
+    }
+}
+
+/// Takes the changes of chunks tracking them, keeping only those that can affect the light.
+fn take_light_changes<T, const N: i32>(
+    mut chunks: Query<(&mut Chunk<T, N>, &mut LightMap<N>)>,
+) where
+    T: Luminous,
+{
+    for (mut chunk, mut light) in chunks.iter_mut() {
+        if !chunk.is_tracking_changes() {
+            continue;
+        }
+
+        let mut pending = Area::Empty;
+
+        // Swapping cells that neither block nor emit light leaves it as it was. Any other swap leaves the cell that
+        // does at one end, and the other end is within its reach.
+        let dark = |cell: &T| !cell.is_opaque() && cell.emission() == 0;
+
+        for (point, kind) in chunk.bypass_change_detection().take_changes() {
+            if kind == ChangeKind::Moved && chunk.get(point).is_ok_and(dark) {
+                continue;
+            }
+
+            pending.push(IRect::from_corners(point, point));
+        }
+
+        pending.coalesce();
+
+        light.bypass_change_detection().pending = Some(pending);
+    }
+}
+
+/// Recomputes the light within reach of every stained cell, and everywhere on chunks new to lighting or once the
+/// [`LightSettings`] change.
+///
+/// Light beyond the reach of a change cannot depend on it, so the light just outside the recomputed cells seeds
+/// the flood inside them. The recomputed cells are gathered across the whole world, which carries light over
+/// chunk boundaries the way stains are exchanged between chunks.
+fn propagate_light<T, const N: i32>(
+    settings: Res<LightSettings>,
+    mut chunks: Query<(&Chunk<T, N>, &ChunkCoords<N>, &mut LightMap<N>)>,
+) where
+    T: Luminous,
+{
+    let area = Chunk::<T, N>::area();
+    let reach = IVec2::splat(settings.reach());
+
+    let mut world = HashMap::new();
+    let mut stains = Area::Empty;
+
+    for (chunk, coords, mut light) in chunks.iter_mut() {
+        let pending = light.bypass_change_detection().pending.take();
+
+        // Only writing new levels marks the light map changed, not forgetting last frame's.
+        light.bypass_change_detection().changed = Area::Empty;
+
+        let mut stain = if light.is_added() || settings.is_changed() {
+            area.into()
+        } else {
+            pending.unwrap_or_else(|| chunk.stained().intersect_rect(area))
+        };
+
+        stain.translate(N * coords.0);
+
+        for rect in stain.rects() {
+            stains.push(IRect { min: rect.min - reach, max: rect.max + reach });
+        }
+
+        world.insert(coords.0, (chunk, light));
+    }
+
+    stains.coalesce();
+
+    if stains.is_empty() {
+        return;
+    }
+
+    // The recomputed cells of each chunk and their new levels, as dense masks rather than sets of points, since the
+    // reach of a slow falloff covers hundreds of cells around every stain.
+    let mut dirty: HashMap<IVec2, (Vec<bool>, Vec<u8>)> = HashMap::new();
+
+    for chunk_coords in world.keys() {
+        let mut local = stains.intersect_rect(IRect { min: N * *chunk_coords, max: N * *chunk_coords + area.max });
+
+        if local.is_empty() {
+            continue;
+        }
+
+        local.translate(-N * *chunk_coords);
+
+        let mut mask = vec![false; Chunk::<T, N>::volume()];
+
+        local.apply(|point| mask[(N * point.y + point.x) as usize] = true);
+
+        dirty.insert(*chunk_coords, (mask, vec![0; Chunk::<T, N>::volume()]));
+    }
+
+    let index = |local: IVec2| (N * local.y + local.x) as usize;
+
+    let cell_at = |point: IVec2| {
+        let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
+
+        world.get(&chunk).and_then(|(chunk, _)| chunk.get(local).ok())
+    };
+
+    let level_at = |point: IVec2| {
+        let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
+
+        world.get(&chunk).map_or(0, |(_, light)| light.level(local))
+    };
+
+    let is_dirty = |dirty: &HashMap<IVec2, (Vec<bool>, Vec<u8>)>, point: IVec2| {
+        let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
+
+        dirty.get(&chunk).is_some_and(|(mask, _)| mask[index(local)])
+    };
+
+    let passed = |cell: &T, level: u8| {
+        if cell.is_opaque() { cell.emission() } else { level }.saturating_sub(settings.falloff)
+    };
+
+    let mut queue = BinaryHeap::new();
+    let mut seeds = Vec::new();
+
+    for (chunk_coords, (mask, _)) in dirty.iter() {
+        for (i, _) in mask.iter().enumerate().filter(|(_, dirty)| **dirty) {
+            let point = N * *chunk_coords + IVec2::new(i as i32 % N, i as i32 / N);
+            let mut level = cell_at(point).map_or(0, T::emission);
+
+            for neighbor in NEIGHBORS.map(|offset| point + offset) {
+                if is_dirty(&dirty, neighbor) {
+                    continue;
+                }
+
+                if let Some(cell) = cell_at(neighbor) {
+                    level = level.max(passed(cell, level_at(neighbor)));
+                }
+            }
+
+            if level > 0 {
+                seeds.push((*chunk_coords, i, level));
+            }
+        }
+    }
+
+    for (chunk_coords, i, level) in seeds {
+        if let Some((_, levels)) = dirty.get_mut(&chunk_coords) {
+            levels[i] = level;
+        }
+
+        let point = N * chunk_coords + IVec2::new(i as i32 % N, i as i32 / N);
+
+        queue.push((level, point.y, point.x));
+    }
+
+    while let Some((level, y, x)) = queue.pop() {
+        let point = IVec2::new(x, y);
+        let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
+
+        if dirty.get(&chunk).is_none_or(|(_, levels)| levels[index(local)] != level) {
+            continue;
+        }
+
+        let Some(cell) = cell_at(point) else {
+            continue;
+        };
+
+        let light = passed(cell, level);
+
+        if light == 0 {
+            continue;
+        }
+
+        for neighbor in NEIGHBORS.map(|offset| point + offset) {
+            let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(neighbor);
+
+            let Some((mask, levels)) = dirty.get_mut(&chunk) else {
+                continue;
+            };
+
+            if mask[index(local)] && levels[index(local)] < light {
+                levels[index(local)] = light;
+                queue.push((light, neighbor.y, neighbor.x));
+            }
+        }
+    }
+
+    for (chunk_coords, (mask, levels)) in dirty {
+        let Some((_, light)) = world.get_mut(&chunk_coords) else {
+            continue;
+        };
+
+        for (i, level) in levels.into_iter().enumerate().filter(|(i, _)| mask[*i]) {
+            if light.levels[i] != level {
+                let local = IVec2::new(i as i32 % N, i as i32 / N);
+
+                light.levels[i] = level;
+                StainPolicy::Bounding.accumulate(&mut light.changed, IRect::from_corners(local, local), area);
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::{mem::{self, size_of}, sync::Arc};
+
+use bevy::{ecs::component::Component, math::{IRect, IVec2}};
+use parking_lot::RwLock;
+
+use crate::{area::Area, cell::Cell, chunk::Chunk, grid::{check_disjoint, DisjointGrid, Grid}, stain::{StainPolicy, Stainable}, PowderkegError};
+
+/// A chunk storing each distinct cell once in a palette and every cell as an index into it, a byte per cell until
+/// the palette grows past 256 entries, then two, then four past 65536. Worlds of a few plain enum cells take a
+/// fraction of the memory of a [`Chunk`].
+///
+/// Cells tick on it as on any [`Stainable`] grid, but the systems of [`PowderkegPlugin`](crate::PowderkegPlugin)
+/// only simulate and draw [`Chunk`]s, so a paletted chunk is stored and converted back with
+/// [`PalettedChunk::to_chunk`] for the plugin to tick it.
+///
+/// Always dense, the absent cells of a sparse chunk are kept as whatever they hold. The chunk state is shared with
+/// the chunk it was made from rather than copied.
+#[derive(Component)]
+pub struct PalettedChunk<T: Cell, const N: i32> {
+    palette: Vec<T>,
+    /// How many cells use each palette entry, entries no cell uses are reused for new cells.
+    counts: Vec<u32>,
+    /// The entries no cell uses.
+    vacant: Vec<usize>,
+    indices: PaletteIndices,
+    stain: Area,
+    stain_policy: StainPolicy,
+    state: Arc<RwLock<T::State>>,
+}
+
+enum PaletteIndices {
+    Narrow(Vec<u8>),
+    Wide(Vec<u16>),
+    Full(Vec<u32>),
+}
+
+impl PaletteIndices {
+    fn get(&self, index: usize) -> usize {
+        match self {
+            PaletteIndices::Narrow(indices) => indices[index] as usize,
+            PaletteIndices::Wide(indices) => indices[index] as usize,
+            PaletteIndices::Full(indices) => indices[index] as usize,
+        }
+    }
+
+    /// Widens the indices first if `entry` does not fit, a chunk never has more entries than cells so `u32` is enough.
+    fn set(&mut self, index: usize, entry: usize) {
+        match self {
+            PaletteIndices::Narrow(indices) if entry > u16::MAX as usize => {
+                *self = PaletteIndices::Full(indices.iter().map(|entry| *entry as u32).collect());
+            },
+            PaletteIndices::Narrow(indices) if entry > u8::MAX as usize => {
+                *self = PaletteIndices::Wide(indices.iter().map(|entry| *entry as u16).collect());
+            },
+            PaletteIndices::Wide(indices) if entry > u16::MAX as usize => {
+                *self = PaletteIndices::Full(indices.iter().map(|entry| *entry as u32).collect());
+            },
+            _ => {},
+        }
+
+        match self {
+            PaletteIndices::Narrow(indices) => indices[index] = entry as u8,
+            PaletteIndices::Wide(indices) => indices[index] = entry as u16,
+            PaletteIndices::Full(indices) => indices[index] = entry as u32,
+        }
+    }
+
+    fn swap(&mut self, first: usize, second: usize) {
+        match self {
+            PaletteIndices::Narrow(indices) => indices.swap(first, second),
+            PaletteIndices::Wide(indices) => indices.swap(first, second),
+            PaletteIndices::Full(indices) => indices.swap(first, second),
+        }
+    }
+
+    fn heap_bytes(&self) -> usize {
+        match self {
+            PaletteIndices::Narrow(indices) => indices.capacity(),
+            PaletteIndices::Wide(indices) => indices.capacity() * size_of::<u16>(),
+            PaletteIndices::Full(indices) => indices.capacity() * size_of::<u32>(),
+        }
+    }
+}
+
+impl<T, const N: i32> PalettedChunk<T, N>
+where
+    T: Cell + Clone + PartialEq,
+{
+    pub fn from_chunk(chunk: &Chunk<T, N>) -> Self {
+        let mut paletted = Self {
+            palette: Vec::new(),
+            counts: Vec::new(),
+            vacant: Vec::new(),
+            indices: PaletteIndices::Narrow(Vec::new()),
+            stain: chunk.stained(),
+            stain_policy: chunk.stain_policy(),
+            state: chunk.state().clone(),
+        };
+
+        paletted.fill(chunk.cells());
+
+        paletted
+    }
+
+    pub fn to_chunk(&self) -> Chunk<T, N> {
+        let cells = (0..Chunk::<T, N>::volume())
+            .map(|index| self.palette[self.indices.get(index)].clone())
+            .collect();
+
+        Chunk::from_shared(cells, self.state.clone(), self.stain.clone(), self.stain_policy)
+    }
+
+    /// Every palette entry, including those no cell uses any more until they are reused or [`PalettedChunk::compact`].
+    pub fn palette(&self) -> &[T] {
+        &self.palette
+    }
+
+    /// Drops palette entries no cell uses and merges equal ones, such as cells given their own entry by `get_mut`
+    /// that ended up like another.
+    pub fn compact(&mut self) {
+        let palette = mem::take(&mut self.palette);
+        let indices = mem::replace(&mut self.indices, PaletteIndices::Narrow(Vec::new()));
+
+        self.counts.clear();
+        self.vacant.clear();
+        self.fill((0..Chunk::<T, N>::volume()).map(|index| &palette[indices.get(index)]));
+    }
+
+    /// The memory the palette and indices hold on the heap, in bytes.
+    pub fn heap_bytes(&self) -> usize {
+        self.palette.capacity() * size_of::<T>() + self.counts.capacity() * size_of::<u32>() + self.vacant.capacity() * size_of::<usize>() + self.indices.heap_bytes()
+    }
+
+    /// Palettes `cells` into an empty palette.
+    fn fill<'c>(&mut self, cells: impl IntoIterator<Item = &'c T>)
+    where
+        T: 'c,
+    {
+        self.indices = PaletteIndices::Narrow(vec![0; Chunk::<T, N>::volume()]);
+
+        for (index, cell) in cells.into_iter().enumerate() {
+            let entry = self.entry(cell);
+
+            self.counts[entry] += 1;
+            self.indices.set(index, entry);
+        }
+    }
+
+    /// The palette entry of `cell`, taking an unused entry or adding one if it is new. New entries start unused.
+    fn entry(&mut self, cell: &T) -> usize {
+        if let Some(entry) = self.palette.iter().zip(&self.counts).position(|(other, count)| *count > 0 && other == cell) {
+            return entry;
+        }
+
+        self.vacant_entry(cell.clone())
+    }
+
+    /// An unused palette entry now holding `cell`.
+    fn vacant_entry(&mut self, cell: T) -> usize {
+        if let Some(entry) = self.vacant.pop() {
+            self.palette[entry] = cell;
+
+            return entry;
+        }
+
+        self.palette.push(cell);
+        self.counts.push(0);
+        self.palette.len() - 1
+    }
+
+    /// Points the cell at `index` to `entry`.
+    fn assign(&mut self, index: usize, entry: usize) {
+        let old = self.indices.get(index);
+
+        if old == entry {
+            return;
+        }
+
+        self.counts[old] -= 1;
+
+        if self.counts[old] == 0 {
+            self.vacant.push(old);
+        }
+
+        self.counts[entry] += 1;
+        self.indices.set(index, entry);
+    }
+
+    /// The palette entry of the cell at `index`, copied into an entry of its own first if other cells share it.
+    fn own_entry(&mut self, index: usize) -> usize {
+        let entry = self.indices.get(index);
+
+        if self.counts[entry] == 1 {
+            return entry;
+        }
+
+        let own = self.vacant_entry(self.palette[entry].clone());
+
+        self.assign(index, own);
+
+        own
+    }
+
+    fn index(point: IVec2) -> Option<usize> {
+        if point.x < 0 || point.y < 0 || point.x >= N || point.y >= N {
+            None
+        } else {
+            Some((N * point.y + point.x) as usize)
+        }
+    }
+}
+
+impl<T, const N: i32> Grid for PalettedChunk<T, N>
+where
+    T: Cell + Clone + PartialEq,
+{
+    type Cell = T;
+
+    fn get(&self, point: IVec2) -> Result<&T, PowderkegError<T>> {
+        let index = Self::index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+
+        Ok(&self.palette[self.indices.get(index)])
+    }
+
+    /// Gives the cell a palette entry of its own if other cells share its entry, cells borrowed again keep theirs.
+    fn get_mut(&mut self, point: IVec2) -> Result<&mut T, PowderkegError<T>> {
+        let index = Self::index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+        let entry = self.own_entry(index);
+
+        self.stain_point(point);
+
+        Ok(&mut self.palette[entry])
+    }
+
+    fn replace(&mut self, point: IVec2, cell: T) -> Result<T, PowderkegError<T>> {
+        let index = Self::index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+
+        let old = self.palette[self.indices.get(index)].clone();
+        let entry = self.entry(&cell);
+
+        self.assign(index, entry);
+        self.stain_point(point);
+
+        if T::HOOKS {
+            let mut state = self.state.write();
+
+            old.on_destroy(point, &mut state);
+            cell.on_create(point, &mut state);
+        }
+
+        Ok(old)
+    }
+
+    fn swap(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<T>> {
+        let first_index = Self::index(first).ok_or(PowderkegError::LocalOutOfBounds(first))?;
+        let second_index = Self::index(second).ok_or(PowderkegError::LocalOutOfBounds(second))?;
+
+        self.indices.swap(first_index, second_index);
+
+        self.stain_point(first);
+        self.stain_point(second);
+
+        Ok(())
+    }
+
+    fn get_state(&self, point: IVec2) -> Result<Arc<RwLock<T::State>>, PowderkegError<T>> {
+        Self::index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+
+        Ok(self.state.clone())
+    }
+
+    fn covers(&self) -> Area {
+        Chunk::<T, N>::area().into()
+    }
+}
+
+impl<T, const N: i32> DisjointGrid for PalettedChunk<T, N>
+where
+    T: Cell + Clone + PartialEq,
+{
+    /// Gives every cell an entry of its own as [`PalettedChunk::get_mut`] does.
+    fn get_many_mut<const K: usize>(&mut self, points: [IVec2; K]) -> Result<[&mut T; K], PowderkegError<T>> {
+        check_disjoint(&points)?;
+
+        let mut indices = [0; K];
+
+        for (index, point) in indices.iter_mut().zip(points) {
+            *index = Self::index(point).ok_or(PowderkegError::LocalOutOfBounds(point))?;
+        }
+
+        let entries = indices.map(|index| self.own_entry(index));
+
+        for point in points {
+            self.stain_point(point);
+        }
+
+        let mut cells: Vec<_> = self.palette.iter_mut().map(Some).collect();
+
+        Ok(entries.map(|entry| cells[entry].take().expect("every cell owns a different entry")))
+    }
+}
+
+impl<T, const N: i32> Stainable for PalettedChunk<T, N>
+where
+    T: Cell + Clone + PartialEq,
+{
+    fn stained(&self) -> Area {
+        self.stain.intersect_rect(Chunk::<T, N>::area())
+    }
+
+    fn clear_stain(&mut self) {
+        self.stain = Area::Empty;
+    }
+
+    fn stain(&mut self, area: IRect) {
+        if let Some(area) = Chunk::<T, N>::clamp_stain(area) {
+            self.stain_policy.accumulate(&mut self.stain, area, Chunk::<T, N>::area());
+        }
+    }
+
+    fn stain_point(&mut self, point: IVec2) {
+        self.stain(IRect::from_corners(point, point));
+    }
+
+    fn stain_policy(&self) -> StainPolicy {
+        self.stain_policy
+    }
+}
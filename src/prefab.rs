@@ -0,0 +1,92 @@
+use bevy::math::IVec2;
+
+#[derive(Debug, Clone)]
+pub struct Prefab<T> {
+    size: IVec2,
+    cells: Vec<Option<T>>,
+}
+
+impl<T> Prefab<T> {
+    /// Creates a prefab from row-major cells, `None` cells are masked and left untouched when stamping.
+    pub fn new(size: IVec2, cells: Vec<Option<T>>) -> Self {
+        assert!(size.x >= 0 && size.y >= 0);
+        assert_eq!(cells.len(), size.x as usize * size.y as usize);
+
+        Self { size, cells }
+    }
+
+    pub fn size(&self) -> IVec2 {
+        self.size
+    }
+
+    pub fn get(&self, local: IVec2) -> Option<&T> {
+        if local.x < 0 || local.y < 0 || local.x >= self.size.x || local.y >= self.size.y {
+            return None;
+        }
+
+        self.cells[(self.size.x * local.y + local.x) as usize].as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(index, cell)| {
+                let index = index as i32;
+
+                cell.as_ref().map(|cell| (IVec2::new(index % self.size.x, index / self.size.x), cell))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PrefabRotation {
+    #[default]
+    None,
+    Quarter,
+    Half,
+    ThreeQuarters,
+}
+
+/// Remaps a prefab's layout when stamping, flips are applied before the counter-clockwise rotation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PrefabTransform {
+    pub rotation: PrefabRotation,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl PrefabTransform {
+    pub fn rotated(rotation: PrefabRotation) -> Self {
+        Self { rotation, ..Default::default() }
+    }
+
+    pub fn with_flip_x(mut self, flip_x: bool) -> Self {
+        self.flip_x = flip_x;
+        self
+    }
+
+    pub fn with_flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+
+    pub fn footprint(&self, size: IVec2) -> IVec2 {
+        match self.rotation {
+            PrefabRotation::None | PrefabRotation::Half => size,
+            PrefabRotation::Quarter | PrefabRotation::ThreeQuarters => IVec2::new(size.y, size.x),
+        }
+    }
+
+    pub fn apply(&self, local: IVec2, size: IVec2) -> IVec2 {
+        let x = if self.flip_x { size.x - 1 - local.x } else { local.x };
+        let y = if self.flip_y { size.y - 1 - local.y } else { local.y };
+
+        match self.rotation {
+            PrefabRotation::None => IVec2::new(x, y),
+            PrefabRotation::Quarter => IVec2::new(size.y - 1 - y, x),
+            PrefabRotation::Half => IVec2::new(size.x - 1 - x, size.y - 1 - y),
+            PrefabRotation::ThreeQuarters => IVec2::new(y, size.x - 1 - x),
+        }
+    }
+}
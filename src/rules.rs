@@ -0,0 +1,64 @@
+use bevy::math::IVec2;
+use rand::Rng;
+
+use crate::{cell::{Cell, TickInput}, stain::Stainable, PowderkegError};
+
+pub trait Solid {
+    fn is_solid(&self) -> bool;
+}
+
+pub trait HasDensity {
+    fn density(&self) -> f32;
+}
+
+/// Moves the cell along [`TickInput::gravity`], or diagonally in a random order, into a non-solid cell that is less dense than it.
+///
+/// Returns where the cell moved to, if it moved.
+pub fn try_fall<T, G>(input: &mut TickInput<'_, T, G>, rng: &mut impl Rng) -> Result<Option<IVec2>, PowderkegError<T>>
+where
+    T: Cell + Solid + HasDensity,
+    G: Stainable<Cell = T>,
+{
+    let gravity = input.gravity();
+
+    try_move(input, rng, gravity, |this, other| !other.is_solid() && other.density() < this.density())
+}
+
+/// Moves the cell against [`TickInput::gravity`], or diagonally in a random order, into a non-solid cell that is more dense than it.
+///
+/// Returns where the cell moved to, if it moved.
+pub fn try_float<T, G>(input: &mut TickInput<'_, T, G>, rng: &mut impl Rng) -> Result<Option<IVec2>, PowderkegError<T>>
+where
+    T: Cell + Solid + HasDensity,
+    G: Stainable<Cell = T>,
+{
+    let gravity = input.gravity();
+
+    try_move(input, rng, -gravity, |this, other| !other.is_solid() && other.density() > this.density())
+}
+
+fn try_move<T, G>(
+    input: &mut TickInput<'_, T, G>,
+    rng: &mut impl Rng,
+    direction: IVec2,
+    can_displace: impl Fn(&T, &T) -> bool,
+) -> Result<Option<IVec2>, PowderkegError<T>>
+where
+    T: Cell,
+    G: Stainable<Cell = T>,
+{
+    let side = if rng.gen_bool(0.5) { direction.perp() } else { -direction.perp() };
+
+    for offset in [direction, direction + side, direction - side] {
+        let target = input.origin + offset;
+
+        if can_displace(input.grid.get(input.origin)?, input.grid.get(target)?) {
+            input.grid.swap(input.origin, target)?;
+            input.grid.stain_around(input.origin, 1);
+
+            return Ok(Some(target));
+        }
+    }
+
+    Ok(None)
+}
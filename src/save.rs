@@ -0,0 +1,161 @@
+use std::{fs, io, path::Path};
+
+use bevy::{hierarchy::despawn_with_children_recursive, prelude::*};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{cell::Renderable, chunk::{Chunk, ChunkBundle, ChunkCoords}, viewer::DrawStained};
+
+/// How [`save_world`] and [`load_world`] store the world, the defaults are used when the resource is absent.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PowderkegWorldIo {
+    pub format: SaveFormat,
+    /// Whether chunks drawing their stain keep doing so once loaded, otherwise no loaded chunk does.
+    pub preserve_draw_stained: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SaveFormat {
+    /// Readable, for debugging, but far larger and slower than binary.
+    #[default]
+    Ron,
+    #[cfg(feature = "bincode")]
+    Bincode,
+}
+
+#[derive(Debug, Error)]
+pub enum WorldIoError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    RonWrite(#[from] ron::Error),
+    #[error(transparent)]
+    RonRead(#[from] ron::error::SpannedError),
+    #[cfg(feature = "bincode")]
+    #[error(transparent)]
+    Bincode(#[from] bincode::Error),
+}
+
+impl SaveFormat {
+    pub(crate) fn write(self, path: &Path, value: &impl Serialize) -> Result<(), WorldIoError> {
+        match self {
+            SaveFormat::Ron => fs::write(path, ron::to_string(value)?)?,
+            #[cfg(feature = "bincode")]
+            SaveFormat::Bincode => fs::write(path, bincode::serialize(value)?)?,
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn read<V: DeserializeOwned>(self, path: &Path) -> Result<V, WorldIoError> {
+        match self {
+            SaveFormat::Ron => Ok(ron::from_str(&fs::read_to_string(path)?)?),
+            #[cfg(feature = "bincode")]
+            SaveFormat::Bincode => Ok(bincode::deserialize(&fs::read(path)?)?),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedWorld<C> {
+    parent: Transform,
+    chunks: Vec<SavedChunk<C>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedChunk<C> {
+    coords: IVec2,
+    transform: Transform,
+    draw_stained: bool,
+    chunk: C,
+}
+
+/// Saves every chunk with its coordinates and transform to one file at `path`, returning how many were saved.
+///
+/// Chunks are expected to share one parent as [`SpawnChunkGrid`](crate::chunk::SpawnChunkGrid) spawns them, whose
+/// transform is saved alongside them.
+pub fn save_world<T, const N: i32>(world: &mut World, path: impl AsRef<Path>) -> Result<usize, WorldIoError>
+where
+    T: Renderable + Serialize,
+    T::State: Serialize,
+{
+    let io = world.get_resource::<PowderkegWorldIo>().copied().unwrap_or_default();
+
+    let mut query = world.query::<(&ChunkCoords<N>, &Chunk<T, N>, &Transform, Option<&Parent>, Has<DrawStained>)>();
+    let mut chunks: Vec<_> = query.iter(world).collect();
+
+    // Queries visit chunks in no particular order, sorting them keeps saves of the same world identical.
+    chunks.sort_unstable_by_key(|(coords, ..)| (coords.0.y, coords.0.x));
+
+    let parent = chunks
+        .iter()
+        .find_map(|(.., parent, _)| *parent)
+        .and_then(|parent| world.get::<Transform>(parent.get()))
+        .copied()
+        .unwrap_or_default();
+
+    let saved = SavedWorld {
+        parent,
+        chunks: chunks
+            .iter()
+            .map(|(coords, chunk, transform, _, draw_stained)| SavedChunk {
+                coords: coords.0,
+                transform: **transform,
+                draw_stained: io.preserve_draw_stained && *draw_stained,
+                chunk: *chunk,
+            })
+            .collect(),
+    };
+
+    io.format.write(path.as_ref(), &saved)?;
+
+    Ok(saved.chunks.len())
+}
+
+/// Replaces every chunk with those saved at `path` by [`save_world`]. They are spawned under the parent of the chunks
+/// they replace, which takes the saved transform, so anything keeping that parent stays valid, or under a new parent
+/// if there were no chunks. Returns the parent.
+pub fn load_world<T, const N: i32>(world: &mut World, path: impl AsRef<Path>) -> Result<Entity, WorldIoError>
+where
+    T: Renderable + DeserializeOwned,
+    T::State: DeserializeOwned,
+{
+    let io = world.get_resource::<PowderkegWorldIo>().copied().unwrap_or_default();
+    let saved: SavedWorld<Chunk<T, N>> = io.format.read(path.as_ref())?;
+
+    let mut query = world.query_filtered::<(Entity, Option<&Parent>), With<Chunk<T, N>>>();
+    let existing: Vec<_> = query.iter(world).map(|(entity, parent)| (entity, parent.map(Parent::get))).collect();
+
+    let parent = existing.iter().find_map(|(_, parent)| *parent);
+
+    for (entity, _) in existing {
+        despawn_with_children_recursive(world, entity);
+    }
+
+    let parent = match parent.filter(|parent| world.get_entity(*parent).is_some()) {
+        Some(parent) => {
+            world.entity_mut(parent).insert(saved.parent);
+            parent
+        },
+        None => world.spawn(SpatialBundle::from_transform(saved.parent)).id(),
+    };
+
+    world
+        .entity_mut(parent)
+        .with_children(|children| {
+            for saved in saved.chunks {
+                let mut chunk = children.spawn(ChunkBundle {
+                    chunk: saved.chunk,
+                    coords: ChunkCoords::<N>(saved.coords),
+                    transform: TransformBundle::from_transform(saved.transform),
+                    visibility: default(),
+                });
+
+                if io.preserve_draw_stained && saved.draw_stained {
+                    chunk.insert(DrawStained);
+                }
+            }
+        });
+
+    Ok(parent)
+}
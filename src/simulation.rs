@@ -1,11 +1,11 @@
-use std::{marker::PhantomData, mem::swap, sync::Arc};
+use std::{marker::PhantomData, mem::{self, swap}, ops::{Deref, DerefMut}, sync::Arc, time::{Duration, Instant}};
 
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic}, ecs::system::SystemParam, prelude::*, tasks::ComputeTaskPool, utils::{HashMap, HashSet}};
 use crossbeam_channel::unbounded;
 use parking_lot::RwLock;
-use rand::thread_rng;
+use rand::{rngs::SmallRng, RngCore, SeedableRng};
 
-use crate::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkCoords}, grid::Grid, stain::Stainable, area::Area, PowderkegError, PowderkegSet};
+use crate::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{decompose_region, Chunk, ChunkBundle, ChunkCoords, ObserveChange}, grid::{check_disjoint, swap_cell_state, DisjointGrid, Grid}, stain::{ChangeKind, Stainable}, area::{Area, StainOrder}, streaming::stream_chunks, PowderkegError, PowderkegSet};
 
 pub(crate) struct PowderkegSimulationPlugin<T: Renderable + Send + Sync + 'static, const N: i32>(PhantomData<T>);
 
@@ -25,266 +25,1884 @@ where
     fn build(&self, app: &mut App) {
         app
             .init_resource::<PowderkegTickRate>()
-            .add_systems(Update, simulate_powderkeg::<T, N>.in_set(PowderkegSet::Tick));
+            .init_resource::<MaxTicksPerFrame>()
+            .init_resource::<PowderkegErrors<T>>()
+            .init_resource::<BorderBehavior<T>>()
+            .init_resource::<SimulationMode<T>>()
+            .init_resource::<TickReport<T>>()
+            .init_resource::<PowderkegTick>()
+            .init_resource::<PowderkegPaused>()
+            .init_resource::<OutOfWorldPolicy>()
+            .init_resource::<StainOrder>()
+            .init_resource::<SimulationSchedule>()
+            .init_resource::<PowderkegRng>()
+            .init_resource::<ChunkSleepThreshold>()
+            .init_resource::<WorldTopology>()
+            .init_resource::<Gravity>()
+            .add_event::<StepOnce>()
+            .add_event::<CellChanged<T>>()
+            .add_systems(Update, (
+                simulate_powderkeg::<T, N>,
+                count_cell_variants::<T, N>,
+            ).chain().in_set(PowderkegSet::Tick))
+            .add_systems(Update, stream_chunks::<T, N>.before(PowderkegSet::Tick));
+
+        if !app.world.contains_resource::<ChunkActivity>() {
+            app
+                .init_resource::<ChunkActivity>()
+                .init_resource::<TickTotals>()
+                .register_diagnostic(Diagnostic::new(AWAKE_CHUNKS))
+                .register_diagnostic(Diagnostic::new(ASLEEP_CHUNKS))
+                .register_diagnostic(Diagnostic::new(CELLS_TICKED))
+                .register_diagnostic(Diagnostic::new(DIRTY_CHUNKS))
+                .register_diagnostic(Diagnostic::new(DEFERRED_CELLS))
+                .register_diagnostic(Diagnostic::new(CROSS_CHUNK_STAINS))
+                .add_systems(First, (clear_chunk_activity, clear_tick_totals))
+                .add_systems(Last, (measure_chunk_activity, measure_tick_totals));
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct PowderkegTickRate(pub f32);
+
+impl Default for PowderkegTickRate {
+    fn default() -> Self {
+        Self(16.0)
+    }
+}
+
+/// The most ticks a single frame runs to keep up with the [`PowderkegTickRate`], whole ticks still owed once the
+/// limit is reached are dropped rather than carried over so a machine that cannot keep up does not fall further behind.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaxTicksPerFrame(pub u32);
+
+impl Default for MaxTicksPerFrame {
+    fn default() -> Self {
+        Self(4)
+    }
+}
+
+/// Limits how long the ticks of a single frame may spend ticking cells, unlimited when the resource is absent.
+/// No further ticks run in a frame once its budget is spent.
+///
+/// The clock is read every few cells, so a tick can overrun its budget by the time those take. The first tick of a
+/// frame and the first cells of each chunk always run, so however small the budget the simulation still advances.
+///
+/// Cells left unprocessed when the budget runs out are re-stained and tick on the next frame instead,
+/// so a tick is no longer atomic: part of the world may have advanced a step while the rest has not,
+/// and the order in which cells move is no longer uniformly random across the stain. Chunks the budget cut short are
+/// ticked before the others on the next frame, so the chunks that miss out take turns.
+///
+/// The budget covers every tick of a frame together rather than each one, so with a [`MaxTicksPerFrame`] above one
+/// a frame may run fewer ticks than it owes, the ticks left over are dropped as those past the limit are.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct TickTimeBudget(pub Duration);
+
+/// Drives the simulation from time advanced manually instead of `Time<Virtual>`, for example in lockstep networking.
+///
+/// Each frame the simulation runs the time advanced since the last frame, so at a [`PowderkegTickRate`] of `rate`
+/// advancing by `1.0 / rate` seconds runs one tick. Time advanced while paused is kept until the simulation resumes.
+///
+/// The clock is shared by every simulated cell type, each reads how far it has advanced without consuming it.
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct PowderkegClock {
+    elapsed: f64,
+}
+
+impl PowderkegClock {
+    pub fn advance(&mut self, seconds: f32) {
+        self.elapsed += seconds as f64;
+    }
+
+    /// The total time advanced since the clock was created.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+}
+
+/// What the world pass does with writes to points outside every spawned chunk, through `replace`, `swap` or `get_mut`
+/// alike. Reads there are errors unless there is a [`BorderBehavior`] cell.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutOfWorldPolicy {
+    /// Drops the write, a skipped swap leaves both cells in place. `get_mut` has no cell to hand out for the write to
+    /// be dropped into, so it still fails with [`PowderkegError::ChunkOutOfBounds`].
+    Skip,
+    /// Spawns the missing chunk with the [`ChunkSpawner`] then writes, erroring without a spawner.
+    Spawn,
+    /// Fails the write with [`PowderkegError::ChunkOutOfBounds`], the error the cell's tick returns unless it handles it.
+    #[default]
+    Error,
+}
+
+/// What cells see past the edge of the world. With a border cell, reads of points outside every chunk return it rather
+/// than an error, whatever the [`OutOfWorldPolicy`]. Writes there still follow the policy, except that a write it would
+/// skip or fail is dropped instead.
+#[derive(Resource)]
+pub enum BorderBehavior<T: Cell> {
+    /// A wall of the cell, nothing crosses it.
+    Solid(T),
+    /// A void of the cell, cells swapped into it leave the world and the function's copy of the cell takes their place.
+    Empty(T, fn(&T) -> T),
+    /// No border, points outside every chunk are errors as the [`OutOfWorldPolicy`] decides.
+    Error,
+}
+
+impl<T: Cell + Clone> BorderBehavior<T> {
+    pub fn empty(cell: T) -> Self {
+        Self::Empty(cell, T::clone)
+    }
+}
+
+impl<T: Cell> BorderBehavior<T> {
+    /// The cell read at points outside every chunk, `None` without a border.
+    pub fn cell(&self) -> Option<&T> {
+        match self {
+            BorderBehavior::Solid(cell) | BorderBehavior::Empty(cell, _) => Some(cell),
+            BorderBehavior::Error => None,
+        }
+    }
+}
+
+// Deriving would require `T: Default`.
+#[allow(clippy::derivable_impls)]
+impl<T: Cell> Default for BorderBehavior<T> {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+/// Stops the simulation from ticking while edits through the grid API still stain chunks and are rendered.
+///
+/// Stains are kept rather than cleared while paused, so every edited cell ticks once the simulation resumes.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PowderkegPaused(pub bool);
+
+/// The direction cells fall in, which cells read through [`TickInput::gravity`] rather than assuming down.
+///
+/// Settled cells are not woken by a change of gravity, stain the chunks they are in, for example with
+/// [`Chunk::mark_dirty`], so they tick and fall the new way.
+///
+/// A cell's [`Cell::range`] must reach every cell it can fall into under any gravity it will see, a range of one cell
+/// around it for gravity along either axis. Cells with the usual falling range of `IRect::new(-1, -1, 1, 0)` fall
+/// down only, under rotated gravity those next to a chunk's edge reach outside their range and fail to tick.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Gravity(pub IVec2);
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self(IVec2::NEG_Y)
+    }
+}
+
+/// Runs one tick while [`PowderkegPaused`] whatever the time elapsed, including the world pass across chunk edges.
+/// Each event sent in a frame runs a tick, up to [`MaxTicksPerFrame`], and events sent while running are ignored.
+#[derive(Event, Debug, Default, Clone, Copy)]
+pub struct StepOnce;
+
+/// The seed everything random in the simulation derives from, given the same seed and initial chunks two runs produce
+/// the same chunks tick for tick. Defaults to a random seed.
+///
+/// Each tick derives its own seed, from which each chunk of the in-chunk pass and each cell of
+/// [`TickInput::rng`] derive theirs, so the result does not depend on the order chunks are ticked in parallel.
+/// Cells must draw from [`TickInput::rng`] rather than `thread_rng` and [`TickTimeBudget`] must be absent, as
+/// where the budget runs out depends on the machine.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PowderkegRng {
+    pub seed: u64,
+}
+
+impl Default for PowderkegRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+impl PowderkegRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// The seed of tick number `tick`.
+    pub fn tick_seed(&self, tick: u64) -> u64 {
+        point_seed(self.seed, IVec2::new(tick as i32, (tick >> 32) as i32))
+    }
+}
+
+/// Mixes `point` into `seed`, like [`ChunkCoords::rng`] does with the chunk coordinates.
+fn point_seed(seed: u64, point: IVec2) -> u64 {
+    let point = (point.x as u32 as u64) | ((point.y as u32 as u64) << 32);
+
+    SmallRng::seed_from_u64(seed ^ point.wrapping_mul(0x9e37_79b9_7f4a_7c15)).next_u64()
+}
+
+/// The shape of the world the simulation's world pass reads and writes through.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorldTopology {
+    /// Points past the spawned chunks are outside the world, see [`OutOfWorldPolicy`].
+    #[default]
+    Open,
+    /// Wraps around the chunks from `min` up to but excluding `max` on both axes, so a cell leaving the right edge
+    /// enters at the left edge. Stains reaching past an edge wrap with it.
+    Toroidal {
+        min: IVec2,
+        max: IVec2,
+    },
+}
+
+impl WorldTopology {
+    /// Wraps the world `point` of a grid of `N` cell chunks into the world's bounds.
+    pub fn wrap<const N: i32>(&self, point: IVec2) -> IVec2 {
+        match *self {
+            WorldTopology::Open => point,
+            WorldTopology::Toroidal { min, max } => min * N + (point - min * N).rem_euclid((max - min) * N),
+        }
+    }
+
+    /// Wraps the chunk coordinates `chunk` into the world's bounds.
+    pub fn wrap_chunk(&self, chunk: IVec2) -> IVec2 {
+        match *self {
+            WorldTopology::Open => chunk,
+            WorldTopology::Toroidal { min, max } => min + (chunk - min).rem_euclid(max - min),
+        }
+    }
+}
+
+/// Only chunks overlapping this world rect of cells are ticked, every chunk is when absent.
+///
+/// Chunks outside keep their stain and resume where they left off once they overlap it again. Cells near its edge
+/// still read and write the chunks just outside, whose stains then wait for them to become active.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActiveRegion(pub IRect);
+
+impl ActiveRegion {
+    /// The cells of a chunk grid spawned under `grid_transform` that fall within the world space `view`, such as the
+    /// area a camera sees, grown by `margin` cells so cells just out of view keep moving.
+    pub fn from_view<const N: i32>(view: Rect, grid_transform: &GlobalTransform, margin: i32) -> Self {
+        let inverse = grid_transform.affine().inverse();
+
+        let first = inverse.transform_point3(view.min.extend(0.0)).truncate();
+        let second = inverse.transform_point3(view.max.extend(0.0)).truncate();
+
+        // Chunk meshes are centered on their chunk's corner times `N`.
+        let offset = Vec2::splat(N as f32 / 2.0);
+
+        Self(IRect {
+            min: (first.min(second) + offset).floor().as_ivec2() - margin,
+            max: (first.max(second) + offset).floor().as_ivec2() + margin,
+        })
+    }
+
+    pub fn overlaps_chunk<const N: i32>(&self, coords: &ChunkCoords<N>) -> bool {
+        let min = coords.offset();
+        let max = min + IVec2::splat(N - 1);
+
+        min.x <= self.0.max.x && self.0.min.x <= max.x && min.y <= self.0.max.y && self.0.min.y <= max.y
+    }
+}
+
+/// How many consecutive ticks a chunk must have nothing stained before it falls asleep, `0` keeps every chunk awake.
+///
+/// Asleep chunks are skipped entirely until something stains them, whether a neighbor's cells reaching across the
+/// boundary onto cells that are not [inert](Cell::is_inert) or edits through the grid API. Writes through
+/// [`Chunk::cells_mut`] are not stained so do not wake them.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkSleepThreshold(pub u32);
+
+impl Default for ChunkSleepThreshold {
+    fn default() -> Self {
+        Self(8)
+    }
+}
+
+/// Diagnostic of how many chunks the most recent tick visited, summed across every simulated cell type.
+pub const AWAKE_CHUNKS: DiagnosticPath = DiagnosticPath::const_new("powderkeg/awake_chunks");
+
+/// Diagnostic of how many chunks the most recent tick skipped for being asleep, summed across every simulated cell
+/// type.
+pub const ASLEEP_CHUNKS: DiagnosticPath = DiagnosticPath::const_new("powderkeg/asleep_chunks");
+
+/// Diagnostic of [`TickReport::cells_ticked`], summed across every simulated cell type on frames where a tick ran.
+pub const CELLS_TICKED: DiagnosticPath = DiagnosticPath::const_new("powderkeg/cells_ticked");
+
+/// Diagnostic of [`TickReport::dirty_chunks`], summed across every simulated cell type on frames where a tick ran.
+pub const DIRTY_CHUNKS: DiagnosticPath = DiagnosticPath::const_new("powderkeg/dirty_chunks");
+
+/// Diagnostic of [`TickReport::deferred`], summed across every simulated cell type on frames where a tick ran.
+pub const DEFERRED_CELLS: DiagnosticPath = DiagnosticPath::const_new("powderkeg/deferred_cells");
+
+/// Diagnostic of [`TickReport::cross_chunk_stains`], summed across every simulated cell type on frames where a tick ran.
+pub const CROSS_CHUNK_STAINS: DiagnosticPath = DiagnosticPath::const_new("powderkeg/cross_chunk_stains");
+
+/// How many chunks were awake and asleep during the last tick of this frame, summed across every simulated cell type.
+/// Both are zero on frames where no tick ran.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkActivity {
+    pub awake: usize,
+    pub asleep: usize,
+}
+
+/// Sends a [`CellChanged`] event for every cell a tick replaces or swaps where `filter(from, to)` passes, only while
+/// this resource exists. Writes through `get_mut` are not observed, nor are edits made between ticks.
+#[derive(Resource)]
+pub struct EmitCellChanged<T: Cell> {
+    observe: ObserveChange<T>,
+}
+
+impl<T: Cell + Clone> EmitCellChanged<T> {
+    pub fn new(filter: impl Fn(&T, &T) -> bool + Send + Sync + 'static) -> Self {
+        Self { observe: Arc::new(move |from: &T, to: &T| filter(from, to).then(|| (from.clone(), to.clone()))) }
+    }
+}
+
+/// A cell changed from `from` to `to` at the world point `world` during a tick, see [`EmitCellChanged`].
+#[derive(Event, Debug, Clone)]
+pub struct CellChanged<T: Cell> {
+    pub world: IVec2,
+    pub from: T,
+    pub to: T,
+}
+
+/// How many ticks have run since the simulation started.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PowderkegTick(pub u64);
+
+/// Errors raised while ticking cells during the ticks of the most recent frame that ticked.
+#[derive(Resource)]
+pub struct PowderkegErrors<T: Cell> {
+    pub errors: Vec<SimulationError<T>>,
+}
+
+impl<T: Cell> Default for PowderkegErrors<T> {
+    fn default() -> Self {
+        Self { errors: Vec::new() }
+    }
+}
+
+/// How the chunks of a tick are scheduled.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimulationSchedule {
+    /// Every chunk ticks in parallel on its own, cells whose range crosses a chunk's edge are deferred to a serial
+    /// pass once all chunks have ticked.
+    #[default]
+    Deferred,
+    /// Chunks tick in parallel sub-passes, each alongside its eight neighbors so cells near its edge tick with the
+    /// rest. Chunks of a sub-pass are three apart on both axes, as alternating by `(x + y) % 2` would still let
+    /// diagonal chunks share a neighbor. Only cells reaching past the neighbors are deferred.
+    Checkerboard,
+}
+
+/// How a tick writes the cells it ticks.
+#[derive(Resource)]
+pub enum SimulationMode<T: Cell> {
+    /// Cells read what earlier cells of the tick wrote, so what a cell does can depend on the order cells tick in.
+    InPlace,
+    /// Cells read the world as the tick found it while their writes land in a back buffer, which becomes what is read
+    /// once the tick ends, so every cell updates at once whatever the order. The function copies a chunk's cells into
+    /// the front buffer the first time the tick writes to it.
+    ///
+    /// Swaps move whatever was last written on either side, so rules built on swaps still keep every cell, though a
+    /// cell swapped into a place this tick can be carried on by the cell that started there.
+    DoubleBuffered(fn(&T) -> T),
+}
+
+impl<T: Cell + Clone> SimulationMode<T> {
+    /// [`SimulationMode::DoubleBuffered`] copying the cells with [`Clone`].
+    pub fn double_buffered() -> Self {
+        Self::DoubleBuffered(T::clone)
+    }
+}
+
+// Deriving would require `T: Default`.
+#[allow(clippy::derivable_impls)]
+impl<T: Cell> Default for SimulationMode<T> {
+    fn default() -> Self {
+        Self::InPlace
+    }
+}
+
+/// Counts the cells matching `conserved` before and after every tick, logging an error whenever a tick changes the
+/// total. Counting every cell each tick is costly so this only runs while the resource exists.
+///
+/// Chunks spawned during the tick are not counted, edits made between ticks are not violations.
+#[derive(Resource)]
+pub struct ConservationCheck<T: Cell> {
+    conserved: Box<dyn Fn(&T) -> bool + Send + Sync>,
+    /// How many ticks have changed the total so far.
+    pub violations: usize,
+}
+
+impl<T: Cell> ConservationCheck<T> {
+    pub fn new(conserved: impl Fn(&T) -> bool + Send + Sync + 'static) -> Self {
+        Self { conserved: Box::new(conserved), violations: 0 }
+    }
+
+    fn count<const N: i32>(&self, chunk: &Chunk<T, N>) -> usize {
+        chunk.iter().filter(|(_, cell)| (self.conserved)(cell)).count()
+    }
+}
+
+/// A summary of the most recent tick of the `T` cells, updated only on frames where a tick ran.
+#[derive(Resource)]
+pub struct TickReport<T: Cell> {
+    /// Cells whose `tick` was called, in either pass.
+    pub cells_ticked: usize,
+    /// Ticked cells that reported themselves unstable.
+    pub cells_unstable: usize,
+    pub errors: usize,
+    /// Cells whose range crossed their chunk's edge and were ticked in the world pass instead.
+    pub deferred: usize,
+    /// Chunks that had any stain at the start of the tick.
+    pub dirty_chunks: usize,
+    /// Stains that reached past the edge of the chunk they were made in and were passed on to its neighbors.
+    pub cross_chunk_stains: usize,
+    pub duration: Duration,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Cell> Default for TickReport<T> {
+    fn default() -> Self {
+        Self {
+            cells_ticked: 0,
+            cells_unstable: 0,
+            errors: 0,
+            deferred: 0,
+            dirty_chunks: 0,
+            cross_chunk_stains: 0,
+            duration: Duration::ZERO,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// The last [`TickReport`] of each cell type that ticked this frame summed, for the diagnostics.
+#[derive(Resource, Default)]
+struct TickTotals {
+    ran: bool,
+    cells_ticked: usize,
+    dirty_chunks: usize,
+    deferred: usize,
+    cross_chunk_stains: usize,
+}
+
+impl TickTotals {
+    fn add<T: Cell>(&mut self, report: &TickReport<T>) {
+        self.ran = true;
+        self.cells_ticked += report.cells_ticked;
+        self.dirty_chunks += report.dirty_chunks;
+        self.deferred += report.deferred;
+        self.cross_chunk_stains += report.cross_chunk_stains;
+    }
+}
+
+/// How many cells of each [`Cell::variant`] exist across every chunk, updated each frame only while this resource exists.
+///
+/// Only chunks changed since the last count are recounted, in parallel, and the counts of every chunk are merged in
+/// coordinate order so the result is reproducible.
+#[derive(Resource)]
+pub struct CellHistogram<T: Cell> {
+    pub counts: Vec<usize>,
+    chunk_counts: HashMap<Entity, Vec<usize>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Cell> CellHistogram<T> {
+    pub fn count(&self, variant: usize) -> usize {
+        self.counts.get(variant).copied().unwrap_or(0)
+    }
+}
+
+impl<T: Cell> Default for CellHistogram<T> {
+    fn default() -> Self {
+        Self { counts: Vec::new(), chunk_counts: HashMap::new(), _phantom: PhantomData }
+    }
+}
+
+/// Generates chunks on demand when a cell in the world pass reaches past the spawned chunks.
+#[derive(Resource)]
+pub struct ChunkSpawner<T: Cell, const N: i32> {
+    generator: Box<dyn Fn(IVec2) -> Chunk<T, N> + Send + Sync>,
+    trigger: Box<dyn Fn(&T) -> bool + Send + Sync>,
+    parent: Option<Entity>,
+}
+
+impl<T: Cell, const N: i32> ChunkSpawner<T, N> {
+    /// Only cells matching `trigger` spawn the chunks their range reaches into,
+    /// so that settled cells such as air at the world edge do not grow the world forever.
+    pub fn new(
+        generator: impl Fn(IVec2) -> Chunk<T, N> + Send + Sync + 'static,
+        trigger: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self { generator: Box::new(generator), trigger: Box::new(trigger), parent: None }
+    }
+
+    /// Spawns new chunks as children of `parent`.
+    pub fn with_parent(mut self, parent: Entity) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+}
+
+enum ChunkSlot<'c, T: Cell, const N: i32> {
+    Borrowed(&'c mut Chunk<T, N>),
+    Spawned(Box<Chunk<T, N>>),
+}
+
+impl<'c, T: Cell, const N: i32> Deref for ChunkSlot<'c, T, N> {
+    type Target = Chunk<T, N>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ChunkSlot::Borrowed(chunk) => chunk,
+            ChunkSlot::Spawned(chunk) => chunk,
+        }
+    }
+}
+
+impl<'c, T: Cell, const N: i32> DerefMut for ChunkSlot<'c, T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            ChunkSlot::Borrowed(chunk) => chunk,
+            ChunkSlot::Spawned(chunk) => chunk,
+        }
+    }
+}
+
+struct WorldGrid<'c, T, const N: i32>
+where
+    T: Renderable,
+{
+    chunks: HashMap<IVec2, ChunkSlot<'c, T, N>>,
+    spawner: Option<&'c ChunkSpawner<T, N>>,
+    /// Points swapped or replaced during the world pass, their cells have already moved, been displaced or been
+    /// overwritten.
+    moved: HashSet<IVec2>,
+    tick: u64,
+    out_of_world: OutOfWorldPolicy,
+    border: Option<&'c BorderBehavior<T>>,
+    /// World points deferred to the serial pass, `None` in the serial pass itself.
+    deferred: Option<Vec<IVec2>>,
+    topology: WorldTopology,
+}
+
+impl<'c, T, const N: i32> WorldGrid<'c, T, N>
+where
+    T: Renderable,
+{
+    /// Passes the part of the chunk at `source`'s local `stain` past its edges on to each neighbor through
+    /// [`Chunk::stain_from_neighbor`], leaving out the parts over nothing but inert cells so stains do not wake chunks
+    /// that have nothing to tick. Stored stains reach a chunk's width past it at most, so only the eight neighbors can
+    /// take any of it.
+    fn stain_from_neighbor_unless_inert(&mut self, source: IVec2, stain: &Area) {
+        for offset in (-1..=1).flat_map(|y| (-1..=1).map(move |x| IVec2::new(x, y))).filter(|offset| *offset != IVec2::ZERO) {
+            let Some(chunk) = self.chunks.get_mut(&self.topology.wrap_chunk(source + offset)) else {
+                continue;
+            };
+
+            let live = Area::from_areas(
+                stain
+                    .rects()
+                    .iter()
+                    .filter(|rect| !chunk.is_inert_within(translate_rect(**rect, -N * offset)))
+                    .map(|rect| Area::from(*rect)),
+            );
+
+            chunk.stain_from_neighbor(-offset, &live);
+        }
+    }
+
+    /// The chunk and local coordinates of `point` once wrapped by the [`WorldTopology`].
+    fn locate(&self, point: IVec2) -> (IVec2, IVec2) {
+        ChunkCoords::<N>::world_to_chunk_and_local(self.topology.wrap::<N>(point))
+    }
+
+    /// Whether every point of `rect` falls within `covers` once wrapped by the [`WorldTopology`].
+    fn covers_rect(&self, rect: IRect, covers: &Area) -> bool {
+        match self.topology {
+            WorldTopology::Open => covers.contains_rect(rect),
+            // The world wraps by whole chunks, so each chunk's part of the rect wraps as one.
+            WorldTopology::Toroidal { .. } => decompose_region::<N>(rect)
+                .all(|(chunk, local)| covers.contains_rect(translate_rect(local, N * self.topology.wrap_chunk(chunk)))),
+        }
+    }
+
+    /// Spawns any missing chunks overlapping `rect` if the cell at `point` triggers the spawner.
+    fn spawn_missing(&mut self, point: IVec2, rect: IRect) {
+        let Some(spawner) = self.spawner else {
+            return;
+        };
+
+        if !self.get(point).is_ok_and(|cell| (spawner.trigger)(cell)) {
+            return;
+        }
+
+        for (chunk_coords, _) in decompose_region::<N>(rect) {
+            self.spawn_chunk(self.topology.wrap_chunk(chunk_coords));
+        }
+    }
+
+    /// Spawns the chunk at `chunk_coords` if it is missing, returning whether it exists afterwards.
+    fn spawn_chunk(&mut self, chunk_coords: IVec2) -> bool {
+        if self.chunks.contains_key(&chunk_coords) {
+            return true;
+        }
+
+        let Some(spawner) = self.spawner else {
+            return false;
+        };
+
+        let mut chunk = (spawner.generator)(chunk_coords);
+
+        chunk.set_tick(self.tick);
+
+        self.chunks.insert(chunk_coords, ChunkSlot::Spawned(Box::new(chunk)));
+
+        true
+    }
+
+    /// Applies the [`OutOfWorldPolicy`] to a write at `point`, returning whether the write should go ahead.
+    fn writable(&mut self, point: IVec2) -> Result<bool, PowderkegError<T>> {
+        let (chunk_coords, _) = self.locate(point);
+
+        if self.chunks.contains_key(&chunk_coords) {
+            return Ok(true);
+        }
+
+        let policy = self.out_of_world;
+
+        match policy {
+            OutOfWorldPolicy::Skip => Ok(false),
+            OutOfWorldPolicy::Spawn if self.spawn_chunk(chunk_coords) => Ok(true),
+            _ if self.border_cell().is_some() => Ok(false),
+            _ => Err(PowderkegError::ChunkOutOfBounds(chunk_coords)),
+        }
+    }
+
+    fn border_cell(&self) -> Option<&T> {
+        self.border.and_then(BorderBehavior::cell)
+    }
+}
+
+impl<'c, T, const N: i32> Grid for WorldGrid<'c, T, N>
+where
+    T: Renderable,
+{
+    type Cell = T;
+
+    fn get(&self, point: IVec2) -> Result<&Self::Cell, PowderkegError<T>> {
+        let (chunk, local) = self.locate(point);
+
+        // Only points outside every chunk read the border, absent cells of a chunk are still errors.
+        match self.chunks.get(&chunk) {
+            Some(chunk) => chunk.get(local),
+            None => self.border_cell().ok_or(PowderkegError::ChunkOutOfBounds(chunk)),
+        }
+    }
+
+    fn get_mut(&mut self, point: IVec2) -> Result<&mut Self::Cell, PowderkegError<T>> {
+        // Spawns the chunk under `OutOfWorldPolicy::Spawn`, any other write outside the world fails.
+        self.writable(point)?;
+
+        let (chunk, local) = self.locate(point);
+
+        self.chunks.get_mut(&chunk).ok_or(PowderkegError::ChunkOutOfBounds(chunk))?.get_mut(local)
+    }
+
+    fn count_matching(&self, region: IRect, pred: impl Fn(&T) -> bool) -> usize {
+        decompose_region::<N>(region)
+            .map(|(chunk_coords, local)| match self.chunks.get(&self.topology.wrap_chunk(chunk_coords)) {
+                Some(chunk) => chunk.count_matching(local, &pred),
+                None if self.border_cell().is_some_and(&pred) => ((local.width() + 1) * (local.height() + 1)) as usize,
+                None => 0,
+            })
+            .sum()
+    }
+
+    fn replace(&mut self, point: IVec2, cell: T) -> Result<T, PowderkegError<T>> {
+        if !self.writable(point)? {
+            return Ok(cell);
+        }
+
+        let (chunk, local) = self.locate(point);
+
+        let old = self.chunks.get_mut(&chunk).ok_or(PowderkegError::ChunkOutOfBounds(chunk))?.replace(local, cell)?;
+
+        self.moved.insert(self.topology.wrap::<N>(point));
+
+        Ok(old)
+    }
+
+    fn replace_if(&mut self, point: IVec2, cell: T, only_if: impl FnOnce(&T) -> bool) -> Result<Option<T>, PowderkegError<T>> {
+        if !self.writable(point)? {
+            return Ok(None);
+        }
+
+        let (chunk, local) = self.locate(point);
+
+        let old = self.chunks.get_mut(&chunk).ok_or(PowderkegError::ChunkOutOfBounds(chunk))?.replace_if(local, cell, only_if)?;
+
+        if old.is_some() {
+            self.moved.insert(self.topology.wrap::<N>(point));
+        }
+
+        Ok(old)
+    }
+
+    fn swap(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<T>> {
+        let first = self.topology.wrap::<N>(first);
+        let second = self.topology.wrap::<N>(second);
+
+        let first_writable = self.writable(first)?;
+        let second_writable = self.writable(second)?;
+
+        if !(first_writable && second_writable) {
+            // Swapping with an empty border pulls the cell out of the world, the border cell taking its place.
+            if let Some(BorderBehavior::Empty(border, leave)) = self.border.filter(|_| first_writable != second_writable) {
+                let inside = if first_writable { first } else { second };
+
+                self.replace(inside, leave(border))?;
+            }
+
+            return Ok(());
+        }
+
+        let (first_chunk, first_local) = self.locate(first);
+        let (second_chunk, second_local) = self.locate(second);
+
+        if first_chunk == second_chunk {
+            self.chunks.get_mut(&first_chunk).ok_or(PowderkegError::ChunkOutOfBounds(first_chunk))?.swap(first_local, second_local)?;
+
+            self.moved.insert(first);
+            self.moved.insert(second);
+
+            Ok(())
+        } else {
+            let [first_chunk, second_chunk] = self.chunks
+                .get_many_mut([&first_chunk, &second_chunk])
+                .ok_or_else(|| PowderkegError::SwapOutOfBounds { first: first_chunk, second: second_chunk })?;
+
+            let first_cell = first_chunk.write(first_local)?;
+            let second_cell = second_chunk.write(second_local)?;
+
+            swap(first_cell, second_cell);
+
+            first_chunk.record_change(first_local, ChangeKind::Moved);
+            second_chunk.record_change(second_local, ChangeKind::Moved);
+
+            if let (Some(first_cell), Some(second_cell)) = (first_chunk.written(first_local), second_chunk.written(second_local)) {
+                first_chunk.observe(first_local, second_cell, first_cell);
+                second_chunk.observe(second_local, first_cell, second_cell);
+            }
+
+            self.moved.insert(first);
+            self.moved.insert(second);
+
+            Ok(())
+        }
+    }
+
+    fn swap_with_state(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<T>> {
+        let first_writable = self.writable(first)?;
+        let second_writable = self.writable(second)?;
+
+        if !(first_writable && second_writable) {
+            return Ok(());
+        }
+
+        self.swap(first, second)?;
+
+        let (_, first_local) = self.locate(first);
+        let (_, second_local) = self.locate(second);
+
+        swap_cell_state::<T>(&self.get_state(first)?, first_local, &self.get_state(second)?, second_local);
+
+        Ok(())
+    }
+
+    fn get_state(&self, point: IVec2) -> Result<Arc<RwLock<<T as Cell>::State>>, PowderkegError<T>> {
+        let (chunk, local) = self.locate(point);
+
+        self.chunks
+            .get(&chunk)
+            .ok_or(PowderkegError::ChunkOutOfBounds(chunk))?
+            .get_state(local)
+    }
+    
+    fn covers(&self) -> Area {
+        Area::from_areas(
+            self.chunks
+                .iter()
+                .map(|(coords, chunk)| {
+                    let mut area = chunk.covers();
+
+                    area.translate(*coords * N);
+
+                    area
+                })
+        )
+    }
+}
+
+impl<'c, T, const N: i32> DisjointGrid for WorldGrid<'c, T, N>
+where
+    T: Renderable,
+{
+    fn get_many_mut<const K: usize>(&mut self, points: [IVec2; K]) -> Result<[&mut T; K], PowderkegError<T>> {
+        let points = points.map(|point| self.topology.wrap::<N>(point));
+
+        check_disjoint(&points)?;
+
+        // Like `get_mut`, spawns missing chunks under `OutOfWorldPolicy::Spawn` and fails on any point that cannot be
+        // written, such as one skipped under `OutOfWorldPolicy::Skip`.
+        for point in points {
+            if !self.writable(point)? {
+                return Err(PowderkegError::ChunkOutOfBounds(self.locate(point).0));
+            }
+        }
+
+        let located = points.map(|point| self.locate(point));
+        let coords = located.map(|(chunk, _)| chunk);
+
+        // Points in chunks of their own, as in `swap`.
+        if check_disjoint::<T>(&coords).is_ok() {
+            let chunks = self.chunks
+                .get_many_mut(coords.each_ref())
+                .ok_or(PowderkegError::ChunkOutOfBounds(coords[0]))?;
+
+            let mut cells: [Option<&mut T>; K] = std::array::from_fn(|_| None);
+
+            for ((cell, chunk), (_, local)) in cells.iter_mut().zip(chunks).zip(located) {
+                *cell = chunk.get_disjoint_mut(&[local])?.pop();
+            }
+
+            return Ok(cells.map(|cell| cell.expect("every cell was found")));
+        }
+
+        // Points sharing one chunk.
+        if coords.iter().all(|chunk| *chunk == coords[0]) {
+            let chunk = self.chunks.get_mut(&coords[0]).ok_or(PowderkegError::ChunkOutOfBounds(coords[0]))?;
+            let cells = chunk.get_disjoint_mut(&located.map(|(_, local)| local))?;
+
+            return Ok(cells.try_into().unwrap_or_else(|_| unreachable!("one cell for every point")));
+        }
+
+        // Some of several chunks hold more than one point, which `get_many_mut` cannot lend without a fixed number of
+        // distinct chunks, so the chunks are walked for theirs.
+        let mut cells: [Option<&mut T>; K] = std::array::from_fn(|_| None);
+
+        for (chunk_coords, chunk) in self.chunks.iter_mut().filter(|(chunk_coords, _)| coords.contains(chunk_coords)) {
+            let found = (0..K).filter(|i| coords[*i] == *chunk_coords);
+            let locals: Vec<_> = found.clone().map(|i| located[i].1).collect();
+
+            for (i, cell) in found.zip(chunk.get_disjoint_mut(&locals)?) {
+                cells[i] = Some(cell);
+            }
+        }
+
+        Ok(cells.map(|cell| cell.expect("every chunk was written to")))
+    }
+}
+
+
+// TODO: Fix this mess of an implementation
+impl<'c, T, const N: i32> Stainable for WorldGrid<'c, T, N>
+where
+    T: Renderable,
+{
+    fn stained(&self) -> Area {
+        Area::from_areas(self.chunks.values().map(|chunk| chunk.stained()))
+    }
+
+    fn stain(&mut self, area: IRect) {
+        for (chunk_coords, local) in decompose_region::<N>(area) {
+            if let Some(chunk) = self.chunks.get_mut(&self.topology.wrap_chunk(chunk_coords)) {
+                chunk.stain(local);
+            }
+        }
+    }
+
+    fn stain_point(&mut self, point: IVec2) {
+        let (chunk, local) = self.locate(point);
+        
+        if let Some(chunk) = self.chunks.get_mut(&chunk) {
+            chunk.stain_point(local);
+        }
+    }
+
+    fn clear_stain(&mut self) {
+        for chunk in self.chunks.values_mut() {
+            chunk.clear_stain();
+        }
+    }
+
+    fn defer(&mut self, point: IVec2) -> bool {
+        if self.get(point).is_err() {
+            return false;
+        }
+
+        match &mut self.deferred {
+            Some(deferred) => {
+                deferred.push(point);
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+pub struct SimulationError<T: Cell> {
+    pub point: IVec2,
+    pub error: PowderkegError<T>,
+}
+
+/// The time passed since the simulation last ran, from the [`PowderkegClock`] if there is one.
+#[derive(SystemParam)]
+struct SimulationClock<'w, 's> {
+    time: Res<'w, Time<Virtual>>,
+    clock: Option<Res<'w, PowderkegClock>>,
+    read: Local<'s, f64>,
+}
+
+impl SimulationClock<'_, '_> {
+    fn delta(&mut self) -> f32 {
+        let Some(clock) = &self.clock else {
+            return self.time.delta_seconds();
+        };
+
+        // A clock inserted again starts over, rather than running nothing until it catches up with the last.
+        if clock.is_added() {
+            *self.read = 0.0;
+        }
+
+        let delta = (clock.elapsed() - *self.read) as f32;
+
+        *self.read = clock.elapsed();
+
+        delta
+    }
+}
+
+/// The resources configuring how the simulation ticks.
+#[derive(SystemParam)]
+struct SimulationConfig<'w, T: Cell> {
+    tick_rate: Res<'w, PowderkegTickRate>,
+    max_ticks: Res<'w, MaxTicksPerFrame>,
+    budget: Option<Res<'w, TickTimeBudget>>,
+    order: Res<'w, StainOrder>,
+    schedule: Res<'w, SimulationSchedule>,
+    paused: Res<'w, PowderkegPaused>,
+    out_of_world: Res<'w, OutOfWorldPolicy>,
+    rng: Res<'w, PowderkegRng>,
+    active: Option<Res<'w, ActiveRegion>>,
+    sleep: Res<'w, ChunkSleepThreshold>,
+    topology: Res<'w, WorldTopology>,
+    gravity: Res<'w, Gravity>,
+    border: Res<'w, BorderBehavior<T>>,
+    mode: Res<'w, SimulationMode<T>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn simulate_powderkeg<T, const N: i32>(
+    mut chunks: Query<(&ChunkCoords<N>, &mut Chunk<T, N>)>,
+    config: SimulationConfig<T>,
+    mut errors: ResMut<PowderkegErrors<T>>,
+    mut ticks: Local<f32>,
+    mut starved: Local<HashSet<IVec2>>,
+    spawner: Option<Res<ChunkSpawner<T, N>>>,
+    mut report: ResMut<TickReport<T>>,
+    mut totals: ResMut<TickTotals>,
+    mut conservation: Option<ResMut<ConservationCheck<T>>>,
+    mut tick: ResMut<PowderkegTick>,
+    mut clock: SimulationClock,
+    mut activity: ResMut<ChunkActivity>,
+    mut steps: EventReader<StepOnce>,
+    emit_changes: Option<Res<EmitCellChanged<T>>>,
+    mut cell_changed: EventWriter<CellChanged<T>>,
+    mut commands: Commands,
+) where
+    T: Renderable,
+{
+    let steps = steps.read().count();
+
+    if config.paused.0 {
+        if steps == 0 {
+            return;
+        }
+
+        // Stepping leaves the time advanced while paused for when the simulation resumes.
+        *ticks += steps as f32;
+    } else {
+        *ticks += config.tick_rate.0 * clock.delta();
+    }
+
+    let deadline = config.budget.as_deref().map(|budget| Instant::now() + budget.0);
+    let out_of_time = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+    // Chunks spawned by earlier ticks this frame stay in the world grid of later ones until they are committed.
+    let mut spawned = Vec::new();
+    let mut ticks_run = 0;
+    let mut last_activity = ChunkActivity::default();
+
+    if *ticks >= 1.0 {
+        errors.errors.clear();
+    }
+
+    // The first tick of a frame always runs, however small the budget.
+    while *ticks >= 1.0 && ticks_run < config.max_ticks.0 && (ticks_run == 0 || !out_of_time()) {
+        let start = Instant::now();
+        let errors_before = errors.errors.len();
+
+        tick.0 += 1;
+
+        let tick = tick.0;
+        let seed = config.rng.tick_seed(tick);
+        let order = *config.order;
+        let schedule = *config.schedule;
+        let active = config.active.as_deref().copied();
+        let sleep = config.sleep.0;
+        let gravity = config.gravity.0;
+        let observe = emit_changes.as_deref().map(|emit| &emit.observe);
+        let copy = match *config.mode {
+            SimulationMode::InPlace => None,
+            SimulationMode::DoubleBuffered(copy) => Some(copy),
+        };
+
+        let conserved_before = conservation
+            .as_deref()
+            .map(|check| chunks.iter().map(|(_, chunk)| check.count(chunk)).sum::<usize>());
+
+        let (send_to_tick, recieve_to_tick) = unbounded::<(u32, IVec2)>();
+        let (send_errors, recieve_errors) = unbounded::<SimulationError<T>>();
+        let (send_stains, recieve_stains) = unbounded::<(IVec2, Area)>();
+        let (send_counts, recieve_counts) = unbounded::<(usize, usize)>();
+        let (send_asleep, recieve_asleep) = unbounded::<bool>();
+        let (send_snapshots, recieve_snapshots) = unbounded::<(IVec2, Area)>();
+        let (send_starved, recieve_starved) = unbounded::<IVec2>();
+        let (send_changes, recieve_changes) = unbounded::<CellChanged<T>>();
+
+        let begin_tick = |coords: &ChunkCoords<N>, chunk: &mut Chunk<T, N>| {
+            chunk.set_tick(tick);
+            chunk.double_buffer(copy);
+
+            if let Some(observe) = observe {
+                chunk.observe_changes(coords, observe.clone(), send_changes.clone());
+            }
+        };
+
+        let tick_chunk = |(coords, mut chunk): (&ChunkCoords<N>, Mut<Chunk<T, N>>)| {
+            let area = Chunk::<T, N>::area();
+
+            // Chunks outside the active region are left untouched, they begin the tick below only to take the writes
+            // of their neighbors.
+            if active.is_some_and(|active| !active.overlaps_chunk(coords)) {
+                return;
+            }
+
+            // Only chunks the tick writes to are marked changed once it is over, see `mark_written_chunks`.
+            let chunk = chunk.bypass_change_detection();
+
+            begin_tick(coords, chunk);
+
+            let asleep = chunk.is_asleep(sleep);
+
+            send_asleep.send(asleep).expect("channel unexpectedly closed");
+
+            if asleep {
+                return;
+            }
+
+            // The checkerboard ticks the stain as it is now once the neighbors are at hand, anything stained in the
+            // meantime waits for the next tick.
+            if schedule == SimulationSchedule::Checkerboard {
+                let mut stain = chunk.stained();
+
+                stain.coalesce();
+                chunk.clear_stain();
+
+                if stain.is_empty() {
+                    chunk.record_idle_tick();
+                } else {
+                    send_snapshots.send((coords.0, stain)).expect("channel unexpectedly closed");
+                }
+
+                return;
+            }
+
+            let Some((ticked, unstable)) = tick_chunk_cells(
+                coords,
+                chunk,
+                order,
+                seed,
+                gravity,
+                out_of_time,
+                |error| send_errors.send(error).expect("channel unexpectedly closed"),
+                |phase, point| send_to_tick.send((phase, point)).expect("channel unexpectedly closed"),
+            ) else {
+                chunk.record_idle_tick();
+                return;
+            };
+
+            chunk.record_ticked(ticked);
+
+            // The budget ran out before the chunk was done, what it did not get to is stained again.
+            if out_of_time() {
+                send_starved.send(coords.0).expect("channel unexpectedly closed");
+            }
+
+            let outside = chunk.stain.subtract(&area.into());
+
+            if !outside.is_empty() {
+                send_stains.send((coords.0, outside)).expect("channel unexpectedly closed");
+            }
+
+            send_counts.send((ticked, unstable)).expect("channel unexpectedly closed");
+        };
+
+        // Chunks the budget cut short last time go first, so a tight budget does not starve the same chunks every
+        // frame while others are always done.
+        let starved_before = mem::take(&mut *starved);
+
+        if !starved_before.is_empty() {
+            chunks.par_iter_mut().for_each(|item| if starved_before.contains(&item.0.0) { tick_chunk(item) });
+        }
+
+        chunks.par_iter_mut().for_each(|item| if !starved_before.contains(&item.0.0) { tick_chunk(item) });
+
+        drop(send_to_tick);
+        drop(send_errors);
+        drop(send_stains);
+        drop(send_counts);
+        drop(send_asleep);
+        drop(send_snapshots);
+        drop(send_starved);
+
+        starved.extend(recieve_starved.iter());
+
+        last_activity = ChunkActivity::default();
+
+        for asleep in recieve_asleep.iter() {
+            if asleep {
+                last_activity.asleep += 1;
+            } else {
+                last_activity.awake += 1;
+            }
+        }
+
+        let mut next_report = TickReport::<T>::default();
+
+        for (ticked, unstable) in recieve_counts.iter() {
+            next_report.cells_ticked += ticked;
+            next_report.cells_unstable += unstable;
+            next_report.dirty_chunks += 1;
+        }
+
+        for error in recieve_errors.iter() {
+            error!("Error ticking {}: {}", error.point, error.error);
+            errors.errors.push(error);
+        }
+
+        let snapshots: Vec<_> = recieve_snapshots.iter().collect();
+        let mut to_tick: Vec<_> = recieve_to_tick.iter().collect();
+        let mut moved = HashSet::new();
+
+        let mut tracked: Vec<_> = chunks.iter_mut().collect();
+
+        // Writes reaching into chunks outside the active region are recorded against this tick like any other.
+        if let Some(active) = active {
+            for (coords, chunk) in tracked.iter_mut().filter(|(coords, _)| !active.overlaps_chunk(coords)) {
+                begin_tick(coords, chunk.bypass_change_detection());
+            }
+        }
+
+        if !snapshots.is_empty() {
+            let mut chunks = tracked
+                .iter_mut()
+                .map(|(ChunkCoords(coords), chunk)| (*coords, chunk.bypass_change_detection()))
+                .collect();
+
+            let first_error = errors.errors.len();
+
+            moved = tick_checkerboard(
+                &mut chunks,
+                snapshots,
+                *config.topology,
+                tick,
+                order,
+                seed,
+                gravity,
+                out_of_time,
+                &mut next_report,
+                &mut errors.errors,
+                &mut to_tick,
+            );
+
+            for error in errors.errors[first_error..].iter() {
+                error!("Error ticking {}: {}", error.point, error.error);
+            }
+        }
+
+        let chunks = tracked
+            .iter_mut()
+            .map(|(ChunkCoords(coords), chunk)| (*coords, ChunkSlot::Borrowed(chunk.bypass_change_detection())))
+            .chain(spawned.drain(..).map(|(coords, chunk)| (coords, ChunkSlot::Spawned(chunk))))
+            .collect();
+
+        let mut world_grid = WorldGrid {
+            chunks,
+            spawner: spawner.as_deref(),
+            moved,
+            tick,
+            out_of_world: *config.out_of_world,
+            border: Some(&config.border),
+            deferred: None,
+            topology: *config.topology,
+        };
+
+        let mut stains: Vec<_> = recieve_stains.iter().collect();
+
+        // Stains arrive in whatever order the chunks finished in, sorting them keeps the stain order reproducible.
+        stains.sort_unstable_by_key(|(source, _)| (source.y, source.x));
+
+        next_report.cross_chunk_stains = stains.iter().map(|(_, stain)| stain.rects().len()).sum();
+
+        for (source, stain) in stains {
+            world_grid.stain_from_neighbor_unless_inert(source, &stain);
+        }
+
+        // Deferred cells tick in a fixed order whatever the `StainOrder`, so that when cells on either side of a seam
+        // contend for each other's place the lowest, then leftmost, always wins.
+        to_tick.sort_unstable_by_key(|(phase, point)| (*phase, point.y, point.x));
+
+        next_report.deferred = to_tick.len();
+
+        let mut deadline = DeadlineCheck::new(out_of_time);
+
+        for (_, point) in to_tick {
+            // The cell queued here has already moved, been displaced or been overwritten by an earlier deferred cell,
+            // ticking whatever is here now could tick a cell twice so it waits for the next tick.
+            if deadline.passed() || world_grid.moved.contains(&point) {
+                world_grid.stain_point(point);
+                continue;
+            }
+
+            // Deferred points come from stains, a bad one is reported like any other tick error rather than panicking.
+            let range = match world_grid.get(point).and_then(|cell| Ok((cell, world_grid.get_state(point)?))) {
+                Ok((cell, _)) if cell.is_inert() => continue,
+                Ok((cell, state)) => translate_rect(cell.range_with_state(&state.read()), point),
+                Err(error) => {
+                    error!("Error ticking {point}: {error}");
+                    errors.errors.push(SimulationError { point, error });
+                    continue;
+                },
+            };
+
+            world_grid.spawn_missing(point, range);
+
+            // Cells reaching past the world tick like any other, the `OutOfWorldPolicy` decides what happens to their
+            // writes there and reads there see the border cell or fail.
+            let input = TickInput {
+                origin: point,
+                grid: &mut world_grid,
+                seed: point_seed(seed, point),
+                gravity,
+            };
+
+            next_report.cells_ticked += 1;
+
+            match T::tick(input) {
+                Ok(TickSuccess::Unstable) => {
+                    next_report.cells_unstable += 1;
+                    world_grid.stain_point(point);
+                },
+                Err(error) => {
+                    error!("Error ticking {point}: {error}");
+                    errors.errors.push(SimulationError { point, error });
+                },
+                _ => {},
+            }
+        }
+
+        if let (Some(check), Some(before)) = (conservation.as_deref_mut(), conserved_before) {
+            let after: usize = world_grid.chunks
+                .values()
+                .filter(|chunk| matches!(chunk, ChunkSlot::Borrowed(_)))
+                .map(|chunk| check.count(chunk))
+                .sum();
+
+            if after != before {
+                error!("Conserved cells changed from {before} to {after} during a tick");
+                check.violations += 1;
+            }
+        }
+
+        for chunk in world_grid.chunks.values_mut() {
+            chunk.swap_buffers();
+            chunk.stop_observing();
+        }
+
+        cell_changed.send_batch(recieve_changes.try_iter());
+
+        for (coords, chunk) in world_grid.chunks.drain() {
+            if let ChunkSlot::Spawned(chunk) = chunk {
+                spawned.push((coords, chunk));
+            }
+        }
+
+        drop(world_grid);
+
+        mark_written_chunks(&mut tracked, tick);
+
+        next_report.errors = errors.errors.len() - errors_before;
+        next_report.duration = start.elapsed();
+
+        *report = next_report;
+
+        *ticks -= 1.0;
+        ticks_run += 1;
     }
-}
 
-#[derive(Resource)]
-pub struct PowderkegTickRate(pub f32);
+    if *ticks >= 1.0 {
+        *ticks = ticks.fract();
+    }
 
-impl Default for PowderkegTickRate {
-    fn default() -> Self {
-        Self(16.0)
+    activity.awake += last_activity.awake;
+    activity.asleep += last_activity.asleep;
+
+    if ticks_run > 0 {
+        totals.add(&report);
+    }
+
+    let parent = spawner.and_then(|spawner| spawner.parent);
+
+    for (coords, chunk) in spawned {
+        let mut entity = commands.spawn(ChunkBundle::new(*chunk, ChunkCoords::<N>(coords)));
+
+        if let Some(parent) = parent {
+            entity.set_parent(parent);
+        }
     }
 }
 
-struct WorldGrid<'c, T, const N: i32>
+/// Marks the chunks written during `tick` as changed. Ticking goes through every chunk every tick, so it bypasses
+/// change detection and leaves `Changed<Chunk>` to the chunks whose cells it actually wrote.
+fn mark_written_chunks<T, const N: i32>(chunks: &mut [(&ChunkCoords<N>, Mut<Chunk<T, N>>)], tick: u64)
 where
-    T: Renderable,
+    T: Cell,
 {
-    chunks: HashMap<IVec2, &'c mut Chunk<T, N>>,
+    for (_, chunk) in chunks.iter_mut() {
+        if chunk.last_modified() == tick {
+            chunk.set_changed();
+        }
+    }
 }
 
-impl<'c, T, const N: i32> Grid for WorldGrid<'c, T, N>
+fn clear_chunk_activity(mut activity: ResMut<ChunkActivity>) {
+    *activity = ChunkActivity::default();
+}
+
+fn measure_chunk_activity(activity: Res<ChunkActivity>, mut diagnostics: Diagnostics) {
+    if activity.awake + activity.asleep == 0 {
+        return;
+    }
+
+    diagnostics.add_measurement(&AWAKE_CHUNKS, || activity.awake as f64);
+    diagnostics.add_measurement(&ASLEEP_CHUNKS, || activity.asleep as f64);
+}
+
+fn clear_tick_totals(mut totals: ResMut<TickTotals>) {
+    *totals = TickTotals::default();
+}
+
+fn measure_tick_totals(totals: Res<TickTotals>, mut diagnostics: Diagnostics) {
+    if !totals.ran {
+        return;
+    }
+
+    diagnostics.add_measurement(&CELLS_TICKED, || totals.cells_ticked as f64);
+    diagnostics.add_measurement(&DIRTY_CHUNKS, || totals.dirty_chunks as f64);
+    diagnostics.add_measurement(&DEFERRED_CELLS, || totals.deferred as f64);
+    diagnostics.add_measurement(&CROSS_CHUNK_STAINS, || totals.cross_chunk_stains as f64);
+}
+
+/// The outcome of ticking a single chunk with [`tick_chunk`].
+pub struct ChunkTickOutcome<T: Cell> {
+    pub ticked: usize,
+    pub unstable: usize,
+    pub errors: Vec<SimulationError<T>>,
+    /// World points of the cells whose range crossed the chunk's edge, these are not ticked.
+    pub deferred: Vec<IVec2>,
+}
+
+/// Runs the in-chunk pass of a tick on one chunk, independent of the schedule and tick rate.
+///
+/// Cells whose range crosses the chunk's edge need their neighbors so are not ticked, when `restain_deferred` they are
+/// stained again to be ticked by the next full tick, otherwise they are dropped. Neighboring chunks are never touched,
+/// including by stains reaching past this chunk.
+///
+/// `seed` seeds the tick as [`PowderkegRng::tick_seed`] does, and `gravity` is what cells read as the [`Gravity`].
+pub fn tick_chunk<T, const N: i32>(coords: &ChunkCoords<N>, chunk: &mut Chunk<T, N>, order: StainOrder, seed: u64, gravity: IVec2, restain_deferred: bool) -> ChunkTickOutcome<T>
 where
-    T: Renderable,
+    T: Cell,
 {
-    type Cell = T;
+    let mut errors = Vec::new();
+    let mut deferred = Vec::new();
 
-    fn get(&self, point: IVec2) -> Result<&Self::Cell, PowderkegError<T>> {
-        let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
+    let (ticked, unstable) = tick_chunk_cells(
+        coords,
+        chunk,
+        order,
+        seed,
+        gravity,
+        || false,
+        |error| errors.push(error),
+        |_, point| deferred.push(point),
+    ).unwrap_or_default();
 
-        self.chunks.get(&chunk).ok_or(PowderkegError::ChunkOutOfBounds(chunk))?.get(local)
+    if restain_deferred {
+        for point in deferred.iter() {
+            chunk.stain_point(coords.world_to_local(*point));
+        }
     }
 
-    fn get_mut(&mut self, point: IVec2) -> Result<&mut Self::Cell, PowderkegError<T>> {
-        let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
+    ChunkTickOutcome { ticked, unstable, errors, deferred }
+}
 
-        self.chunks.get_mut(&chunk).ok_or(PowderkegError::ChunkOutOfBounds(chunk))?.get_mut(local)
+/// How many cells tick between reads of the clock while a [`TickTimeBudget`] is set.
+const DEADLINE_CHECK_INTERVAL: u32 = 32;
+
+/// Reads the clock after every [`DEADLINE_CHECK_INTERVAL`] cells rather than every cell, once the deadline has
+/// passed it stays passed. The first cells of each pass always tick, so a budget too small for any work still makes
+/// progress.
+struct DeadlineCheck<F> {
+    out_of_time: F,
+    cells: u32,
+    passed: bool,
+}
+
+impl<F: Fn() -> bool> DeadlineCheck<F> {
+    fn new(out_of_time: F) -> Self {
+        Self { out_of_time, cells: 0, passed: false }
     }
 
-    fn swap(&mut self, first: IVec2, second: IVec2) -> Result<(), PowderkegError<T>> {
-        let (first_chunk, first_local) = ChunkCoords::<N>::world_to_chunk_and_local(first);
-        let (second_chunk, second_local) = ChunkCoords::<N>::world_to_chunk_and_local(second);
+    fn passed(&mut self) -> bool {
+        if !self.passed {
+            self.cells += 1;
+            self.passed = self.cells.is_multiple_of(DEADLINE_CHECK_INTERVAL) && (self.out_of_time)();
+        }
 
-        if first_chunk == second_chunk {
-            self.chunks.get_mut(&first_chunk).ok_or(PowderkegError::ChunkOutOfBounds(first_chunk))?.swap(first_local, second_local)
-        } else {
-            let [first_chunk, second_chunk] = self.chunks
-                .get_many_mut([&first_chunk, &second_chunk])
-                .ok_or_else(|| PowderkegError::SwapOutOfBounds { first: first_chunk, second: second_chunk })?;
+        self.passed
+    }
+}
 
-            first_chunk.stain_point(first_local);
-            second_chunk.stain_point(second_local);
+thread_local! {
+    /// The points of the stain being ticked, kept per thread so chunks ticked in parallel each reuse a buffer rather
+    /// than allocating one every tick.
+    static STAIN_POINTS: std::cell::Cell<Vec<IVec2>> = const { std::cell::Cell::new(Vec::new()) };
+    /// The phase of each cell of the chunk being ticked when its tick began, reused like `STAIN_POINTS`.
+    static START_PHASES: std::cell::Cell<Vec<u32>> = const { std::cell::Cell::new(Vec::new()) };
+}
 
-            let first_cell = first_chunk.get_mut(first_local)?;
-            let second_cell = second_chunk.get_mut(second_local)?;
+/// The phase `cell` ticks in, the last one if it claims a phase past [`Cell::PHASES`].
+fn cell_phase<T: Cell>(cell: &T) -> u32 {
+    let phase = cell.phase();
 
-            swap(first_cell, second_cell);
+    debug_assert!(phase < T::PHASES, "phase {phase} is not below Cell::PHASES ({})", T::PHASES);
 
-            Ok(())
-        }
-    }
+    phase.min(T::PHASES - 1)
+}
 
-    fn get_state(&self, point: IVec2) -> Result<Arc<RwLock<<T as Cell>::State>>, PowderkegError<T>> {
-        let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
+/// Records the phase of every stained cell of a chunk `N` wide by its local index, as [`Cell::phase`] is decided when
+/// the tick begins. Nothing is recorded for cells with a single phase.
+fn record_start_phases<T: Cell, const N: i32>(stain: &Area, phases: &mut Vec<u32>, cell_at: impl Fn(IVec2) -> Option<u32>) {
+    phases.clear();
 
-        self.chunks
-            .get(&chunk)
-            .ok_or(PowderkegError::ChunkOutOfBounds(chunk))?
-            .get_state(local)
+    if T::PHASES <= 1 {
+        return;
     }
-    
-    fn covers(&self) -> Area {
-        Area::from_areas(
-            self.chunks
-                .iter()
-                .map(|(coords, chunk)| {
-                    let mut area = chunk.covers();
 
-                    area.translate(*coords * N);
+    phases.resize((N * N) as usize, 0);
 
-                    area
-                })
-        )
+    for point in stain.points() {
+        if let Some(phase) = cell_at(point) {
+            phases[(N * point.y + point.x) as usize] = phase;
+        }
     }
 }
 
+/// Whether the cell at the local `point`, now in phase `current`, ticks in `phase`.
+fn ticks_in_phase<const N: i32>(phases: &[u32], point: IVec2, current: u32, phase: u32) -> bool {
+    current == phase && phases.get((N * point.y + point.x) as usize).is_none_or(|start| *start == phase)
+}
 
-// TODO: Fix this mess of an implementation
-impl<'c, T, const N: i32> Stainable for WorldGrid<'c, T, N>
+/// Ticks every stained cell whose range is within the chunk, returning how many were ticked and how many of those
+/// were unstable, or `None` if nothing but inert cells was stained.
+#[allow(clippy::too_many_arguments)]
+fn tick_chunk_cells<T, const N: i32>(
+    coords: &ChunkCoords<N>,
+    chunk: &mut Chunk<T, N>,
+    order: StainOrder,
+    seed: u64,
+    gravity: IVec2,
+    out_of_time: impl Fn() -> bool,
+    mut on_error: impl FnMut(SimulationError<T>),
+    mut on_deferred: impl FnMut(u32, IVec2),
+) -> Option<(usize, usize)>
 where
-    T: Renderable,
+    T: Cell,
 {
-    fn stained(&self) -> Area {
-        Area::from_areas(self.chunks.values().map(|chunk| chunk.stained()))
+    let mut stain = chunk.stained();
+
+    stain.coalesce();
+    chunk.clear_stain();
+
+    if stain.is_empty() {
+        return None;
     }
 
-    fn stain(&mut self, area: IRect) {
-        let (min_chunk, _) = ChunkCoords::<N>::world_to_chunk_and_local(area.min);
-        let (max_chunk, _) = ChunkCoords::<N>::world_to_chunk_and_local(area.max);
-        
-        for cx in min_chunk.x..=max_chunk.x {
-            for cy in min_chunk.y..=max_chunk.y {
-                let chunk_coords = IVec2::new(cx, cy);
+    let mut ticked = 0;
+    let mut unstable = 0;
+    let mut live = false;
 
-                if let Some(chunk) = self.chunks.get_mut(&chunk_coords) {
-                    let translated = translate_rect(area, -N * chunk_coords);
-                    chunk.stain(translated);
-                }
+    let mut rng = coords.rng(seed);
+    let mut points = STAIN_POINTS.take();
+    let mut phases = START_PHASES.take();
+    let mut deadline = DeadlineCheck::new(out_of_time);
+
+    record_start_phases::<T, N>(&stain, &mut phases, |point| chunk.get(point).ok().map(cell_phase));
+
+    for phase in 0..T::PHASES {
+        stain.apply_in_order_with(order, &mut rng, &mut points, |point| {
+            if deadline.passed() {
+                live = true;
+                chunk.stain_point(point);
+                return;
             }
-        }
-    }
 
-    fn stain_point(&mut self, point: IVec2) {
-        let (chunk, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
-        
-        if let Some(chunk) = self.chunks.get_mut(&chunk) {
-            chunk.stain_point(local);
-        }
-    }
+            let range = {
+                let Ok(cell) = chunk.get(point) else {
+                    return;
+                };
 
-    fn clear_stain(&mut self) {
-        for chunk in self.chunks.values_mut() {
-            chunk.clear_stain();
-        }
+                if cell.is_inert() {
+                    return;
+                }
+
+                live = true;
+
+                if !ticks_in_phase::<N>(&phases, point, cell_phase(cell), phase) {
+                    return;
+                }
+
+                translate_rect(cell.range_with_state(&chunk.state().read()), point)
+            };
+
+            if chunk.contains_rect(range) {
+                let input = TickInput {
+                    origin: point,
+                    grid: &mut *chunk,
+                    seed: point_seed(seed, coords.local_to_world(point)),
+                    gravity,
+                };
+
+                ticked += 1;
+
+                match T::tick(input) {
+                    Ok(TickSuccess::Unstable) => {
+                        unstable += 1;
+                        chunk.stain_point(point);
+                    },
+                    Err(error) => {
+                        on_error(SimulationError { point: coords.local_to_world(point), error });
+                    },
+                    _ => {},
+                }
+
+                for local in chunk.drain_deferred() {
+                    on_deferred(phase, coords.local_to_world(local));
+                }
+            } else {
+                on_deferred(phase, coords.local_to_world(point));
+            }
+        });
     }
-}
 
-struct SimulationError<T: Cell> {
-    pub point: IVec2,
-    pub error: PowderkegError<T>,
+    STAIN_POINTS.set(points);
+    START_PHASES.set(phases);
+
+    // A stain on nothing but inert cells leaves the chunk as idle as it was, so it still falls asleep.
+    live.then_some((ticked, unstable))
 }
 
-fn simulate_powderkeg<T, const N: i32>(
-    mut chunks: Query<(&ChunkCoords<N>, &mut Chunk<T, N>)>,
-    tick_rate: Res<PowderkegTickRate>,
-    mut ticks: Local<f32>,
-    time: Res<Time<Virtual>>,
-) where
+/// Ticks each chunk's stain snapshot in a world grid of it and its eight neighbors, as the sub-passes of
+/// [`SimulationSchedule::Checkerboard`]. Returns the world points swapped, which the world pass does not tick again.
+#[allow(clippy::too_many_arguments)]
+fn tick_checkerboard<T, const N: i32>(
+    chunks: &mut HashMap<IVec2, &mut Chunk<T, N>>,
+    mut snapshots: Vec<(IVec2, Area)>,
+    topology: WorldTopology,
+    tick: u64,
+    order: StainOrder,
+    seed: u64,
+    gravity: IVec2,
+    out_of_time: impl Fn() -> bool + Sync,
+    report: &mut TickReport<T>,
+    errors: &mut Vec<SimulationError<T>>,
+    deferred: &mut Vec<(u32, IVec2)>,
+) -> HashSet<IVec2>
+where
     T: Renderable,
 {
-    *ticks += tick_rate.0 * time.delta_seconds();
+    let mut moved = HashSet::new();
 
-    if *ticks >= 1.0 {
-        let (send_to_tick, recieve_to_tick) = unbounded::<IVec2>();
-        let (send_errors, recieve_errors) = unbounded::<SimulationError<T>>();
-        let (send_stains, recieve_stains) = unbounded::<IRect>();
+    report.dirty_chunks += snapshots.len();
 
-        chunks.par_iter_mut().for_each(|(coords, mut chunk)| {
-            let area = Chunk::<T, N>::area();
+    // Snapshots arrive in whatever order the chunks finished in, sorting them keeps which group claims a chunk shared
+    // across a small torus reproducible.
+    snapshots.sort_unstable_by_key(|(coords, _)| (coords.y, coords.x));
 
-            let stain = chunk.stained();
+    while !snapshots.is_empty() {
+        // A chunk already claimed as another's neighbor in its sub-pass, only possible on a torus under three chunks
+        // across, ticks once the sub-passes come round again.
+        let mut postponed = Vec::new();
 
-            chunk.clear_stain();
+        for color in 0..9 {
+            let (pass, rest): (Vec<_>, Vec<_>) = mem::take(&mut snapshots)
+                .into_iter()
+                .partition(|(coords, _)| coords.x.rem_euclid(3) + 3 * coords.y.rem_euclid(3) == color);
 
-            let mut rng = thread_rng();
+            snapshots = rest;
 
-            stain.apply_randomly(&mut rng, |point| {
-                let range = {
-                    let cell = chunk.at(point);
+            let mut groups = Vec::new();
 
-                    translate_rect(cell.range(), point)
+            for (coords, stain) in pass {
+                let Some(center) = chunks.remove(&coords) else {
+                    postponed.push((coords, stain));
+                    continue;
                 };
 
-                if area.contains(range.min) && area.contains(range.max) {
-                    let input = TickInput {
-                        origin: point,
-                        grid: chunk.as_mut(),
-                    };
-
-                    match T::tick(input) {
-                        Ok(TickSuccess::Unstable) => {
-                            chunk.stain_point(point);
-                        },
-                        Err(error) => {
-                            let error = SimulationError { point: coords.local_to_world(point), error };
-                            send_errors.send(error).expect("channel unexpectedly closed");
-                        },
-                        _ => {},
+                let mut group = HashMap::new();
+
+                group.insert(coords, ChunkSlot::Borrowed(center));
+
+                for y in -1..=1 {
+                    for x in -1..=1 {
+                        let neighbor = topology.wrap_chunk(coords + IVec2::new(x, y));
+
+                        if let Some(chunk) = chunks.remove(&neighbor) {
+                            group.insert(neighbor, ChunkSlot::Borrowed(chunk));
+                        }
                     }
-                } else {
-                    send_to_tick.send(coords.local_to_world(point)).expect("channel unexpectedly closed");
+                }
+
+                let grid = WorldGrid {
+                    chunks: group,
+                    spawner: None,
+                    moved: HashSet::new(),
+                    tick,
+                    out_of_world: OutOfWorldPolicy::Error,
+                    border: None,
+                    deferred: Some(Vec::new()),
+                    topology,
+                };
+
+                groups.push((ChunkCoords::<N>(coords), stain, grid));
+            }
+
+            let moved_before = &moved;
+            let out_of_time = &out_of_time;
+
+            let outcomes = ComputeTaskPool::get().scope(|scope| {
+                for (coords, stain, grid) in groups.iter_mut() {
+                    scope.spawn(async move {
+                        let mut errors = Vec::new();
+                        let mut deferred = Vec::new();
+
+                        let counts = tick_chunk_in_world(
+                            coords,
+                            grid,
+                            stain,
+                            order,
+                            seed,
+                            gravity,
+                            moved_before,
+                            out_of_time,
+                            |error| errors.push(error),
+                            |phase, point| deferred.push((phase, point)),
+                        );
+
+                        (counts, errors, deferred)
+                    });
                 }
             });
 
-            if let Some(stain) = chunk.stain.as_ref() {
-                let area = Chunk::<T, N>::area();
+            // Scoped tasks only return owned values, the chunks are taken back from the groups once they finish.
+            for (((ticked, unstable), group_errors, group_deferred), (coords, _, mut grid)) in outcomes.into_iter().zip(groups) {
+                if let Some(chunk) = grid.chunks.get_mut(&coords.0) {
+                    chunk.record_ticked(ticked);
+                }
+
+                report.cells_ticked += ticked;
+                report.cells_unstable += unstable;
+                errors.extend(group_errors);
+                deferred.extend(group_deferred);
+                moved.extend(grid.moved);
 
-                if !(rect_contains_inclusive(area, stain.min) && rect_contains_inclusive(area, stain.max)) {
-                    send_stains.send(translate_rect(*stain, N * coords.0)).expect("channel unexpectedly closed");
+                for (coords, chunk) in grid.chunks {
+                    if let ChunkSlot::Borrowed(chunk) = chunk {
+                        chunks.insert(coords, chunk);
+                    }
                 }
             }
-        });
+        }
 
-        drop(send_to_tick);
-        drop(send_errors);
-        drop(send_stains);
+        snapshots = postponed;
+    }
 
-        for SimulationError { point, error } in recieve_errors.iter() {
-            error!("Error ticking {point}: {error}");
-        }
-        
-        let chunks = chunks
-            .iter_mut()
-            .map(|(ChunkCoords(coords), chunk)| (*coords, chunk.into_inner()))
-            .collect();
+    moved
+}
 
-        let mut world_grid = WorldGrid {
-            chunks,
-        };
+/// Ticks the cells of `stain`, local to the chunk at `coords`, whose range is within `grid`, returning how many were
+/// ticked and how many of those were unstable. Cells swapped by an earlier sub-pass wait for the next tick.
+#[allow(clippy::too_many_arguments)]
+fn tick_chunk_in_world<T, const N: i32>(
+    coords: &ChunkCoords<N>,
+    grid: &mut WorldGrid<'_, T, N>,
+    stain: &Area,
+    order: StainOrder,
+    seed: u64,
+    gravity: IVec2,
+    moved_before: &HashSet<IVec2>,
+    out_of_time: impl Fn() -> bool,
+    mut on_error: impl FnMut(SimulationError<T>),
+    mut on_deferred: impl FnMut(u32, IVec2),
+) -> (usize, usize)
+where
+    T: Renderable,
+{
+    let covers = grid.covers();
 
-        for stain in recieve_stains.iter() {
-            world_grid.stain(stain);
-        }
+    let mut ticked = 0;
+    let mut unstable = 0;
 
-        let world_covers = world_grid.covers();
+    let mut rng = coords.rng(seed);
+    let mut points = STAIN_POINTS.take();
+    let mut phases = START_PHASES.take();
+    let mut deadline = DeadlineCheck::new(out_of_time);
 
-        for point in recieve_to_tick.iter() {
-            let range = {
-                let cell = world_grid.at(point);
+    record_start_phases::<T, N>(stain, &mut phases, |local| grid.get(coords.local_to_world(local)).ok().map(cell_phase));
 
-                translate_rect(cell.range(), point)
+    for phase in 0..T::PHASES {
+        stain.apply_in_order_with(order, &mut rng, &mut points, |local| {
+            let point = coords.local_to_world(local);
+
+            if deadline.passed() || moved_before.contains(&point) {
+                grid.stain_point(point);
+                return;
+            }
+
+            let range = match grid.get(point).and_then(|cell| Ok((cell, grid.get_state(point)?))) {
+                Ok((cell, _)) if cell.is_inert() || !ticks_in_phase::<N>(&phases, local, cell_phase(cell), phase) => return,
+                Ok((cell, state)) => translate_rect(cell.range_with_state(&state.read()), point),
+                Err(error) => {
+                    on_error(SimulationError { point, error });
+                    return;
+                },
             };
 
-            if area_contains(range, &world_covers) {
+            if grid.covers_rect(range, &covers) {
                 let input = TickInput {
                     origin: point,
-                    grid: &mut world_grid,
+                    grid: &mut *grid,
+                    seed: point_seed(seed, point),
+                    gravity,
                 };
-    
+
+                ticked += 1;
+
                 match T::tick(input) {
                     Ok(TickSuccess::Unstable) => {
-                        world_grid.stain_point(point);
-                    },
-                    Err(error) => {
-                        error!("Error ticking {point}: {error}");
+                        unstable += 1;
+                        grid.stain_point(point);
                     },
+                    Err(error) => on_error(SimulationError { point, error }),
                     _ => {},
                 }
+
+                for point in grid.deferred.iter_mut().flat_map(|deferred| deferred.drain(..)) {
+                    on_deferred(phase, point);
+                }
+            } else {
+                on_deferred(phase, point);
             }
-        }
+        });
+    }
 
-        *ticks = f32::clamp(*ticks - 1.0, 0.0, 1.0);
-    }   
-}
+    STAIN_POINTS.set(points);
+    START_PHASES.set(phases);
 
-fn translate_rect(rect: IRect, offset: IVec2) -> IRect {
-    IRect { min: rect.min + offset, max: rect.max + offset }
+    (ticked, unstable)
 }
 
-fn rect_contains_inclusive(rect: IRect, point: IVec2) -> bool {
-    rect.min.x <= point.x && point.x <= rect.max.x && rect.min.y <= point.y && point.y <= rect.max.y
-}
+fn count_cell_variants<T, const N: i32>(
+    chunks: Query<(Entity, &ChunkCoords<N>, Ref<Chunk<T, N>>)>,
+    histogram: Option<ResMut<CellHistogram<T>>>,
+) where
+    T: Renderable,
+{
+    let Some(mut histogram) = histogram else {
+        return;
+    };
+
+    let histogram = &mut *histogram;
+    let cached = &histogram.chunk_counts;
+
+    let (send_counts, recieve_counts) = unbounded::<(Entity, Vec<usize>)>();
+
+    chunks.par_iter().for_each(|(entity, _, chunk)| {
+        if !chunk.is_changed() && cached.contains_key(&entity) {
+            return;
+        }
 
-fn area_contains(rect: IRect, area: &Area) -> bool {
-    for x in rect.min.x..=rect.max.x {
-        for y in rect.min.y..=rect.max.y {
-            if !area.contains(IVec2::new(x, y)) {
-                return false;
+        let mut counts = Vec::new();
+
+        for variant in chunk.iter().filter_map(|(_, cell)| cell.variant()) {
+            if variant >= counts.len() {
+                counts.resize(variant + 1, 0);
             }
+
+            counts[variant] += 1;
+        }
+
+        send_counts.send((entity, counts)).expect("channel unexpectedly closed");
+    });
+
+    drop(send_counts);
+
+    histogram.chunk_counts.extend(recieve_counts.iter());
+    histogram.chunk_counts.retain(|entity, _| chunks.contains(*entity));
+
+    let mut ordered: Vec<_> = chunks
+        .iter()
+        .filter_map(|(entity, coords, _)| Some((coords.0, histogram.chunk_counts.get(&entity)?)))
+        .collect();
+
+    ordered.sort_unstable_by_key(|(coords, _)| (coords.y, coords.x));
+
+    let mut counts = Vec::new();
+
+    for (_, chunk_counts) in ordered {
+        if chunk_counts.len() > counts.len() {
+            counts.resize(chunk_counts.len(), 0);
+        }
+
+        for (total, count) in counts.iter_mut().zip(chunk_counts) {
+            *total += count;
         }
     }
 
-    true
+    histogram.counts = counts;
+}
+
+fn translate_rect(rect: IRect, offset: IVec2) -> IRect {
+    IRect { min: rect.min + offset, max: rect.max + offset }
 }
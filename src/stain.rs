@@ -1,13 +1,62 @@
 use bevy::math::{IRect, IVec2};
 
-use crate::{grid::Grid, area::Area};
+use crate::{cell::Cell, grid::{DisjointGrid, GridSnapshot}, area::Area, PowderkegError};
 
-pub trait Stainable: Grid {
+/// What kind of change stained a cell, for consumers such as lighting that only care about some changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Created,
+    Removed,
+    Moved,
+    /// Written in place through `get_mut`, which may have been any change.
+    Modified,
+}
+
+/// How a grid accumulates stains, trading stain precision against the cost of tracking it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StainPolicy {
+    /// Grows a single rect bounding everything stained.
+    #[default]
+    Bounding,
+    /// Keeps every stained rect, tighter for scattered changes but grows with every stain not already covered.
+    Precise,
+    /// Stains the whole grid whenever any of it is stained.
+    Whole,
+}
+
+impl StainPolicy {
+    /// Adds `rect` to `stain` for a grid covering `bounds`.
+    pub fn accumulate(&self, stain: &mut Area, rect: IRect, bounds: IRect) {
+        match self {
+            StainPolicy::Bounding => {
+                *stain = match stain.bounding_rect() {
+                    Some(stained) => stained.union(rect),
+                    None => rect,
+                }.into();
+            },
+            StainPolicy::Precise => if !stain.contains_rect(rect) {
+                stain.push(rect);
+            },
+            StainPolicy::Whole => {
+                let stained = stain.bounding_rect().unwrap_or(bounds);
+
+                *stain = stained.union(bounds).union(rect).into();
+            },
+        }
+    }
+}
+
+pub trait Stainable: DisjointGrid {
     fn stained(&self) -> Area;
     fn stain(&mut self, area: IRect);
     fn stain_point(&mut self, point: IVec2);
     fn clear_stain(&mut self);
 
+    fn stain_policy(&self) -> StainPolicy {
+        StainPolicy::Bounding
+    }
+
     fn stain_around(&mut self, point: IVec2, radius: i32) {
         self.stain(IRect::from_center_half_size(point, IVec2::splat(radius)))
     }
@@ -15,4 +64,64 @@ pub trait Stainable: Grid {
     fn is_stained(&self, point: IVec2) -> bool {
         self.stained().contains(point)
     }
+
+    /// Asks for the cell at `point` to tick again in the serial pass after every chunk has ticked, where its writes
+    /// reach into neighboring chunks, returning whether it will. Grids that already reach every chunk, or are not
+    /// being ticked by the simulation, return `false`.
+    fn defer(&mut self, _point: IVec2) -> bool {
+        false
+    }
+
+    /// Writes `cell` over every point of `rect` the grid covers, then stains what was written.
+    fn fill_rect(&mut self, rect: IRect, cell: Self::Cell) -> Result<(), PowderkegError<Self::Cell>>
+    where
+        Self::Cell: Clone,
+    {
+        self.fill_with(rect, |_| cell.clone())
+    }
+
+    /// Writes back the cells and chunk states of `snapshot` the grid covers, then stains its region.
+    fn restore(&mut self, snapshot: &GridSnapshot<Self::Cell>) -> Result<(), PowderkegError<Self::Cell>>
+    where
+        Self::Cell: Clone,
+        <Self::Cell as Cell>::State: Clone,
+    {
+        let covers = self.covers();
+
+        for (point, cell) in snapshot.iter().filter(|(point, _)| covers.contains(*point)) {
+            self.replace(point, cell.clone())?;
+        }
+
+        // After the cells, so the states are as they were whatever replacing the cells did to them.
+        for (point, state) in snapshot.states().filter(|(point, _)| covers.contains(*point)) {
+            *self.get_state(point)?.write() = state.clone();
+        }
+
+        for area in covers.intersect_rect(snapshot.region()).rects() {
+            self.stain(*area);
+        }
+
+        Ok(())
+    }
+
+    /// Like [`Stainable::fill_rect`] but writes whatever `f` returns for each point, for procedural fills.
+    fn fill_with(&mut self, rect: IRect, mut f: impl FnMut(IVec2) -> Self::Cell) -> Result<(), PowderkegError<Self::Cell>> {
+        let filled = self.covers().intersect_rect(rect);
+
+        for area in filled.rects() {
+            for y in area.min.y..=area.max.y {
+                for x in area.min.x..=area.max.x {
+                    let point = IVec2::new(x, y);
+
+                    self.replace(point, f(point))?;
+                }
+            }
+        }
+
+        for area in filled.rects() {
+            self.stain(*area);
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,159 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{cell::{Cell, Renderable}, chunk::{Chunk, ChunkBundle, ChunkCoords}};
+
+/// Spawns the chunks within `radius` chunks of the focus entity and despawns those beyond `despawn_radius`, for
+/// worlds too large to spawn up front.
+///
+/// Chunks are streamed in fully stained, waking them even when the generator or a stored copy says otherwise, so they
+/// render and tick straight away. Chunks outside the [`ActiveRegion`](crate::simulation::ActiveRegion) are still
+/// spawned, they just wait to be ticked.
+#[derive(Resource)]
+#[allow(clippy::type_complexity)]
+pub struct ChunkStreamer<T: Cell, const N: i32> {
+    pub focus: Entity,
+    pub radius: i32,
+    /// Kept above `radius` so chunks near the edge are not despawned and spawned again as the focus moves back and forth.
+    pub despawn_radius: i32,
+    generator: Box<dyn Fn(IVec2) -> Chunk<T, N> + Send + Sync>,
+    store: Option<Box<dyn Fn(IVec2, &Chunk<T, N>) + Send + Sync>>,
+    restore: Option<Box<dyn Fn(IVec2) -> Option<Chunk<T, N>> + Send + Sync>>,
+    parent: Option<Entity>,
+}
+
+impl<T: Cell, const N: i32> ChunkStreamer<T, N> {
+    pub fn new(focus: Entity, radius: i32, generator: impl Fn(IVec2) -> Chunk<T, N> + Send + Sync + 'static) -> Self {
+        Self {
+            focus,
+            radius,
+            despawn_radius: radius + 2,
+            generator: Box::new(generator),
+            store: None,
+            restore: None,
+            parent: None,
+        }
+    }
+
+    /// Spawns chunks as children of `parent`, whose transform also places the focus among them.
+    pub fn with_parent(mut self, parent: Entity) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Hands despawned chunks to `store` and streams chunks back in from `restore` when it has them, only generating
+    /// those it does not.
+    pub fn with_storage(
+        mut self,
+        store: impl Fn(IVec2, &Chunk<T, N>) + Send + Sync + 'static,
+        restore: impl Fn(IVec2) -> Option<Chunk<T, N>> + Send + Sync + 'static,
+    ) -> Self {
+        self.store = Some(Box::new(store));
+        self.restore = Some(Box::new(restore));
+        self
+    }
+
+    /// Stores despawned chunks as one file each in `directory`, in the [`SaveFormat`](crate::save::SaveFormat) given.
+    /// Chunks that fail to save are lost and those that fail to load are generated again, either is logged.
+    #[cfg(feature = "serde")]
+    pub fn with_directory(self, directory: impl Into<std::path::PathBuf>, format: crate::save::SaveFormat) -> Self
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+        T::State: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let directory = directory.into();
+        let restore_directory = directory.clone();
+
+        self.with_storage(
+            move |coords, chunk| {
+                let stored = std::fs::create_dir_all(&directory)
+                    .map_err(Into::into)
+                    .and_then(|_| format.write(&chunk_path(&directory, coords), chunk));
+
+                if let Err(error) = stored {
+                    error!("Failed to store chunk {coords}: {error}");
+                }
+            },
+            move |coords| {
+                let path = chunk_path(&restore_directory, coords);
+
+                if !path.exists() {
+                    return None;
+                }
+
+                format
+                    .read(&path)
+                    .inspect_err(|error| error!("Failed to restore chunk {coords}: {error}"))
+                    .ok()
+            },
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+fn chunk_path(directory: &std::path::Path, coords: IVec2) -> std::path::PathBuf {
+    directory.join(format!("chunk_{}_{}", coords.x, coords.y))
+}
+
+pub(crate) fn stream_chunks<T, const N: i32>(
+    streamer: Option<Res<ChunkStreamer<T, N>>>,
+    transforms: Query<&GlobalTransform>,
+    chunks: Query<(Entity, &ChunkCoords<N>, &Chunk<T, N>)>,
+    mut commands: Commands,
+) where
+    T: Renderable,
+{
+    let Some(streamer) = streamer else {
+        return;
+    };
+
+    let Ok(focus) = transforms.get(streamer.focus) else {
+        return;
+    };
+
+    let mut position = focus.translation();
+
+    if let Some(parent) = streamer.parent.and_then(|parent| transforms.get(parent).ok()) {
+        position = parent.affine().inverse().transform_point3(position);
+    }
+
+    // Chunks are centered on their coordinates times `N`.
+    let center = ((position.truncate() + Vec2::splat(N as f32 / 2.0)) / N as f32).floor().as_ivec2();
+
+    let mut spawned = HashSet::new();
+
+    for (entity, coords, chunk) in chunks.iter() {
+        if (coords.0 - center).length_squared() > streamer.despawn_radius * streamer.despawn_radius {
+            if let Some(store) = &streamer.store {
+                store(coords.0, chunk);
+            }
+
+            commands.entity(entity).despawn_recursive();
+        } else {
+            spawned.insert(coords.0);
+        }
+    }
+
+    for y in -streamer.radius..=streamer.radius {
+        for x in -streamer.radius..=streamer.radius {
+            let offset = IVec2::new(x, y);
+            let coords = center + offset;
+
+            if offset.length_squared() > streamer.radius * streamer.radius || spawned.contains(&coords) {
+                continue;
+            }
+
+            let mut chunk = streamer.restore
+                .as_ref()
+                .and_then(|restore| restore(coords))
+                .unwrap_or_else(|| (streamer.generator)(coords));
+
+            chunk.mark_dirty();
+
+            let mut entity = commands.spawn(ChunkBundle::new(chunk, ChunkCoords::<N>(coords)));
+
+            if let Some(parent) = streamer.parent {
+                entity.set_parent(parent);
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::marker::PhantomData;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    cell::Renderable,
+    chunk::{decompose_region, Chunk, ChunkBundle, ChunkCoords},
+    grid::Grid,
+    simulation::{PowderkegErrors, PowderkegPaused, PowderkegRng, PowderkegSimulationPlugin, PowderkegTick, SimulationError, StepOnce},
+    stain::Stainable,
+    PowderkegError,
+};
+
+/// Ticks hand-built chunks of `T` one tick at a time without a window, rendering or time passing, for testing cell
+/// rules. The simulation runs paused in an `App` of its own with a fixed seed, so the same chunks step the same way
+/// every run.
+pub struct TestGrid<T, const N: i32> {
+    app: App,
+    chunks: HashMap<IVec2, Entity>,
+    _cell: PhantomData<T>,
+}
+
+impl<T, const N: i32> TestGrid<T, N>
+where
+    T: Renderable,
+{
+    pub fn new(seed: u64) -> Self {
+        let mut app = App::new();
+
+        app
+            .add_plugins(MinimalPlugins)
+            .add_plugins(PowderkegSimulationPlugin::<T, N>::default())
+            .insert_resource(PowderkegRng::new(seed))
+            .insert_resource(PowderkegPaused(true));
+
+        Self { app, chunks: HashMap::default(), _cell: PhantomData }
+    }
+
+    /// Simulates chunks of `U` cells alongside the `T` cells, stepped together with them. Chunks of `U` are spawned
+    /// through [`TestGrid::app_mut`].
+    pub fn simulate_also<U: Renderable>(&mut self) -> &mut Self {
+        self.app.add_plugins(PowderkegSimulationPlugin::<U, N>::default());
+
+        self
+    }
+
+    /// Adds `chunk` at the chunk coordinates `coords`, replacing any chunk already there.
+    pub fn insert_chunk(&mut self, coords: IVec2, chunk: Chunk<T, N>) -> &mut Self {
+        let entity = self.app.world.spawn(ChunkBundle::new(chunk, ChunkCoords::<N>(coords))).id();
+
+        if let Some(old) = self.chunks.insert(coords, entity) {
+            self.app.world.despawn(old);
+        }
+
+        self
+    }
+
+    pub fn chunk(&self, coords: IVec2) -> Option<&Chunk<T, N>> {
+        self.chunks.get(&coords).and_then(|entity| self.app.world.get::<Chunk<T, N>>(*entity))
+    }
+
+    pub fn chunk_mut(&mut self, coords: IVec2) -> Option<Mut<'_, Chunk<T, N>>> {
+        self.chunks.get(&coords).and_then(|entity| self.app.world.get_mut::<Chunk<T, N>>(*entity))
+    }
+
+    /// The cell at the world point `world`, `None` if no chunk was inserted there.
+    pub fn get(&self, world: IVec2) -> Option<&T> {
+        let (coords, local) = ChunkCoords::<N>::world_to_chunk_and_local(world);
+
+        self.chunk(coords).and_then(|chunk| chunk.get(local).ok())
+    }
+
+    /// Writes `cell` at the world point `world` and stains the cells around it, returning the cell it replaced.
+    pub fn set(&mut self, world: IVec2, cell: T) -> Result<T, PowderkegError<T>> {
+        let (coords, local) = ChunkCoords::<N>::world_to_chunk_and_local(world);
+
+        let old = self.chunk_mut(coords)
+            .ok_or(PowderkegError::ChunkOutOfBounds(coords))?
+            .replace(local, cell)?;
+
+        for (coords, local) in decompose_region::<N>(IRect::from_center_half_size(world, IVec2::ONE)) {
+            if let Some(mut chunk) = self.chunk_mut(coords) {
+                chunk.stain(local);
+            }
+        }
+
+        Ok(old)
+    }
+
+    /// Runs exactly one tick, returning the errors raised while ticking.
+    pub fn step(&mut self) -> &[SimulationError<T>] {
+        self.app.world.send_event(StepOnce);
+        self.app.update();
+
+        &self.app.world.resource::<PowderkegErrors<T>>().errors
+    }
+
+    /// The number of ticks run so far.
+    pub fn tick(&self) -> u64 {
+        self.app.world.resource::<PowderkegTick>().0
+    }
+
+    /// The app the simulation runs in, for inserting resources such as [`Gravity`](crate::simulation::Gravity) or
+    /// reading the [`TickReport`](crate::simulation::TickReport).
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+}
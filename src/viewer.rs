@@ -1,9 +1,21 @@
-use std::marker::PhantomData;
+use std::{borrow::Cow, marker::PhantomData, mem};
 
-use bevy::{asset::load_internal_asset, prelude::*, render::{render_asset::RenderAssetUsages, render_resource::AsBindGroup}, sprite::{Material2d, Material2dPlugin, Mesh2dHandle}};
-use image::{DynamicImage, RgbaImage};
+use bevy::{
+    asset::load_internal_asset,
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+    render::{
+        render_asset::{RenderAssetUsages, RenderAssets},
+        texture::ImageSampler,
+        mesh::MeshVertexBufferLayout,
+        render_resource::{AsBindGroup, Extent3d, RenderPipelineDescriptor, SpecializedMeshPipelineError, ImageCopyTexture, ImageDataLayout, Origin3d, TextureAspect, TextureDimension, TextureFormat},
+        renderer::RenderQueue,
+        ExtractSchedule, MainWorld, Render, RenderApp, RenderSet,
+    },
+    sprite::{Material2d, Material2dKey, Material2dPlugin, Mesh2dHandle},
+};
 
-use crate::{cell::Renderable, chunk::Chunk, grid::Grid, stain::Stainable, area::Area, PowderkegSet};
+use crate::{cell::Renderable, chunk::{Chunk, ChunkCoords}, grid::Grid, lighting::{LightMap, LightSettings}, stain::Stainable, area::Area, PowderkegSet};
 
 #[rustfmt::skip]
 pub const CHUNK_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(33721791328259611974385727409331747184);
@@ -26,58 +38,302 @@ where
     fn build(&self, app: &mut App) {
         load_internal_asset!(app, CHUNK_SHADER_HANDLE, "chunk.wgsl", Shader::from_wgsl);
         
+        if !app.is_plugin_added::<Material2dPlugin<ChunkMaterial>>() {
+            app.add_plugins(Material2dPlugin::<ChunkMaterial>::default());
+        }
+
+        if !app.world.contains_resource::<TextureUploads>() {
+            app
+                .init_resource::<TextureUploads>()
+                .register_diagnostic(Diagnostic::new(TEXTURE_UPLOAD_BYTES).with_suffix(" B"))
+                .add_systems(First, clear_texture_uploads)
+                .add_systems(Last, measure_texture_uploads);
+
+            if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+                render_app
+                    .init_resource::<TextureUploads>()
+                    .add_systems(ExtractSchedule, extract_texture_uploads)
+                    .add_systems(Render, upload_chunk_textures.in_set(RenderSet::Queue));
+            }
+        }
+
         app
-            .add_plugins(Material2dPlugin::<ChunkMaterial>::default())
+            .init_resource::<RenderChannel>()
+            .init_resource::<StainGizmoConfig>()
+            .init_resource::<HeatmapOverlay>()
+            .init_resource::<ChunkRenderConfig>()
             .add_systems(Update, (
                 instantiate_chunk_images::<T, N>,
+                select_chunk_lod::<T, N>,
                 generate_chunk_images::<T, N>,
+                draw_heatmap::<T, N>,
             ).chain().in_set(PowderkegSet::Render))
             .add_systems(Update, draw_stained::<T, N>);
     }
 }
 
+/// The strongest glow [`Renderable::emissive`] can give a cell, brightening it to this many times its color again.
+pub const MAX_EMISSIVE: f32 = 4.0;
+
+/// The bytes of chunk pixels sent to the GPU each frame.
+pub const TEXTURE_UPLOAD_BYTES: DiagnosticPath = DiagnosticPath::const_new("powderkeg/texture_upload_bytes");
+
+/// Pixels redrawn this frame, written straight into the chunk textures in the render world rather than through their
+/// image assets, which would upload every texture in full whenever any of its pixels changed.
+#[derive(Resource, Default)]
+struct TextureUploads {
+    uploads: Vec<TextureUpload>,
+    /// Bytes of whole images added this frame, these are uploaded through their assets.
+    image_bytes: usize,
+}
+
+struct TextureUpload {
+    image: AssetId<Image>,
+    /// The pixels of `rect` row by row, in the image's format.
+    rect: IRect,
+    data: Vec<u8>,
+}
+
+/// Selects which field of the cells the chunk images display.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderChannel {
+    #[default]
+    Color,
+    Temperature,
+    Age,
+    Pressure,
+}
+
+impl RenderChannel {
+    /// The color `cell` is drawn in, `state` is the state of its chunk.
+    pub fn color<T: Renderable>(&self, cell: &T, point: IVec2, state: &T::State) -> Color {
+        if cell.is_transparent() {
+            return Color::NONE;
+        }
+
+        if let RenderChannel::Color = self {
+            return Color::rgba_linear_from_array(cell.to_linear_rgba(point, state));
+        }
+
+        match cell.channel(*self, point) {
+            Some(value) => ColorRamp::HEAT.sample(value),
+            None => Color::rgb(0.5, 0.5, 0.5),
+        }
+    }
+}
+
+/// Linearly interpolates colors between stops sorted by position, clamping outside the first and last stop.
+///
+/// Interpolation is per component of the linear color, so blends mix light as the renderer does whatever color space
+/// the stops were given in. Colors between stops are returned as [`Color::RgbaLinear`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp(Cow<'static, [(f32, Color)]>);
+
+impl ColorRamp {
+    /// Blue through yellow to red, used for the auxiliary render channels.
+    pub const HEAT: ColorRamp = ColorRamp::from_static(&[
+        (0.0, Color::rgb(0.0, 0.0, 1.0)),
+        (0.5, Color::rgb(1.0, 1.0, 0.0)),
+        (1.0, Color::rgb(1.0, 0.0, 0.0)),
+    ]);
+
+    pub fn new(mut stops: Vec<(f32, Color)>) -> Self {
+        stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Self(Cow::Owned(stops))
+    }
+
+    /// Creates a ramp from stops that are already sorted by position.
+    pub const fn from_static(stops: &'static [(f32, Color)]) -> Self {
+        Self(Cow::Borrowed(stops))
+    }
+
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.0
+    }
+
+    pub fn sample(&self, position: f32) -> Color {
+        let stops = self.stops();
+
+        let Some(&(first_position, first)) = stops.first() else {
+            return Color::NONE;
+        };
+
+        if position <= first_position {
+            return first;
+        }
+
+        for window in stops.windows(2) {
+            let [(start_position, start), (end_position, end)] = [window[0], window[1]];
+
+            if position <= end_position {
+                if end_position <= start_position {
+                    return end;
+                }
+
+                let t = (position - start_position) / (end_position - start_position);
+
+                let [r, g, b, a] = Vec4::from_array(start.as_linear_rgba_f32()).lerp(Vec4::from_array(end.as_linear_rgba_f32()), t).to_array();
+
+                return Color::rgba_linear(r, g, b, a);
+            }
+        }
+
+        stops[stops.len() - 1].1
+    }
+}
+
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[bind_group_data(ChunkMaterialKey)]
 pub struct ChunkMaterial {
+    /// The chunk's colors, sRGB encoded so the shader samples them as linear RGBA.
+    ///
+    /// Chunk images live only in the render world, the main world [`Image`] holds no pixels since redraws are written
+    /// straight into the texture. Read cells from the [`Chunk`] or draw them with [`Renderable`] rather than reading
+    /// this on the CPU.
     #[texture(0)]
     #[sampler(1)]
     pub texture: Handle<Image>,
+    /// The glow of each cell scaled down by [`MAX_EMISSIVE`], `None` for cells without [`Renderable::EMISSIVE`],
+    /// whose chunks the shader then draws without sampling any glow.
+    #[texture(2)]
+    #[sampler(3)]
+    pub emissive: Option<Handle<Image>>,
+}
+
+/// Specializes the chunk shader on whether its material has an emissive texture.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChunkMaterialKey {
+    emissive: bool,
+}
+
+impl From<&ChunkMaterial> for ChunkMaterialKey {
+    fn from(material: &ChunkMaterial) -> Self {
+        Self { emissive: material.emissive.is_some() }
+    }
 }
 
 impl Material2d for ChunkMaterial {
     fn fragment_shader() -> bevy::render::render_resource::ShaderRef {
         CHUNK_SHADER_HANDLE.into()
     }
+
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if let Some(fragment) = descriptor.fragment.as_mut().filter(|_| key.bind_group_data.emissive) {
+            fragment.shader_defs.push("EMISSIVE".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders chunks far from the camera at half resolution, averaging 2x2 blocks of cells, disabled when absent.
+///
+/// A chunk is reduced while none of the cameras show its cells at least `min_pixels_per_cell` logical pixels across,
+/// so zooming out reduces chunks once the detail would be lost anyway and zooming in restores them. Reduced images take
+/// a quarter of the memory and upload bandwidth of full resolution ones.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ChunkLodSettings {
+    pub min_pixels_per_cell: f32,
+}
+
+/// Called with the world position of every stained cell redrawn by the chunk images, for attaching visual reactions
+/// such as particles to exactly the cells that changed. Cells only redrawn because the [`RenderChannel`] or
+/// [`HeatmapOverlay`] changed or their light did are skipped. Nothing is called when the resource is absent.
+#[allow(clippy::type_complexity)]
+#[derive(Resource)]
+pub struct RenderHook<T: Renderable>(pub Box<dyn Fn(IVec2, &T) + Send + Sync>);
+
+impl<T: Renderable> RenderHook<T> {
+    pub fn new(hook: impl Fn(IVec2, &T) + Send + Sync + 'static) -> Self {
+        Self(Box::new(hook))
+    }
 }
 
+/// How chunk images are sampled, independent of the [`ImagePlugin`] default the rest of the app uses. Nearest by
+/// default so cells stay crisp however the chunks are scaled. Changing it recreates every chunk's images.
+///
+/// Chunk images have no mipmaps and there is no option for them, since redraws only ever write the full resolution
+/// level and would leave every smaller level stale. Zoomed out chunks are reduced by [`ChunkLodSettings`] instead.
+#[derive(Resource, Debug, Clone)]
+pub struct ChunkRenderConfig {
+    pub sampler: ImageSampler,
+}
+
+impl Default for ChunkRenderConfig {
+    fn default() -> Self {
+        Self { sampler: ImageSampler::nearest() }
+    }
+}
+
+/// Draws every chunk in one color in place of its cells, by how many cells its last tick ticked on
+/// [`ColorRamp::HEAT`], from blue for none to red for the busiest chunk. For finding where the simulation spends its
+/// time.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HeatmapOverlay(pub bool);
+
+/// What a chunk's images were last drawn with, chunks hidden when the [`RenderChannel`] or [`HeatmapOverlay`] changes
+/// are redrawn in full once they are visible again.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct DrawnChannel {
+    channel: RenderChannel,
+    /// The heatmap color the chunk is drawn in, `None` while its cells are drawn.
+    heat: Option<[u8; 4]>,
+}
+
+impl DrawnChannel {
+    fn cells(channel: RenderChannel) -> Self {
+        Self { channel, heat: None }
+    }
+}
+
+/// The resolution a chunk's image is currently rendered at.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkLod {
+    #[default]
+    Full,
+    Reduced,
+}
+
+impl ChunkLod {
+    /// The width in cells of each pixel.
+    pub fn factor(&self) -> i32 {
+        match self {
+            ChunkLod::Full => 1,
+            ChunkLod::Reduced => 2,
+        }
+    }
+
+    /// The width in pixels of the image of a chunk `size` cells wide.
+    pub fn resolution(&self, size: i32) -> i32 {
+        (size + self.factor() - 1) / self.factor()
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn instantiate_chunk_images<T: Renderable + Send + Sync + 'static, const N: i32>(
     mut commands: Commands,
     query: Query<(Entity, &Chunk<T, N>), (Without<Mesh2dHandle>, Without<Handle<ChunkMaterial>>)>,
     mut images: ResMut<Assets<Image>>,
     mut materials: ResMut<Assets<ChunkMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
+    channel: Res<RenderChannel>,
+    config: Res<ChunkRenderConfig>,
+    mut uploads: ResMut<TextureUploads>,
 ) {
     for (entity, chunk) in query.iter() {
-        let image_buffer = RgbaImage::new(N as u32, N as u32);
-        let dynamic = DynamicImage::from(image_buffer);
-        let mut image = Image::from_dynamic(dynamic, true, RenderAssetUsages::all());
+        let image = chunk_image(chunk, *channel, ChunkLod::Full, None, &config.sampler);
+        let emissive = T::EMISSIVE.then(|| emissive_image(chunk, *channel, ChunkLod::Full, &config.sampler));
 
-        for y in 0..N {
-            for x in 0..N {
-                let point = IVec2::new(x, y);
-
-                if let Some(index) = chunk.index(point) {
-                    let [r, g, b, a] = chunk.at(point).to_color(point).as_rgba_u8();
-
-                    image.data[4 * index + 0] = r;
-                    image.data[4 * index + 1] = g;
-                    image.data[4 * index + 2] = b;
-                    image.data[4 * index + 3] = a;
-                }
-            }
-        }
+        uploads.image_bytes += image.data.len() + emissive.as_ref().map_or(0, |emissive| emissive.data.len());
 
         let material = ChunkMaterial {
             texture: images.add(image),
+            emissive: emissive.map(|emissive| images.add(emissive)),
         };
 
         commands
@@ -85,63 +341,437 @@ fn instantiate_chunk_images<T: Renderable + Send + Sync + 'static, const N: i32>
             .insert((
                 Mesh2dHandle::from(meshes.add(Rectangle::new(N as f32, N as f32))),
                 materials.add(material),
+                ChunkLod::Full,
+                DrawnChannel::cells(*channel),
             ));
     }
 }
 
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn select_chunk_lod<T, const N: i32>(
+    settings: Option<Res<ChunkLodSettings>>,
+    cameras: Query<(&Camera, &OrthographicProjection)>,
+    mut chunks: Query<(&Chunk<T, N>, &GlobalTransform, &Handle<ChunkMaterial>, &mut ChunkLod, &mut DrawnChannel, Option<&LightMap<N>>)>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<ChunkMaterial>>,
+    channel: Res<RenderChannel>,
+    light_settings: Option<Res<LightSettings>>,
+    config: Res<ChunkRenderConfig>,
+    mut uploads: ResMut<TextureUploads>,
+) where
+    T: Renderable,
+{
+    let light_settings = light_settings.as_deref().copied().unwrap_or_default();
+    let reconfigured = config.is_changed() && !config.is_added();
+
+    for (chunk, transform, material_handle, mut lod, mut drawn, light) in chunks.iter_mut() {
+        let selected = match settings.as_deref() {
+            Some(settings) => {
+                // The chunk's mesh is a world unit per cell before its transform scales it.
+                let cell_size = transform.affine().matrix3.x_axis.length();
+
+                let coarse = cameras
+                    .iter()
+                    .filter_map(|(camera, projection)| Some(camera.logical_viewport_size()?.x / projection.area.width()))
+                    .reduce(f32::max)
+                    .is_some_and(|pixels_per_unit| pixels_per_unit * cell_size < settings.min_pixels_per_cell);
+
+                if coarse { ChunkLod::Reduced } else { ChunkLod::Full }
+            },
+            None => ChunkLod::Full,
+        };
+
+        if *lod == selected && !reconfigured {
+            continue;
+        }
+
+        let Some(material) = materials.get_mut(material_handle) else {
+            continue;
+        };
+
+        let image = chunk_image(chunk, *channel, selected, light.map(|light| (light, light_settings)), &config.sampler);
+        // The images are replaced under the same handles, touching the material rebinds them at their new size.
+        uploads.image_bytes += image.data.len();
+        images.insert(material.texture.id(), image);
+
+        if let Some(handle) = &material.emissive {
+            let emissive = emissive_image(chunk, *channel, selected, &config.sampler);
+
+            uploads.image_bytes += emissive.data.len();
+            images.insert(handle.id(), emissive);
+        }
+
+        *lod = selected;
+        *drawn = DrawnChannel::cells(*channel);
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn generate_chunk_images<T, const N: i32>(
     mut chunks: Query<(
         &Chunk<T, N>,
-        &mut Handle<ChunkMaterial>,
-        &ViewVisibility
+        &ChunkCoords<N>,
+        &Handle<ChunkMaterial>,
+        &ChunkLod,
+        &mut DrawnChannel,
+        &ViewVisibility,
+        Option<&LightMap<N>>,
     )>,
-    mut images: ResMut<Assets<Image>>,
-    mut materials: ResMut<Assets<ChunkMaterial>>,
+    materials: Res<Assets<ChunkMaterial>>,
+    channel: Res<RenderChannel>,
+    hook: Option<Res<RenderHook<T>>>,
+    light_settings: Option<Res<LightSettings>>,
+    heatmap: Res<HeatmapOverlay>,
+    mut uploads: ResMut<TextureUploads>,
 ) where
     T: Renderable,
 {
-    for (chunk, material_handle, visible) in chunks.iter_mut() {
+    if heatmap.0 {
+        return;
+    }
+
+    let light_settings = light_settings.as_deref().copied().unwrap_or_default();
+
+    for (chunk, coords, material_handle, lod, mut drawn, visible, light) in chunks.iter_mut() {
         if !visible.get() {
             continue;
         }
 
-        let stain = chunk.stained();
+        // Turning the heatmap off redraws the cells it covered.
+        let mut stain = if *drawn != DrawnChannel::cells(*channel) {
+            Chunk::<T, N>::area().into()
+        } else {
+            match light {
+                Some(light) => Area::from_areas([chunk.stained(), light.changed().clone()].into_iter()),
+                None => chunk.stained(),
+            }
+        };
+
+        stain.coalesce();
 
         if stain.is_empty() {
             continue;
         }
 
-        let Some(material) = materials.get_mut(&*material_handle) else {
+        let Some(material) = materials.get(material_handle) else {
             continue;
         };
 
-        let Some(image) = images.get_mut(&material.texture) else {
+        let light = light.map(|light| (light, light_settings));
+
+        *drawn = DrawnChannel::cells(*channel);
+
+        for rect in stain_blocks::<T, N>(*lod, &stain).rects() {
+            uploads.uploads.push(TextureUpload { image: material.texture.id(), rect: *rect, data: draw_blocks(chunk, *channel, *lod, light, *rect) });
+
+            if let Some(emissive) = &material.emissive {
+                uploads.uploads.push(TextureUpload { image: emissive.id(), rect: *rect, data: draw_emissive(chunk, *channel, *lod, *rect) });
+            }
+        }
+
+        if let Some(hook) = hook.as_deref() {
+            let mut changed = chunk.stained();
+
+            changed.coalesce();
+
+            for point in changed.points() {
+                if let Ok(cell) = chunk.get(point) {
+                    (hook.0)(coords.local_to_world(point), cell);
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn draw_heatmap<T, const N: i32>(
+    mut chunks: Query<(&Chunk<T, N>, &Handle<ChunkMaterial>, &ChunkLod, &mut DrawnChannel, &ViewVisibility)>,
+    materials: Res<Assets<ChunkMaterial>>,
+    heatmap: Res<HeatmapOverlay>,
+    mut uploads: ResMut<TextureUploads>,
+) where
+    T: Renderable,
+{
+    if !heatmap.0 {
+        return;
+    }
+
+    let busiest = chunks.iter().map(|(chunk, ..)| chunk.cells_ticked()).max().unwrap_or_default().max(1);
+
+    for (chunk, material_handle, lod, mut drawn, visible) in chunks.iter_mut() {
+        if !visible.get() {
+            continue;
+        }
+
+        let color = encode_srgba8(ColorRamp::HEAT.sample(chunk.cells_ticked() as f32 / busiest as f32));
+
+        // Chunks whose color is unchanged are left as they are rather than uploaded again every frame.
+        if drawn.heat == Some(color) {
+            continue;
+        }
+
+        let Some(material) = materials.get(material_handle) else {
             continue;
         };
 
-        stain.apply(|point| {
-            if let Some(index) = chunk.index(point) {
-                let cell = chunk.at(point);
-                let [r, g, b, a] = cell.to_color(point).as_rgba_u8();
+        let resolution = lod.resolution(N);
+        let rect = IRect::new(0, 0, resolution - 1, resolution - 1);
 
-                image.data[4 * index + 0] = r;
-                image.data[4 * index + 1] = g;
-                image.data[4 * index + 2] = b;
-                image.data[4 * index + 3] = a;
-            }
+        drawn.heat = Some(color);
+
+        uploads.uploads.push(TextureUpload {
+            image: material.texture.id(),
+            rect,
+            data: color.repeat((resolution * resolution) as usize),
         });
+
+        if let Some(emissive) = &material.emissive {
+            uploads.uploads.push(TextureUpload { image: emissive.id(), rect, data: vec![0; (resolution * resolution) as usize] });
+        }
+    }
+}
+
+fn chunk_image<T, const N: i32>(chunk: &Chunk<T, N>, channel: RenderChannel, lod: ChunkLod, light: Option<(&LightMap<N>, LightSettings)>, sampler: &ImageSampler) -> Image
+where
+    T: Renderable,
+{
+    let resolution = lod.resolution(N);
+    let data = draw_blocks(chunk, channel, lod, light, IRect::new(0, 0, resolution - 1, resolution - 1));
+
+    // Only the render world keeps the pixels, later redraws write into the texture directly. The pixels are sRGB
+    // encoded, which keeps dark colors apart in eight bits, and sampling decodes them back to linear for the shader.
+    let mut image = Image::new(
+        Extent3d { width: resolution as u32, height: resolution as u32, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+
+    image.sampler = sampler.clone();
+
+    image
+}
+
+/// The rects of pixels covering the part of the local `stain` within the chunk.
+fn stain_blocks<T, const N: i32>(lod: ChunkLod, stain: &Area) -> Area
+where
+    T: Renderable,
+{
+    let factor = lod.factor();
+
+    let mut blocks = Area::Empty;
+
+    // Stains exchanged between chunks can reach past the chunk, clipping them first keeps every block in the image.
+    for rect in stain.intersect_rect(Chunk::<T, N>::area()).rects() {
+        blocks.push(IRect { min: rect.min / factor, max: rect.max / factor });
+    }
+
+    blocks.coalesce();
+
+    blocks
+}
+
+/// The sRGB pixels of the image rect `blocks` row by row, at reduced resolution each pixel covers every cell of its block.
+fn draw_blocks<T, const N: i32>(
+    chunk: &Chunk<T, N>,
+    channel: RenderChannel,
+    lod: ChunkLod,
+    light: Option<(&LightMap<N>, LightSettings)>,
+    blocks: IRect,
+) -> Vec<u8>
+where
+    T: Renderable,
+{
+    let factor = lod.factor();
+    let mut data = Vec::with_capacity(4 * (blocks.width() + 1) as usize * (blocks.height() + 1) as usize);
+
+    // Read once for the whole rect rather than locked again for every cell.
+    let state = chunk.state().read();
+
+    for y in blocks.min.y..=blocks.max.y {
+        for x in blocks.min.x..=blocks.max.x {
+            data.extend_from_slice(&encode_srgba8(block_color(chunk, &state, channel, light, IVec2::new(x, y) * factor, factor)));
+        }
+    }
+
+    data
+}
+
+/// An image of the glow of every cell, one byte a pixel, see [`ChunkMaterial::emissive`].
+fn emissive_image<T, const N: i32>(chunk: &Chunk<T, N>, channel: RenderChannel, lod: ChunkLod, sampler: &ImageSampler) -> Image
+where
+    T: Renderable,
+{
+    let resolution = lod.resolution(N);
+    let data = draw_emissive(chunk, channel, lod, IRect::new(0, 0, resolution - 1, resolution - 1));
+
+    let mut image = Image::new(
+        Extent3d { width: resolution as u32, height: resolution as u32, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+
+    image.sampler = sampler.clone();
+
+    image
+}
+
+/// The glow of the image rect `blocks` row by row, at reduced resolution each pixel averages every cell of its block.
+/// Only the color channel glows.
+fn draw_emissive<T, const N: i32>(chunk: &Chunk<T, N>, channel: RenderChannel, lod: ChunkLod, blocks: IRect) -> Vec<u8>
+where
+    T: Renderable,
+{
+    let factor = lod.factor();
+    let mut data = Vec::with_capacity((blocks.width() + 1) as usize * (blocks.height() + 1) as usize);
+
+    for y in blocks.min.y..=blocks.max.y {
+        for x in blocks.min.x..=blocks.max.x {
+            let min = IVec2::new(x, y) * factor;
+
+            let emissive = match channel {
+                RenderChannel::Color => (0..factor)
+                    .flat_map(|y| (0..factor).map(move |x| min + IVec2::new(x, y)))
+                    .filter_map(|point| chunk.get(point).ok().map(|cell| cell.emissive(point)))
+                    .sum::<f32>() / (factor * factor) as f32,
+                _ => 0.0,
+            };
+
+            data.push(((emissive / MAX_EMISSIVE).clamp(0.0, 1.0) * u8::MAX as f32).round() as u8);
+        }
     }
+
+    data
+}
+
+fn clear_texture_uploads(mut uploads: ResMut<TextureUploads>) {
+    uploads.uploads.clear();
+    uploads.image_bytes = 0;
+}
+
+fn measure_texture_uploads(uploads: Res<TextureUploads>, mut diagnostics: Diagnostics) {
+    diagnostics.add_measurement(&TEXTURE_UPLOAD_BYTES, || {
+        (uploads.image_bytes + uploads.uploads.iter().map(|upload| upload.data.len()).sum::<usize>()) as f64
+    });
+}
+
+/// Moves the frame's uploads into the render world, they were measured in [`Last`] and are not read again.
+fn extract_texture_uploads(mut uploads: ResMut<TextureUploads>, mut main_world: ResMut<MainWorld>) {
+    if let Some(mut main_uploads) = main_world.get_resource_mut::<TextureUploads>() {
+        uploads.uploads = mem::take(&mut main_uploads.uploads);
+    }
+}
+
+/// Writes the redrawn pixels into their textures, uploads to images that are no longer prepared are dropped.
+fn upload_chunk_textures(mut uploads: ResMut<TextureUploads>, images: Res<RenderAssets<Image>>, queue: Res<RenderQueue>) {
+    for upload in uploads.uploads.drain(..) {
+        let Some(image) = images.get(upload.image) else {
+            continue;
+        };
+
+        let width = (upload.rect.width() + 1) as u32;
+        let height = (upload.rect.height() + 1) as u32;
+
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &image.texture,
+                mip_level: 0,
+                origin: Origin3d { x: upload.rect.min.x as u32, y: upload.rect.min.y as u32, z: 0 },
+                aspect: TextureAspect::All,
+            },
+            &upload.data,
+            ImageDataLayout { offset: 0, bytes_per_row: Some(upload.data.len() as u32 / height), rows_per_image: None },
+            Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+    }
+}
+
+/// Averages the colors of the `factor` wide block at `min`, weighted by alpha so absent and transparent cells do
+/// not darken their neighbors.
+fn block_color<T, const N: i32>(chunk: &Chunk<T, N>, state: &T::State, channel: RenderChannel, light: Option<(&LightMap<N>, LightSettings)>, min: IVec2, factor: i32) -> Color
+where
+    T: Renderable,
+{
+    if factor == 1 {
+        return match chunk.get(min) {
+            Ok(cell) => lit_color(channel.color(cell, min, state), channel, light, min),
+            Err(_) => Color::NONE,
+        };
+    }
+
+    let mut color = Vec3::ZERO;
+    let mut alpha = 0.0;
+
+    for y in 0..factor {
+        for x in 0..factor {
+            let point = min + IVec2::new(x, y);
+
+            if let Ok(cell) = chunk.get(point) {
+                let [r, g, b, a] = lit_color(channel.color(cell, point, state), channel, light, point).as_linear_rgba_f32();
+
+                color += Vec3::new(r, g, b) * a;
+                alpha += a;
+            }
+        }
+    }
+
+    if alpha == 0.0 {
+        return Color::NONE;
+    }
+
+    let color = color / alpha;
+
+    Color::rgba_linear(color.x, color.y, color.z, alpha / (factor * factor) as f32)
+}
+
+/// Multiplies the light at `point` into colors of the color channel, the other channels show data and are never lit.
+fn lit_color<const N: i32>(color: Color, channel: RenderChannel, light: Option<(&LightMap<N>, LightSettings)>, point: IVec2) -> Color {
+    let Some((light, settings)) = light.filter(|_| channel == RenderChannel::Color) else {
+        return color;
+    };
+
+    let brightness = settings.brightness(light.level(point));
+    let [r, g, b, a] = color.as_linear_rgba_f32();
+
+    Color::rgba_linear(r * brightness, g * brightness, b * brightness, a)
+}
+
+/// Encodes `color` as sRGB rounded to the nearest byte, for the chunk textures and images leaving the app such as
+/// exported tiles alike.
+pub(crate) fn encode_srgba8(color: Color) -> [u8; 4] {
+    color.as_rgba_f32().map(|channel| (channel.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8)
 }
 
 #[derive(Component)]
 pub struct DrawStained;
 
+/// How the stains of chunks with [`DrawStained`] are drawn, disabling it hides them all without touching the chunks.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct StainGizmoConfig {
+    pub color: Color,
+    pub enabled: bool,
+}
+
+impl Default for StainGizmoConfig {
+    fn default() -> Self {
+        Self { color: Color::RED, enabled: true }
+    }
+}
+
 fn draw_stained<T, const N: i32>(
     mut gizmos: Gizmos,
+    config: Res<StainGizmoConfig>,
     chunks: Query<(&GlobalTransform, &Chunk<T, N>), With<DrawStained>>,
 ) where
     T: Renderable,
 {
+    if !config.enabled {
+        return;
+    }
+
     for (transform, chunk) in chunks.iter() {
         let (s, _, t) = transform.to_scale_rotation_translation();
 
@@ -154,14 +784,14 @@ fn draw_stained<T, const N: i32>(
                 let min = (area.min.as_vec2() - Vec2::splat(N as f32 / 2.0)) * s + t;
                 let max = ((area.max + IVec2::ONE).as_vec2() - Vec2::splat(N as f32 / 2.0)) * s + t;
 
-                gizmos.rect_2d((max + min) / 2.0, 0.0, max - min, Color::RED);
+                gizmos.rect_2d((max + min) / 2.0, 0.0, max - min, config.color);
             },
             Area::Many(areas) => {
                 for area in areas.iter() {
                     let min = (area.min.as_vec2() - Vec2::splat(N as f32 / 2.0)) * s + t;
                     let max = ((area.max + IVec2::ONE).as_vec2() - Vec2::splat(N as f32 / 2.0)) * s + t;
     
-                    gizmos.rect_2d((max + min) / 2.0, 0.0, max - min, Color::RED);
+                    gizmos.rect_2d((max + min) / 2.0, 0.0, max - min, config.color);
                 }
             },
         }
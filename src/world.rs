@@ -0,0 +1,436 @@
+use std::{cell::RefCell, hash::{DefaultHasher, Hash, Hasher}, mem, sync::Arc};
+
+use bevy::{ecs::{component::Tick, system::{SystemChangeTick, SystemParam}}, prelude::*, utils::HashMap};
+use image::{Rgba, RgbaImage};
+use parking_lot::RwLock;
+
+use crate::{cell::{Cell, Renderable}, chunk::{decompose_region, Chunk, ChunkCoords}, grid::{flood, line, Grid, GridSnapshot}, stain::{ChangeKind, Stainable}, viewer::{encode_srgba8, RenderChannel}, PowderkegError};
+
+/// Reads and writes the cells of every chunk by world coordinates, painting across chunk boundaries as if the chunks
+/// were one grid. Writes stain the cells around them so their neighbors react.
+#[derive(SystemParam)]
+pub struct PowderkegWorld<'w, 's, T, const N: i32>
+where
+    T: Cell,
+{
+    chunks: Query<'w, 's, (Entity, &'static ChunkCoords<N>, &'static mut Chunk<T, N>, &'static GlobalTransform)>,
+    index: Local<'s, RefCell<ChunkIndex>>,
+    ticks: SystemChangeTick,
+}
+
+/// The entity of every chunk by its coordinates, built on the first lookup of each run of the system and reused for
+/// the rest of it, chunks can only be spawned or despawned between runs.
+#[derive(Default)]
+struct ChunkIndex {
+    built: Option<Tick>,
+    entities: HashMap<IVec2, Entity>,
+}
+
+impl<'w, 's, T, const N: i32> PowderkegWorld<'w, 's, T, N>
+where
+    T: Cell,
+{
+    /// The world cell under the world space `position`, such as a cursor from `Camera::viewport_to_world_2d`,
+    /// `None` if no chunk is spawned.
+    ///
+    /// The cell is found through the transform of the chunk it falls in, so chunks need not share one. Positions
+    /// outside every chunk are placed through the transform of the chunk they are closest to.
+    pub fn world_point(&self, position: Vec2) -> Option<IVec2> {
+        let half = Vec2::splat(N as f32 / 2.0);
+
+        self.chunks
+            .iter()
+            .map(|(_, coords, _, transform)| {
+                let local = transform.affine().inverse().transform_point3(position.extend(0.0)).truncate() + half;
+
+                // How far outside the chunk the position is, in cells, zero within it.
+                let outside = (local - local.clamp(Vec2::ZERO, Vec2::splat(N as f32))).length_squared();
+
+                (outside, coords.local_to_world(local.floor().as_ivec2()))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, point)| point)
+    }
+
+    pub fn get_cell(&self, world: IVec2) -> Option<&T> {
+        let (chunk_coords, local) = ChunkCoords::<N>::world_to_chunk_and_local(world);
+
+        self.chunk(chunk_coords).and_then(|chunk| chunk.get(local).ok())
+    }
+
+    /// Writes `cell` at `world`, returning the cell it replaced.
+    pub fn set_cell(&mut self, world: IVec2, cell: T) -> Result<T, PowderkegError<T>> {
+        let (chunk_coords, local) = ChunkCoords::<N>::world_to_chunk_and_local(world);
+
+        let old = self.chunk_mut(chunk_coords)
+            .ok_or(PowderkegError::ChunkOutOfBounds(chunk_coords))?
+            .replace(local, cell)?;
+
+        self.stain_around(world, 1);
+
+        Ok(old)
+    }
+
+    /// Swaps the cells at `a` and `b`, which may be in different chunks, staining the cells around both. Like
+    /// [`Grid::swap`] their chunk state is left where it was.
+    pub fn swap(&mut self, a: IVec2, b: IVec2) -> Result<(), PowderkegError<T>> {
+        let (a_coords, a_local) = ChunkCoords::<N>::world_to_chunk_and_local(a);
+        let (b_coords, b_local) = ChunkCoords::<N>::world_to_chunk_and_local(b);
+
+        if a_coords == b_coords {
+            self.chunk_mut(a_coords)
+                .ok_or(PowderkegError::ChunkOutOfBounds(a_coords))?
+                .swap(a_local, b_local)?;
+        } else {
+            let out_of_bounds = PowderkegError::SwapOutOfBounds { first: a_coords, second: b_coords };
+            let entities = [self.entity(a_coords), self.entity(b_coords)];
+
+            let [Some(a_entity), Some(b_entity)] = entities else {
+                return Err(out_of_bounds);
+            };
+
+            let Ok([(_, _, mut first, _), (_, _, mut second, _)]) = self.chunks.get_many_mut([a_entity, b_entity]) else {
+                return Err(out_of_bounds);
+            };
+
+            mem::swap(first.write(a_local)?, second.write(b_local)?);
+
+            first.record_change(a_local, ChangeKind::Moved);
+            second.record_change(b_local, ChangeKind::Moved);
+
+            if let (Some(first_cell), Some(second_cell)) = (first.written(a_local), second.written(b_local)) {
+                first.observe(a_local, second_cell, first_cell);
+                second.observe(b_local, first_cell, second_cell);
+            }
+        }
+
+        self.stain_around(a, 1);
+        self.stain_around(b, 1);
+
+        Ok(())
+    }
+
+    /// The state of the chunk holding `world`, `None` if no chunk is spawned there.
+    pub fn state_at(&self, world: IVec2) -> Option<Arc<RwLock<T::State>>> {
+        let (chunk_coords, _) = ChunkCoords::<N>::world_to_chunk_and_local(world);
+
+        self.chunk(chunk_coords).map(|chunk| chunk.state().clone())
+    }
+
+    /// Writes `cell` over every point within `radius` of `center` whose current cell passes `only_if`, skipping
+    /// points outside the spawned chunks. Returns how many were written.
+    pub fn paint_circle(&mut self, center: IVec2, radius: i32, cell: T, only_if: impl Fn(&T) -> bool) -> usize
+    where
+        T: Clone,
+    {
+        let bounds = IRect::from_center_half_size(center, IVec2::splat(radius));
+        let mut painted = 0;
+
+        for (chunk_coords, local) in decompose_region::<N>(bounds) {
+            let Some(mut chunk) = self.chunk_mut(chunk_coords) else {
+                continue;
+            };
+
+            for y in local.min.y..=local.max.y {
+                for x in local.min.x..=local.max.x {
+                    let point = IVec2::new(x, y);
+
+                    if (point + N * chunk_coords - center).length_squared() <= radius * radius
+                        && chunk.get(point).is_ok_and(&only_if)
+                        && chunk.replace(point, cell.clone()).is_ok()
+                    {
+                        painted += 1;
+                    }
+                }
+            }
+        }
+
+        if painted > 0 {
+            self.stain_around(center, radius + 1);
+        }
+
+        painted
+    }
+
+    /// Paints circles of `radius` along the line from `a` to `b`, a continuous stroke however far apart they are.
+    /// Each covered cell is written once however many circles overlap it. Returns how many cells were written.
+    pub fn paint_line(&mut self, a: IVec2, b: IVec2, radius: i32, cell: T, only_if: impl Fn(&T) -> bool) -> usize
+    where
+        T: Clone,
+    {
+        // The circles along the line overlap, so each row is merged into spans first and every cell is written once.
+        let mut rows: HashMap<i32, Vec<(i32, i32)>> = HashMap::default();
+
+        for point in line(a, b) {
+            for dy in -radius..=radius {
+                let half = circle_half_width(radius, dy);
+
+                rows.entry(point.y + dy).or_default().push((point.x - half, point.x + half));
+            }
+        }
+
+        let mut spans = Vec::new();
+
+        for (y, mut row) in rows {
+            row.sort_unstable();
+
+            let mut merged = row[0];
+
+            for (min, max) in row.into_iter().skip(1) {
+                if min > merged.1 + 1 {
+                    spans.push(IRect::new(merged.0, y, merged.1, y));
+                    merged = (min, max);
+                } else {
+                    merged.1 = merged.1.max(max);
+                }
+            }
+
+            spans.push(IRect::new(merged.0, y, merged.1, y));
+        }
+
+        let mut painted = 0;
+
+        for span in spans.iter() {
+            for (chunk_coords, local) in decompose_region::<N>(*span) {
+                let Some(mut chunk) = self.chunk_mut(chunk_coords) else {
+                    continue;
+                };
+
+                for x in local.min.x..=local.max.x {
+                    let point = IVec2::new(x, local.min.y);
+
+                    if chunk.get(point).is_ok_and(&only_if) && chunk.replace(point, cell.clone()).is_ok() {
+                        painted += 1;
+                    }
+                }
+            }
+        }
+
+        if painted > 0 {
+            for span in spans {
+                self.stain(IRect::from_corners(span.min - IVec2::ONE, span.max + IVec2::ONE));
+            }
+        }
+
+        painted
+    }
+
+    /// Like [`Grid::flood_fill`] across every spawned chunk, each written cell stains the cells around it.
+    pub fn flood_fill(&mut self, start: IVec2, replacement: T, matches: impl Fn(&T) -> bool, limit: Option<usize>) -> usize
+    where
+        T: Clone,
+    {
+        flood(start, limit, |point| {
+            self.get_cell(point).is_some_and(&matches) && self.set_cell(point, replacement.clone()).is_ok()
+        })
+    }
+
+    /// Like [`Grid::count_matching`] across every spawned chunk.
+    pub fn count_matching(&self, region: IRect, pred: impl Fn(&T) -> bool) -> usize {
+        decompose_region::<N>(region)
+            .filter_map(|(chunk_coords, local)| self.chunk(chunk_coords).map(|chunk| chunk.count_matching(local, &pred)))
+            .sum()
+    }
+
+    /// Like [`Grid::snapshot_region`] across every spawned chunk, points outside them are left out.
+    pub fn snapshot_region(&self, region: IRect) -> GridSnapshot<T>
+    where
+        T: Clone,
+        T::State: Clone,
+    {
+        GridSnapshot::new(region, |point| Some((self.get_cell(point)?.clone(), self.state_at(point)?)))
+    }
+
+    /// Like [`Stainable::restore`] across every spawned chunk, returning how many cells were written.
+    pub fn restore(&mut self, snapshot: &GridSnapshot<T>) -> usize
+    where
+        T: Clone,
+        T::State: Clone,
+    {
+        let mut restored = 0;
+
+        for (chunk_coords, local) in decompose_region::<N>(snapshot.region()) {
+            let Some(mut chunk) = self.chunk_mut(chunk_coords) else {
+                continue;
+            };
+
+            for y in local.min.y..=local.max.y {
+                for x in local.min.x..=local.max.x {
+                    let point = IVec2::new(x, y);
+
+                    if let Some(cell) = snapshot.get(point + N * chunk_coords) {
+                        if chunk.replace(point, cell.clone()).is_ok() {
+                            restored += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        for (point, state) in snapshot.states() {
+            if let Some(lock) = self.state_at(point) {
+                *lock.write() = state.clone();
+            }
+        }
+
+        self.stain(snapshot.region());
+
+        restored
+    }
+
+    /// Stains the world `rect` in every chunk it overlaps.
+    pub fn stain(&mut self, rect: IRect) {
+        for (chunk_coords, local) in decompose_region::<N>(rect) {
+            if let Some(mut chunk) = self.chunk_mut(chunk_coords) {
+                chunk.stain(local);
+            }
+        }
+    }
+
+    pub fn stain_around(&mut self, point: IVec2, radius: i32) {
+        self.stain(IRect::from_center_half_size(point, IVec2::splat(radius)));
+    }
+
+    /// The entity of the chunk at `chunk_coords`, indexing every chunk on the first lookup of each system run.
+    fn entity(&self, chunk_coords: IVec2) -> Option<Entity> {
+        let mut index = self.index.borrow_mut();
+        let this_run = self.ticks.this_run();
+
+        if index.built != Some(this_run) {
+            index.entities = self.chunks.iter().map(|(entity, ChunkCoords(coords), _, _)| (*coords, entity)).collect();
+            index.built = Some(this_run);
+        }
+
+        index.entities.get(&chunk_coords).copied()
+    }
+
+    fn chunk(&self, chunk_coords: IVec2) -> Option<&Chunk<T, N>> {
+        self.entity(chunk_coords).and_then(|entity| self.chunks.get(entity).ok()).map(|(_, _, chunk, _)| chunk)
+    }
+
+    fn chunk_mut(&mut self, chunk_coords: IVec2) -> Option<Mut<'_, Chunk<T, N>>> {
+        self.entity(chunk_coords).and_then(|entity| self.chunks.get_mut(entity).ok()).map(|(_, _, chunk, _)| chunk)
+    }
+}
+
+/// How far a circle of `radius` reaches either side of its center on the row `dy` from it.
+fn circle_half_width(radius: i32, dy: i32) -> i32 {
+    let mut half = ((radius * radius - dy * dy) as f32).sqrt() as i32;
+
+    // Float rounding can be off by one either way for large radii.
+    while half * half + dy * dy > radius * radius {
+        half -= 1;
+    }
+
+    while (half + 1) * (half + 1) + dy * dy <= radius * radius {
+        half += 1;
+    }
+
+    half
+}
+
+/// Lists the coordinates of every spawned chunk, for example `spawned_chunk_coords(&coords)` with a `Query<&ChunkCoords<N>>`.
+pub fn spawned_chunk_coords<'a, const N: i32>(coords: impl IntoIterator<Item = &'a ChunkCoords<N>> + 'a) -> impl Iterator<Item = IVec2> + 'a {
+    coords.into_iter().map(|ChunkCoords(coords)| *coords)
+}
+
+/// Whether the world `point` is stained in whichever chunk holds it, false if that chunk is not spawned.
+/// For example `world_is_stained(&chunks, point)` with a `Query<(&ChunkCoords<N>, &Chunk<T, N>)>`.
+pub fn world_is_stained<'a, T, const N: i32>(chunks: impl IntoIterator<Item = (&'a ChunkCoords<N>, &'a Chunk<T, N>)>, point: IVec2) -> bool
+where
+    T: Cell,
+{
+    let (chunk_coords, local) = ChunkCoords::<N>::world_to_chunk_and_local(point);
+
+    chunks
+        .into_iter()
+        .find(|(ChunkCoords(coords), _)| *coords == chunk_coords)
+        .is_some_and(|(_, chunk)| chunk.is_stained(local))
+}
+
+/// Sums the stained cells of every chunk, for example `total_stained_cells(&chunks)` with a `Query<&Chunk<T, N>>`.
+pub fn total_stained_cells<'a, T, const N: i32>(chunks: impl IntoIterator<Item = &'a Chunk<T, N>>) -> usize
+where
+    T: Cell,
+{
+    chunks
+        .into_iter()
+        .map(|chunk| chunk.stained().cell_count())
+        .sum()
+}
+
+/// Hashes every chunk's cells in coordinate order, optionally including their stains.
+///
+/// The hash is stable between runs of the same build, making it suitable for regression tests and desync checks.
+pub fn world_state_hash<'a, T, const N: i32>(
+    chunks: impl IntoIterator<Item = (&'a ChunkCoords<N>, &'a Chunk<T, N>)>,
+    include_stain: bool,
+) -> u64
+where
+    T: Cell + Hash,
+{
+    let mut chunks: Vec<_> = chunks.into_iter().collect();
+
+    chunks.sort_unstable_by_key(|(ChunkCoords(coords), _)| (coords.y, coords.x));
+
+    let mut hasher = DefaultHasher::new();
+
+    for (ChunkCoords(coords), chunk) in chunks {
+        coords.hash(&mut hasher);
+        chunk.cells().hash(&mut hasher);
+
+        if include_stain {
+            for area in chunk.stained().rects() {
+                area.min.hash(&mut hasher);
+                area.max.hash(&mut hasher);
+            }
+        }
+    }
+
+    hasher.finish()
+}
+
+/// Renders every spawned chunk into `tile_size` square images aligned to multiples of `tile_size` in world space,
+/// returning each tile's coordinates sorted by row then column. Only tiles overlapping a chunk are produced and
+/// anything not covered by a present cell is transparent.
+///
+/// Images are top row first, so the tile at `(x, y)` sits left of `(x + 1, y)` and below `(x, y + 1)`.
+pub fn export_world_tiles<'a, T, const N: i32>(
+    chunks: impl IntoIterator<Item = (&'a ChunkCoords<N>, &'a Chunk<T, N>)>,
+    tile_size: u32,
+) -> Vec<(IVec2, RgbaImage)>
+where
+    T: Renderable,
+{
+    assert!(tile_size > 0);
+
+    let size = tile_size as i32;
+    let mut tiles = HashMap::<IVec2, RgbaImage>::new();
+
+    for (coords, chunk) in chunks {
+        let state = chunk.state().read();
+
+        for y in 0..N {
+            for x in 0..N {
+                let local = IVec2::new(x, y);
+                let world = coords.local_to_world(local);
+
+                let tile = tiles
+                    .entry(world.div_euclid(IVec2::splat(size)))
+                    .or_insert_with(|| RgbaImage::new(tile_size, tile_size));
+
+                if let Ok(cell) = chunk.get(local) {
+                    let pixel = world.rem_euclid(IVec2::splat(size));
+
+                    tile.put_pixel(pixel.x as u32, (size - 1 - pixel.y) as u32, Rgba(encode_srgba8(RenderChannel::Color.color(cell, local, &state))));
+                }
+            }
+        }
+    }
+
+    let mut tiles: Vec<_> = tiles.into_iter().collect();
+
+    tiles.sort_unstable_by_key(|(tile, _)| (tile.y, tile.x));
+
+    tiles
+}
@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+use powderkeg::{chunk::Chunk, simulation::ActiveRegion, testing::TestGrid};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+#[test]
+fn chunks_outside_the_region_wait_with_their_stain() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()));
+    grid.insert_chunk(IVec2::X, Chunk::full_copied(SandCell::Air, ()));
+    grid.app_mut().insert_resource(ActiveRegion(IRect::new(0, 0, CHUNK_SIZE - 1, CHUNK_SIZE - 1)));
+
+    let inside = IVec2::new(4, 8);
+    let outside = IVec2::new(CHUNK_SIZE + 4, 8);
+
+    grid.set(inside, SandCell::Sand).unwrap();
+    grid.set(outside, SandCell::Sand).unwrap();
+
+    for _ in 0..4 {
+        grid.step();
+    }
+
+    assert_eq!(grid.get(inside - 4 * IVec2::Y), Some(&SandCell::Sand));
+    assert_eq!(grid.get(outside), Some(&SandCell::Sand));
+    assert_eq!(grid.chunk(IVec2::X).unwrap().cells_ticked(), 0);
+
+    grid.app_mut().world.remove_resource::<ActiveRegion>();
+    grid.step();
+
+    assert_eq!(grid.get(outside - IVec2::Y), Some(&SandCell::Sand));
+}
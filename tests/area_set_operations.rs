@@ -0,0 +1,62 @@
+use bevy::{math::{IRect, IVec2}, utils::HashSet};
+use powderkeg::area::Area;
+
+fn cells(area: &Area) -> HashSet<IVec2> {
+    area.points().collect()
+}
+
+fn rect(min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Area {
+    Area::Area(IRect::new(min_x, min_y, max_x, max_y))
+}
+
+#[test]
+fn disjoint_areas() {
+    let a = rect(0, 0, 3, 3);
+    let b = rect(10, 10, 12, 12);
+
+    assert!(a.intersect(&b).is_empty());
+    assert_eq!(cells(&a.subtract(&b)), cells(&a));
+    assert_eq!(a.subtract(&b).rects(), a.rects());
+}
+
+#[test]
+fn overlapping_areas() {
+    let a = rect(0, 0, 5, 5);
+    let b = rect(3, 3, 8, 8);
+
+    let intersection = a.intersect(&b);
+
+    assert_eq!(intersection.rects(), &[IRect::new(3, 3, 5, 5)]);
+
+    let difference = a.subtract(&b);
+
+    assert_eq!(difference.cell_count(), 36 - 9);
+    assert_eq!(cells(&difference), cells(&a).difference(&cells(&b)).copied().collect());
+    assert!(cells(&difference).is_disjoint(&cells(&intersection)));
+}
+
+#[test]
+fn contained_areas() {
+    let outer = rect(0, 0, 9, 9);
+    let inner = rect(2, 3, 4, 5);
+
+    assert_eq!(outer.intersect(&inner).rects(), inner.rects());
+    assert!(inner.subtract(&outer).is_empty());
+
+    // A hole in the middle leaves a ring of pieces around it.
+    let ring = outer.subtract(&inner);
+
+    assert_eq!(ring.cell_count(), 100 - 9);
+    assert!(!ring.contains(IVec2::new(3, 4)));
+    assert!(matches!(ring, Area::Many(_)));
+}
+
+#[test]
+fn many_rects_against_many_rects() {
+    let a = Area::Many(vec![IRect::new(0, 0, 3, 3), IRect::new(6, 0, 9, 3)]);
+    let b = Area::Many(vec![IRect::new(2, 1, 7, 2), IRect::new(20, 20, 21, 21)]);
+
+    assert_eq!(cells(&a.intersect(&b)), cells(&a).intersection(&cells(&b)).copied().collect());
+    assert_eq!(cells(&a.subtract(&b)), cells(&a).difference(&cells(&b)).copied().collect());
+    assert!(Area::Empty.intersect(&a).is_empty() && Area::Empty.subtract(&a).is_empty());
+}
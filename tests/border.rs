@@ -0,0 +1,52 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, simulation::BorderBehavior, testing::TestGrid};
+
+fn falling_onto_the_border(border: BorderBehavior<SandCell>) -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    grid.app_mut().insert_resource(border);
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+    grid.set(IVec2::new(3, 2), SandCell::Sand).unwrap();
+
+    for _ in 0..4 {
+        grid.step();
+    }
+
+    grid
+}
+
+#[test]
+fn solid_border_holds_cells_in() {
+    let grid = falling_onto_the_border(BorderBehavior::Solid(SandCell::Bedrock));
+
+    assert_eq!(grid.get(IVec2::new(3, 0)), Some(&SandCell::Sand));
+}
+
+#[test]
+fn empty_border_lets_cells_leave() {
+    let grid = falling_onto_the_border(BorderBehavior::empty(SandCell::Air));
+
+    assert!((0..3).all(|y| grid.get(IVec2::new(3, y)) == Some(&SandCell::Air)));
+}
+
+#[test]
+fn absent_cells_of_a_chunk_are_not_border() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    // The bottom chunk has no cells at all, reads there must fail rather than see the empty border's air.
+    let absent = (0..CHUNK_SIZE * CHUNK_SIZE).map(|_| None).collect();
+
+    grid.app_mut().insert_resource(BorderBehavior::empty(SandCell::Air));
+    grid.insert_chunk(IVec2::ZERO, Chunk::sparse(absent, ()).without_initial_stain());
+    grid.insert_chunk(IVec2::Y, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+    grid.set(IVec2::new(3, CHUNK_SIZE), SandCell::Sand).unwrap();
+
+    for _ in 0..4 {
+        assert!(grid.step().is_empty());
+    }
+
+    assert_eq!(grid.get(IVec2::new(3, CHUNK_SIZE)), Some(&SandCell::Sand));
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, simulation::{CellChanged, EmitCellChanged}, testing::TestGrid};
+
+fn two_chunks_of_air() -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+    grid.insert_chunk(IVec2::Y, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+
+    grid
+}
+
+/// Steps once, returning the world points of the changes sent during the step.
+fn step(grid: &mut TestGrid<SandCell, CHUNK_SIZE>, reader: &mut ManualEventReader<CellChanged<SandCell>>) -> Vec<IVec2> {
+    grid.step();
+
+    let events = grid.app_mut().world.resource::<Events<CellChanged<SandCell>>>();
+
+    reader.read(events).map(|changed| changed.world).collect()
+}
+
+#[test]
+fn falling_sand_is_observed_within_and_across_chunks() {
+    let mut grid = two_chunks_of_air();
+    let mut reader = ManualEventReader::default();
+
+    grid.app_mut().insert_resource(EmitCellChanged::<SandCell>::new(|_, to| *to == SandCell::Sand));
+    grid.set(IVec2::new(3, CHUNK_SIZE + 1), SandCell::Sand).unwrap();
+
+    let observed: Vec<_> = (0..2 * CHUNK_SIZE).flat_map(|_| step(&mut grid, &mut reader)).collect();
+    let fallen: Vec<_> = (0..=CHUNK_SIZE).rev().map(|y| IVec2::new(3, y)).collect();
+
+    assert_eq!(observed, fallen);
+}
+
+#[test]
+fn nothing_is_sent_without_the_resource() {
+    let mut grid = two_chunks_of_air();
+    let mut reader = ManualEventReader::default();
+
+    grid.set(IVec2::new(3, CHUNK_SIZE + 1), SandCell::Sand).unwrap();
+
+    for _ in 0..3 {
+        assert!(step(&mut grid, &mut reader).is_empty());
+    }
+}
+
+#[test]
+fn edits_between_ticks_are_not_observed() {
+    let mut grid = two_chunks_of_air();
+    let mut reader = ManualEventReader::default();
+
+    grid.app_mut().insert_resource(EmitCellChanged::<SandCell>::new(|_, _| true));
+    step(&mut grid, &mut reader);
+
+    grid.set(IVec2::new(3, 3), SandCell::Bedrock).unwrap();
+
+    assert!(step(&mut grid, &mut reader).is_empty());
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, simulation::{PowderkegErrors, SimulationError}, stain::Stainable, testing::TestGrid, PowderkegError};
+use thiserror::Error;
+
+mod common;
+
+use common::CHUNK_SIZE;
+
+/// Reactors next to each other melt down, turning to air and raising an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ReactorCell {
+    Reactor,
+    Sand,
+    #[default]
+    Air,
+}
+
+#[derive(Debug, Error)]
+enum ReactorError {
+    #[error("reactors placed next to each other melted down")]
+    Meltdown,
+}
+
+impl Cell for ReactorCell {
+    type Error = ReactorError;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        match input.this() {
+            ReactorCell::Reactor => {
+                if input.grid.map_cell(input.origin + IVec2::X, |cell| *cell == ReactorCell::Reactor).unwrap_or(false)
+                    || input.grid.map_cell(input.origin + IVec2::NEG_X, |cell| *cell == ReactorCell::Reactor).unwrap_or(false)
+                {
+                    input.grid.replace(input.origin, ReactorCell::Air)?;
+
+                    return Err(PowderkegError::Cell(ReactorError::Meltdown));
+                }
+
+                Ok(TickSuccess::Stable)
+            },
+            ReactorCell::Sand => {
+                let below = input.origin + IVec2::NEG_Y;
+
+                if input.grid.map_cell(below, |cell| *cell == ReactorCell::Air).unwrap_or(false) {
+                    input.grid.swap(input.origin, below)?;
+                    input.grid.stain_around(input.origin, 1);
+
+                    return Ok(TickSuccess::Unstable);
+                }
+
+                Ok(TickSuccess::Stable)
+            },
+            ReactorCell::Air => Ok(TickSuccess::Stable),
+        }
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-1, -1, 1, 0)
+    }
+}
+
+impl Renderable for ReactorCell {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            ReactorCell::Reactor => Color::LIME_GREEN,
+            ReactorCell::Sand => Color::BEIGE,
+            ReactorCell::Air => Color::BLACK,
+        }
+    }
+}
+
+fn meltdowns(errors: &[SimulationError<ReactorCell>]) -> Vec<IVec2> {
+    let mut points: Vec<_> = errors
+        .iter()
+        .filter(|error| matches!(error.error, PowderkegError::Cell(ReactorError::Meltdown)))
+        .map(|error| error.point)
+        .collect();
+
+    points.sort_by_key(|point| (point.y, point.x));
+
+    points
+}
+
+#[test]
+fn cell_errors_reach_the_resource_without_halting_the_simulation() {
+    let mut grid = TestGrid::<ReactorCell, CHUNK_SIZE>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(ReactorCell::Air, ()).without_initial_stain());
+
+    grid.set(IVec2::new(4, 4), ReactorCell::Reactor).unwrap();
+    grid.set(IVec2::new(5, 4), ReactorCell::Reactor).unwrap();
+    grid.set(IVec2::new(10, 10), ReactorCell::Sand).unwrap();
+
+    let errors = meltdowns(grid.step());
+
+    // Whichever reactor ticks first melts down, leaving the other without a neighbor.
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0] == IVec2::new(4, 4) || errors[0] == IVec2::new(5, 4));
+    assert_eq!(*grid.get(errors[0]).unwrap(), ReactorCell::Air);
+
+    let resource = &grid.app_mut().world.resource::<PowderkegErrors<ReactorCell>>().errors;
+
+    assert_eq!(meltdowns(resource), errors);
+
+    // The sand ticked in the same tick as the error.
+    assert_eq!(*grid.get(IVec2::new(10, 9)).unwrap(), ReactorCell::Sand);
+
+    // Later ticks run on and replace the errors of the last.
+    for _ in 0..3 {
+        assert!(grid.step().is_empty());
+    }
+
+    assert_eq!(grid.tick(), 4);
+    assert_eq!(*grid.get(IVec2::new(10, 6)).unwrap(), ReactorCell::Sand);
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+use powderkeg::{chunk::Chunk, grid::Grid, stain::ChangeKind, testing::TestGrid};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+fn classify(old: &SandCell, new: &SandCell) -> Option<ChangeKind> {
+    match (old, new) {
+        (SandCell::Air, SandCell::Sand) => Some(ChangeKind::Created),
+        (SandCell::Sand, SandCell::Air) => Some(ChangeKind::Removed),
+        _ => None,
+    }
+}
+
+#[test]
+fn every_kind_of_write_is_logged_once() {
+    let mut chunk = Chunk::<SandCell, 8>::full_copied(SandCell::Air, ());
+
+    chunk.track_changes(classify);
+
+    chunk.replace(IVec2::new(1, 1), SandCell::Sand).unwrap();
+    chunk.replace(IVec2::new(2, 2), SandCell::Air).unwrap();
+    chunk.swap(IVec2::new(1, 1), IVec2::new(1, 0)).unwrap();
+    *chunk.get_mut(IVec2::new(3, 3)).unwrap() = SandCell::Sand;
+
+    assert_eq!(chunk.take_changes(), vec![
+        (IVec2::new(1, 1), ChangeKind::Created),
+        (IVec2::new(1, 1), ChangeKind::Moved),
+        (IVec2::new(1, 0), ChangeKind::Moved),
+        (IVec2::new(3, 3), ChangeKind::Modified),
+    ]);
+
+    assert!(chunk.changes().is_empty());
+}
+
+#[test]
+fn swaps_across_chunks_are_logged_in_both() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    for coords in [IVec2::ZERO, IVec2::NEG_Y] {
+        let mut chunk = Chunk::full_copied(SandCell::Air, ());
+
+        chunk.track_changes(classify);
+        grid.insert_chunk(coords, chunk);
+    }
+
+    grid.set(IVec2::new(9, 0), SandCell::Sand).unwrap();
+
+    assert!(grid.step().is_empty());
+
+    assert_eq!(grid.chunk_mut(IVec2::ZERO).unwrap().take_changes(), vec![
+        (IVec2::new(9, 0), ChangeKind::Created),
+        (IVec2::new(9, 0), ChangeKind::Moved),
+    ]);
+    assert_eq!(grid.chunk_mut(IVec2::NEG_Y).unwrap().take_changes(), vec![(IVec2::new(9, CHUNK_SIZE - 1), ChangeKind::Moved)]);
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use powderkeg::{chunk::Chunk, grid::{ClampedGrid, Grid}, stain::{StainPolicy, Stainable}, PowderkegError};
+
+mod common;
+
+use common::SandCell;
+
+fn chunk() -> Chunk<SandCell, 8> {
+    let mut chunk = Chunk::full_copied(SandCell::Air, ());
+
+    chunk.clear_stain();
+    chunk
+}
+
+#[test]
+fn clearing_the_stain_leaves_the_stain_outside_the_bounds() {
+    let mut chunk = chunk().with_stain_policy(StainPolicy::Precise);
+
+    chunk.stain_point(IVec2::new(1, 1));
+    chunk.stain_point(IVec2::new(6, 6));
+
+    ClampedGrid::new(&mut chunk, IRect::new(0, 0, 3, 3)).clear_stain();
+
+    assert!(!chunk.is_stained(IVec2::new(1, 1)));
+    assert!(chunk.is_stained(IVec2::new(6, 6)));
+}
+
+#[test]
+fn reads_outside_the_bounds_return_the_nearest_edge_cell() {
+    let mut chunk = chunk();
+
+    chunk.replace(IVec2::new(3, 1), SandCell::Sand).unwrap();
+    chunk.replace(IVec2::new(3, 3), SandCell::Bedrock).unwrap();
+    // Outside the bounds, never read through them.
+    chunk.replace(IVec2::new(4, 1), SandCell::Bedrock).unwrap();
+
+    let clamped = ClampedGrid::new(&mut chunk, IRect::new(0, 0, 3, 3));
+
+    assert_eq!(clamped.get(IVec2::new(4, 1)).ok(), Some(&SandCell::Sand));
+    assert_eq!(clamped.get(IVec2::new(100, 1)).ok(), Some(&SandCell::Sand));
+    // Past a corner reads the corner.
+    assert_eq!(clamped.get(IVec2::new(6, 5)).ok(), Some(&SandCell::Bedrock));
+    assert_eq!(clamped.get(IVec2::new(-1, -1)).ok(), Some(&SandCell::Air));
+    assert!(clamped.get_state(IVec2::new(-5, 2)).is_ok());
+    assert_eq!(clamped.clamp(IVec2::new(6, -2)), IVec2::new(3, 0));
+}
+
+#[test]
+fn writes_outside_the_bounds_fail_and_leave_the_grid_untouched() {
+    let mut chunk = chunk();
+    let mut clamped = ClampedGrid::new(&mut chunk, IRect::new(0, 0, 3, 3));
+
+    let outside = IVec2::new(5, 5);
+
+    assert!(matches!(clamped.replace(outside, SandCell::Sand), Err(PowderkegError::LocalOutOfBounds(point)) if point == outside));
+    assert!(matches!(clamped.replace_if(outside, SandCell::Sand, |_| true), Err(PowderkegError::LocalOutOfBounds(_))));
+    assert!(matches!(clamped.swap(IVec2::new(3, 3), IVec2::new(4, 3)), Err(PowderkegError::LocalOutOfBounds(point)) if point == IVec2::new(4, 3)));
+    assert!(matches!(clamped.swap_with_state(IVec2::new(4, 3), IVec2::new(3, 3)), Err(PowderkegError::LocalOutOfBounds(_))));
+    assert!(clamped.get_mut(outside).is_err());
+
+    // Painting across the edge writes only the part within the bounds.
+    assert_eq!(clamped.paint_rect(IRect::new(2, 0, 6, 0), SandCell::Bedrock, |_| true), 2);
+
+    assert_eq!(clamped.replace(IVec2::new(3, 3), SandCell::Sand).ok(), Some(SandCell::Air));
+
+    assert_eq!(chunk.get(outside).ok(), Some(&SandCell::Air));
+    assert_eq!(chunk.get(IVec2::new(3, 3)).ok(), Some(&SandCell::Sand));
+    assert_eq!(chunk.get(IVec2::new(4, 3)).ok(), Some(&SandCell::Air));
+    assert_eq!(chunk.get(IVec2::new(4, 0)).ok(), Some(&SandCell::Air));
+    assert_eq!(chunk.get(IVec2::new(3, 0)).ok(), Some(&SandCell::Bedrock));
+    assert!(!chunk.is_stained(outside));
+    assert!(!chunk.is_stained(IVec2::new(4, 0)));
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use powderkeg::{chunk::Chunk, simulation::{MaxTicksPerFrame, PowderkegClock, PowderkegPaused, PowderkegTickRate}, stain::Stainable, testing::TestGrid};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+fn clocked_grid() -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::default());
+    grid.app_mut()
+        .insert_resource(PowderkegPaused(false))
+        .insert_resource(PowderkegTickRate(8.0))
+        .insert_resource(MaxTicksPerFrame(16))
+        .init_resource::<PowderkegClock>();
+
+    grid
+}
+
+fn advance(grid: &mut TestGrid<SandCell, CHUNK_SIZE>, seconds: f32) {
+    grid.app_mut().world.resource_mut::<PowderkegClock>().advance(seconds);
+    grid.app_mut().update();
+}
+
+#[test]
+fn clock_runs_an_exact_number_of_ticks() {
+    let mut grid = clocked_grid();
+
+    grid.app_mut().update();
+
+    assert_eq!(grid.tick(), 0);
+
+    advance(&mut grid, 0.375);
+
+    assert_eq!(grid.tick(), 3);
+
+    // Partial ticks carry over to the next frame.
+    advance(&mut grid, 0.0625);
+    advance(&mut grid, 0.0625);
+
+    assert_eq!(grid.tick(), 4);
+}
+
+#[test]
+fn clock_is_read_rather_than_drained() {
+    let mut grid = clocked_grid();
+
+    advance(&mut grid, 0.25);
+
+    assert_eq!(grid.tick(), 2);
+    assert_eq!(grid.app_mut().world.resource::<PowderkegClock>().elapsed(), 0.25);
+}
+
+#[test]
+fn time_advanced_while_paused_runs_on_resume() {
+    let mut grid = clocked_grid();
+    let sand = IVec2::new(4, 10);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::default().without_initial_stain());
+    grid.app_mut().insert_resource(PowderkegPaused(true));
+    grid.set(sand, SandCell::Sand).unwrap();
+
+    advance(&mut grid, 0.25);
+
+    // Painted while paused, the sand is stained but does not fall until the simulation resumes.
+    assert_eq!(grid.tick(), 0);
+    assert!(grid.chunk(IVec2::ZERO).unwrap().is_stained(sand));
+    assert_eq!(grid.get(sand), Some(&SandCell::Sand));
+
+    grid.app_mut().insert_resource(PowderkegPaused(false));
+    grid.app_mut().update();
+
+    assert_eq!(grid.tick(), 2);
+    assert_eq!(grid.get(sand), Some(&SandCell::Air));
+    assert_eq!(grid.get(sand - IVec2::new(0, 2)), Some(&SandCell::Sand));
+}
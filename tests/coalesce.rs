@@ -0,0 +1,54 @@
+use bevy::{math::{IRect, IVec2}, utils::HashSet};
+use powderkeg::area::Area;
+
+fn covered(area: &Area) -> HashSet<IVec2> {
+    area.points().collect()
+}
+
+#[test]
+fn overlapping_boxes_collapse_to_their_union() {
+    let mut area = Area::Many(vec![
+        IRect::new(0, 0, 3, 3),
+        IRect::new(2, 0, 5, 3),
+        IRect::new(0, 2, 3, 5),
+        IRect::new(2, 2, 5, 5),
+        IRect::new(1, 1, 4, 4),
+    ]);
+
+    area.coalesce();
+
+    assert_eq!(area.rects(), &[IRect::new(0, 0, 5, 5)]);
+}
+
+#[test]
+fn coalescing_covers_exactly_the_stained_cells_once() {
+    // An L of stains around a corner, whose bounding rect would take in cells never stained.
+    let rects = vec![
+        IRect::new(0, 0, 2, 2),
+        IRect::new(1, 1, 3, 3),
+        IRect::new(2, 2, 9, 3),
+        IRect::new(2, 2, 3, 9),
+        IRect::new(3, 8, 3, 8),
+    ];
+
+    let mut area = Area::Many(rects.clone());
+    let before = covered(&Area::Many(rects));
+
+    area.coalesce();
+
+    assert_eq!(covered(&area), before);
+    assert_eq!(area.points().count(), before.len());
+    assert!(!area.contains(IVec2::new(8, 8)));
+}
+
+#[test]
+fn edge_adjacent_rects_merge_and_corner_touching_ones_do_not() {
+    let mut side_by_side = Area::Many(vec![IRect::new(0, 0, 1, 3), IRect::new(2, 0, 4, 3)]);
+    let mut corners = Area::Many(vec![IRect::new(0, 0, 1, 1), IRect::new(2, 2, 3, 3)]);
+
+    side_by_side.coalesce();
+    corners.coalesce();
+
+    assert_eq!(side_by_side.rects(), &[IRect::new(0, 0, 4, 3)]);
+    assert_eq!(corners.rects().len(), 2);
+}
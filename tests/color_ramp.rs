@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use powderkeg::viewer::ColorRamp;
+
+fn assert_close(a: [f32; 4], b: [f32; 4]) {
+    assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4), "{a:?} != {b:?}");
+}
+
+#[test]
+fn ramp_interpolates_in_linear_space() {
+    let ramp = ColorRamp::new(vec![(1.0, Color::WHITE), (0.0, Color::BLACK)]);
+
+    assert_close(ramp.sample(0.5).as_linear_rgba_f32(), [0.5, 0.5, 0.5, 1.0]);
+    assert_close(ramp.sample(0.25).as_linear_rgba_f32(), [0.25, 0.25, 0.25, 1.0]);
+
+    // Half the light is well over half the sRGB value.
+    assert!(ramp.sample(0.5).r() > 0.7);
+}
+
+#[test]
+fn ramp_clamps_to_its_end_stops() {
+    let ramp = ColorRamp::HEAT;
+
+    assert_eq!(ramp.sample(-1.0), Color::rgb(0.0, 0.0, 1.0));
+    assert_eq!(ramp.sample(0.5).as_linear_rgba_f32(), Color::rgb(1.0, 1.0, 0.0).as_linear_rgba_f32());
+    assert_eq!(ramp.sample(2.0), Color::rgb(1.0, 0.0, 0.0));
+}
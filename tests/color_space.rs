@@ -0,0 +1,79 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, stain::Stainable, PowderkegError};
+
+/// Colors given every way a cell can give them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Swatch {
+    /// A dark sRGB color, which a linear eight bit store would round to black.
+    #[default]
+    DarkSrgb,
+    Linear,
+    /// Overrides [`Renderable::to_linear_rgba`] rather than giving a [`Color`].
+    Raw,
+}
+
+const RAW: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+
+impl Cell for Swatch {
+    type State = ();
+    type Error = Infallible;
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+impl Renderable for Swatch {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            Swatch::DarkSrgb => Color::rgb(0.02, 0.04, 0.5),
+            Swatch::Linear => Color::rgba_linear(0.2, 0.4, 0.6, 1.0),
+            Swatch::Raw => Color::WHITE,
+        }
+    }
+
+    fn to_linear_rgba(&self, point: IVec2, state: &()) -> [f32; 4] {
+        match self {
+            Swatch::Raw => RAW,
+            _ => self.to_color_with_state(point, state).as_linear_rgba_f32(),
+        }
+    }
+}
+
+/// The linear color the GPU samples from the texel of the cell at `x` on the bottom row.
+fn sampled(chunk: &Chunk<Swatch, 4>, x: u32) -> [f32; 4] {
+    let [r, g, b, a] = chunk.to_image().get_pixel(x, 3).0.map(|byte| byte as f32 / u8::MAX as f32);
+
+    Color::rgba(r, g, b, a).as_linear_rgba_f32()
+}
+
+fn assert_close(actual: [f32; 4], expected: [f32; 4]) {
+    for (actual, expected) in actual.into_iter().zip(expected) {
+        // Rounding to the nearest sRGB byte moves a color at most this far in linear space.
+        assert!((actual - expected).abs() < 0.005, "sampled {actual}, specified {expected}");
+    }
+}
+
+#[test]
+fn known_colors_render_with_no_gamma_shift() {
+    let cells = [Swatch::DarkSrgb, Swatch::Linear, Swatch::Raw, Swatch::DarkSrgb].into_iter().cycle().take(16).collect();
+    let chunk = Chunk::<Swatch, 4>::new(cells, ());
+
+    assert_close(sampled(&chunk, 0), Color::rgb(0.02, 0.04, 0.5).as_linear_rgba_f32());
+    assert_close(sampled(&chunk, 1), [0.2, 0.4, 0.6, 1.0]);
+    assert_close(sampled(&chunk, 2), RAW);
+}
+
+#[test]
+fn dark_colors_keep_their_bytes() {
+    let chunk = Chunk::<Swatch, 4>::full_copied(Swatch::DarkSrgb, ());
+
+    // 0.02 and 0.04 of 255, rounded.
+    assert_eq!(chunk.to_image().get_pixel(0, 0).0, [5, 10, 128, 255]);
+}
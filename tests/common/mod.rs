@@ -0,0 +1,98 @@
+#![allow(dead_code)]
+
+use std::{convert::Infallible, sync::{Arc, Mutex}};
+
+use bevy::{gizmos::GizmoPlugin, prelude::*};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkBundle, ChunkCoords}, simulation::PowderkegPaused, stain::Stainable, viewer::RenderHook, PowderkegError, PowderkegPlugin};
+use serde::{Deserialize, Serialize};
+
+pub const CHUNK_SIZE: i32 = 16;
+
+/// Sand that falls straight down into air.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SandCell {
+    Sand,
+    #[default]
+    Air,
+    /// Never ticks, see [`Cell::is_inert`].
+    Bedrock,
+}
+
+impl Cell for SandCell {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() != SandCell::Sand {
+            return Ok(TickSuccess::Stable);
+        }
+
+        let below = input.origin + IVec2::NEG_Y;
+
+        if input.grid.get(below).is_ok_and(|cell| *cell == SandCell::Air) {
+            input.grid.swap(input.origin, below)?;
+            input.grid.stain_around(input.origin, 1);
+
+            return Ok(TickSuccess::Unstable);
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, -1, 0, 0)
+    }
+
+    fn is_inert(&self) -> bool {
+        *self == SandCell::Bedrock
+    }
+
+    fn variant(&self) -> Option<usize> {
+        Some(*self as usize)
+    }
+}
+
+impl Renderable for SandCell {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            SandCell::Sand => Color::BEIGE,
+            SandCell::Air => Color::BLACK,
+            SandCell::Bedrock => Color::DARK_GRAY,
+        }
+    }
+}
+
+/// An app drawing the chunk images of `T` cells without a GPU, paused so stains stay until a tick is asked for.
+pub fn drawing_app<T: Renderable>() -> App {
+    let mut app = App::new();
+
+    app
+        .add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Shader>()
+        .init_asset::<Image>()
+        .init_asset::<Mesh>()
+        .add_plugins(GizmoPlugin)
+        .add_plugins(PowderkegPlugin::<T, CHUNK_SIZE>::default().seed(0))
+        .insert_resource(PowderkegPaused(true));
+
+    app
+}
+
+/// Spawns `chunk` into a [`drawing_app`], always visible as there is no camera to see it.
+pub fn spawn_drawn_chunk<T: Renderable>(app: &mut App, coords: IVec2, chunk: Chunk<T, CHUNK_SIZE>) -> Entity {
+    let entity = app.world.spawn(ChunkBundle::new(chunk, ChunkCoords(coords))).id();
+
+    app.world.get_mut::<ViewVisibility>(entity).unwrap().set();
+
+    entity
+}
+
+/// Records every point the [`RenderHook`] of a [`drawing_app`] is called with.
+pub fn record_redraws<T: Renderable>(app: &mut App) -> Arc<Mutex<Vec<IVec2>>> {
+    let redrawn = Arc::new(Mutex::new(Vec::new()));
+    let recorded = redrawn.clone();
+
+    app.insert_resource(RenderHook::<T>::new(move |point, _| recorded.lock().unwrap().push(point)));
+
+    redrawn
+}
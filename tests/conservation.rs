@@ -0,0 +1,50 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, simulation::ConservationCheck, testing::TestGrid};
+
+const CHUNKS: [IVec2; 4] = [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE];
+
+fn count_sand(grid: &TestGrid<SandCell, CHUNK_SIZE>) -> usize {
+    CHUNKS
+        .iter()
+        .map(|coords| grid.chunk(*coords).unwrap().iter().filter(|(_, cell)| **cell == SandCell::Sand).count())
+        .sum()
+}
+
+#[test]
+fn falling_sand_is_conserved_across_chunk_seams() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(7);
+
+    for coords in CHUNKS {
+        grid.insert_chunk(coords, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+    }
+
+    // Every third cell of the top half of the upper chunks, so sand falls over both the horizontal and vertical seams.
+    let mut painted = 0;
+
+    for y in CHUNK_SIZE + CHUNK_SIZE / 2..CHUNK_SIZE * 2 {
+        for x in 0..CHUNK_SIZE * 2 {
+            if (x + y) % 3 == 0 {
+                grid.set(IVec2::new(x, y), SandCell::Sand).unwrap();
+                painted += 1;
+            }
+        }
+    }
+
+    assert_eq!(count_sand(&grid), painted);
+
+    grid.app_mut().insert_resource(ConservationCheck::new(|cell: &SandCell| *cell == SandCell::Sand));
+
+    for _ in 0..CHUNK_SIZE * 3 {
+        assert!(grid.step().is_empty());
+    }
+
+    assert_eq!(grid.app_mut().world.resource::<ConservationCheck<SandCell>>().violations, 0);
+    assert_eq!(count_sand(&grid), painted);
+
+    // It all settled on the bottom edge of the lower chunks.
+    assert!(grid.chunk(IVec2::Y).unwrap().iter().all(|(_, cell)| *cell == SandCell::Air));
+    assert!(grid.chunk(IVec2::ONE).unwrap().iter().all(|(_, cell)| *cell == SandCell::Air));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, grid::Grid};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+fn random_cell(rng: &mut SmallRng) -> SandCell {
+    match rng.gen_range(0..3) {
+        0 => SandCell::Sand,
+        1 => SandCell::Air,
+        _ => SandCell::Bedrock,
+    }
+}
+
+fn random_region(rng: &mut SmallRng) -> IRect {
+    let mut corner = || IVec2::new(rng.gen_range(-8..CHUNK_SIZE + 8), rng.gen_range(-8..CHUNK_SIZE + 8));
+    let (a, b) = (corner(), corner());
+
+    IRect::from_corners(a, b)
+}
+
+/// Counts through [`Grid::get`] like the default implementation, which [`OwnedGrid`](powderkeg::grid::OwnedGrid)
+/// keeps.
+fn assert_matches_default(chunk: &Chunk<SandCell, CHUNK_SIZE>, rng: &mut SmallRng) {
+    let owned = chunk.snapshot();
+
+    for _ in 0..200 {
+        let region = random_region(rng);
+
+        for cell in [SandCell::Sand, SandCell::Air, SandCell::Bedrock] {
+            assert_eq!(
+                chunk.count_matching(region, |other| *other == cell),
+                owned.count_matching(region, |other| *other == cell),
+                "{cell:?} in {region:?}",
+            );
+        }
+    }
+}
+
+#[test]
+fn fast_path_matches_the_default_on_a_random_chunk() {
+    let mut rng = SmallRng::seed_from_u64(5);
+    let cells = (0..Chunk::<SandCell, CHUNK_SIZE>::volume()).map(|_| random_cell(&mut rng)).collect();
+
+    assert_matches_default(&Chunk::new(cells, ()), &mut rng);
+}
+
+#[test]
+fn fast_path_matches_the_default_on_a_random_sparse_chunk() {
+    let mut rng = SmallRng::seed_from_u64(6);
+    let cells = (0..Chunk::<SandCell, CHUNK_SIZE>::volume())
+        .map(|_| rng.gen_bool(0.7).then(|| random_cell(&mut rng)))
+        .collect();
+
+    assert_matches_default(&Chunk::sparse(cells, ()), &mut rng);
+}
@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+use powderkeg::chunk::decompose_region;
+
+const N: i32 = 8;
+
+#[test]
+fn rect_across_the_origin_splits_into_four_chunks() {
+    let parts: Vec<_> = decompose_region::<N>(IRect::new(-3, -2, 4, 5)).collect();
+
+    assert_eq!(parts, vec![
+        (IVec2::new(-1, -1), IRect::new(5, 6, 7, 7)),
+        (IVec2::new(0, -1), IRect::new(0, 6, 4, 7)),
+        (IVec2::new(-1, 0), IRect::new(5, 0, 7, 5)),
+        (IVec2::new(0, 0), IRect::new(0, 0, 4, 5)),
+    ]);
+}
+
+#[test]
+fn rect_within_a_negative_chunk_stays_whole() {
+    let parts: Vec<_> = decompose_region::<N>(IRect::new(-16, -9, -9, -9)).collect();
+
+    assert_eq!(parts, vec![(IVec2::new(-2, -2), IRect::new(0, 7, 7, 7))]);
+}
+
+#[test]
+fn local_rects_cover_every_point_once() {
+    let rect = IRect::new(-11, -5, 13, 20);
+    let mut covered = 0;
+
+    for (coords, local) in decompose_region::<N>(rect) {
+        assert!(local.min.cmpge(IVec2::ZERO).all() && local.max.cmplt(IVec2::splat(N)).all());
+
+        let world = IRect { min: local.min + N * coords, max: local.max + N * coords };
+
+        assert!(world.min.cmpge(rect.min).all() && world.max.cmple(rect.max).all());
+
+        covered += (local.width() + 1) * (local.height() + 1);
+    }
+
+    assert_eq!(covered, (rect.width() + 1) * (rect.height() + 1));
+}
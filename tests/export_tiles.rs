@@ -0,0 +1,72 @@
+mod common;
+
+use bevy::prelude::*;
+use common::SandCell;
+use image::{Rgba, RgbaImage};
+use powderkeg::{chunk::{Chunk, ChunkCoords}, world::export_world_tiles};
+
+const SIZE: i32 = 4;
+const TILE: u32 = 6;
+
+/// A pattern of every cell, with the cell at `hole` left absent.
+fn patterned(hole: Option<IVec2>) -> Chunk<SandCell, SIZE> {
+    let cells = (0..SIZE * SIZE)
+        .map(|i| {
+            let local = IVec2::new(i % SIZE, i / SIZE);
+
+            (Some(local) != hole).then_some(match (local.x + 2 * local.y) % 3 {
+                0 => SandCell::Sand,
+                1 => SandCell::Air,
+                _ => SandCell::Bedrock,
+            })
+        })
+        .collect();
+
+    Chunk::sparse(cells, ())
+}
+
+#[test]
+fn tiles_of_a_sparse_world_reassemble_into_its_image() {
+    // Two chunks on a diagonal, the other two of the 2x2 square are missing. Tiles are not aligned to chunks.
+    let chunks = [
+        (ChunkCoords::<SIZE>(IVec2::ZERO), patterned(None)),
+        (ChunkCoords::<SIZE>(IVec2::ONE), patterned(Some(IVec2::new(1, 2)))),
+    ];
+
+    let tiles = export_world_tiles(chunks.iter().map(|(coords, chunk)| (coords, chunk)), TILE);
+
+    assert_eq!(tiles.iter().map(|(tile, _)| *tile).collect::<Vec<_>>(), [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE]);
+
+    // The tiles side by side, top row first.
+    let extent = 2 * TILE;
+    let mut assembled = RgbaImage::new(extent, extent);
+
+    for (tile, image) in tiles.iter() {
+        assert_eq!(image.dimensions(), (TILE, TILE));
+
+        for (x, row, pixel) in image.enumerate_pixels() {
+            assembled.put_pixel(tile.x as u32 * TILE + x, extent - (tile.y as u32 + 1) * TILE + row, *pixel);
+        }
+    }
+
+    let images: Vec<_> = chunks.iter().map(|(coords, chunk)| (coords.0, chunk.to_image())).collect();
+
+    let expected = RgbaImage::from_fn(extent, extent, |x, row| {
+        let world = IVec2::new(x as i32, extent as i32 - 1 - row as i32);
+        let (coords, local) = ChunkCoords::<SIZE>::world_to_chunk_and_local(world);
+
+        images
+            .iter()
+            .find(|(chunk, _)| *chunk == coords)
+            .map_or(Rgba([0; 4]), |(_, image)| *image.get_pixel(local.x as u32, (SIZE - 1 - local.y) as u32))
+    });
+
+    assert_eq!(assembled, expected);
+
+    // The missing chunks, the absent cell and the area past the world are all transparent.
+    assert_eq!(*assembled.get_pixel(0, extent - 1 - 5), Rgba([0; 4]));
+    assert_eq!(*assembled.get_pixel(5, extent - 1 - 6), Rgba([0; 4]));
+    assert_eq!(*assembled.get_pixel(11, 0), Rgba([0; 4]));
+    assert_ne!(*assembled.get_pixel(0, extent - 1), Rgba([0; 4]));
+    assert_ne!(*assembled.get_pixel(4, extent - 1 - 4), Rgba([0; 4]));
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use image::{Rgba, RgbaImage};
+use powderkeg::{chunk::Chunk, grid::Grid, PowderkegError};
+
+fn to_cell(pixel: Rgba<u8>) -> SandCell {
+    if pixel.0[0] > 0 { SandCell::Sand } else { SandCell::Air }
+}
+
+#[test]
+fn images_of_the_wrong_size_are_an_error() {
+    let size = CHUNK_SIZE as u32;
+
+    for (width, height) in [(size - 1, size), (size, size + 1), (2 * size, 2 * size), (0, 0)] {
+        let result = Chunk::<SandCell, CHUNK_SIZE>::from_image(&RgbaImage::new(width, height), to_cell, ());
+
+        assert!(
+            matches!(result, Err(PowderkegError::ImageSize { width: w, height: h, size: CHUNK_SIZE }) if (w, h) == (width, height)),
+            "{width}x{height}",
+        );
+    }
+}
+
+#[test]
+fn the_top_row_of_the_image_is_the_top_row_of_the_chunk() {
+    let mut image = RgbaImage::new(CHUNK_SIZE as u32, CHUNK_SIZE as u32);
+
+    image.put_pixel(2, 0, Rgba([255; 4]));
+
+    let chunk = Chunk::<SandCell, CHUNK_SIZE>::from_image(&image, to_cell, ()).unwrap();
+
+    assert_eq!(*chunk.get(IVec2::new(2, CHUNK_SIZE - 1)).unwrap(), SandCell::Sand);
+    assert_eq!(chunk.iter().filter(|(_, cell)| **cell == SandCell::Sand).count(), 1);
+}
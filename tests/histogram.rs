@@ -0,0 +1,71 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, simulation::CellHistogram, testing::TestGrid};
+
+fn sand_grid() -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(7);
+
+    grid.app_mut().insert_resource(CellHistogram::<SandCell>::default());
+
+    for x in 0..2 {
+        grid.insert_chunk(IVec2::new(x, 0), Chunk::default());
+    }
+
+    for x in 0..CHUNK_SIZE * 2 {
+        grid.set(IVec2::new(x, CHUNK_SIZE - 1), SandCell::Sand).unwrap();
+    }
+
+    grid
+}
+
+fn counts(grid: &mut TestGrid<SandCell, CHUNK_SIZE>) -> Vec<usize> {
+    grid.app_mut().world.resource::<CellHistogram<SandCell>>().counts.clone()
+}
+
+#[test]
+fn histogram_counts_every_chunk() {
+    let mut grid = sand_grid();
+
+    grid.step();
+
+    let area = (CHUNK_SIZE * CHUNK_SIZE * 2) as usize;
+    let sand = (CHUNK_SIZE * 2) as usize;
+
+    assert_eq!(counts(&mut grid), vec![sand, area - sand]);
+}
+
+#[test]
+fn histogram_is_reproducible() {
+    let mut first = sand_grid();
+    let mut second = sand_grid();
+
+    for _ in 0..CHUNK_SIZE {
+        first.step();
+        second.step();
+
+        assert_eq!(counts(&mut first), counts(&mut second));
+    }
+}
+
+#[test]
+fn histogram_follows_changes_once_settled() {
+    let mut grid = sand_grid();
+
+    // Let the sand settle so the chunks stop changing and their cached counts are reused.
+    for _ in 0..CHUNK_SIZE * 2 {
+        grid.step();
+    }
+
+    let settled = counts(&mut grid);
+
+    grid.step();
+
+    assert_eq!(counts(&mut grid), settled);
+
+    grid.set(IVec2::new(CHUNK_SIZE + 3, CHUNK_SIZE - 1), SandCell::Sand).unwrap();
+    grid.step();
+
+    assert_eq!(counts(&mut grid), vec![settled[0] + 1, settled[1] - 1]);
+}
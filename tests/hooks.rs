@@ -0,0 +1,76 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, TickInput, TickSuccess}, chunk::Chunk, grid::Grid, stain::Stainable, PowderkegError};
+
+/// Keeps the number of fires in its chunk in the chunk state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Counted {
+    Fire,
+    #[default]
+    Air,
+}
+
+impl Cell for Counted {
+    type State = i32;
+    type Error = Infallible;
+
+    const HOOKS: bool = true;
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+
+    fn on_create(&self, _: IVec2, fires: &mut i32) {
+        *fires += (*self == Counted::Fire) as i32;
+    }
+
+    fn on_destroy(&self, _: IVec2, fires: &mut i32) {
+        *fires -= (*self == Counted::Fire) as i32;
+    }
+}
+
+#[test]
+fn replacing_and_filling_keep_the_count() {
+    let mut chunk = Chunk::<Counted, 4>::full_copied(Counted::Air, 0);
+
+    chunk.replace(IVec2::ZERO, Counted::Fire).unwrap();
+    chunk.replace(IVec2::ONE, Counted::Fire).unwrap();
+    chunk.replace(IVec2::ZERO, Counted::Air).unwrap();
+    chunk.fill_rect(IRect::new(2, 0, 3, 0), Counted::Fire).unwrap();
+
+    assert_eq!(*chunk.state().read(), 3);
+
+    chunk.fill(Counted::Fire);
+
+    assert_eq!(*chunk.state().read(), 16);
+
+    chunk.clear();
+
+    assert_eq!(*chunk.state().read(), 0);
+}
+
+#[test]
+fn filling_skips_the_hooks_of_absent_cells() {
+    let cells = (0..16).map(|i| (i % 4 != 0).then_some(Counted::Air)).collect();
+    let mut chunk = Chunk::<Counted, 4>::sparse(cells, 0);
+
+    chunk.fill(Counted::Fire);
+
+    assert_eq!(*chunk.state().read(), 12);
+}
+
+#[test]
+fn replacing_through_the_default_runs_the_hooks() {
+    let mut grid = Chunk::<Counted, 4>::full_copied(Counted::Air, 0).snapshot();
+
+    grid.replace(IVec2::ONE, Counted::Fire).unwrap();
+
+    assert_eq!(*grid.get_state(IVec2::ONE).unwrap().read(), 1);
+    assert!(grid.replace(IVec2::splat(4), Counted::Fire).is_err());
+    assert_eq!(*grid.get_state(IVec2::ONE).unwrap().read(), 1);
+}
@@ -0,0 +1,77 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, stain::Stainable, testing::TestGrid, PowderkegError};
+
+const N: i32 = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Loud {
+    #[default]
+    Air,
+    /// Falls straight down into air.
+    Sand,
+    /// Stains far more than the chunk it is in the first time it ticks.
+    Shout,
+    Shouted,
+}
+
+impl Cell for Loud {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        match input.this() {
+            Loud::Shout => {
+                input.grid.replace(input.origin, Loud::Shouted)?;
+                input.grid.stain(IRect::from_center_half_size(input.origin, IVec2::splat(1_000_000)));
+            },
+            Loud::Sand if input.grid.get(input.origin + IVec2::NEG_Y).is_ok_and(|cell| *cell == Loud::Air) => {
+                input.grid.swap(input.origin, input.origin + IVec2::NEG_Y)?;
+            },
+            _ => {},
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, -1, 0, 0)
+    }
+}
+
+impl Renderable for Loud {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::WHITE
+    }
+}
+
+/// A chunk of air with `cell` in the middle of its top row, left unstained so nothing ticks until something stains it.
+fn with_on_top(cell: Loud) -> Chunk<Loud, N> {
+    let mut cells = vec![Loud::Air; Chunk::<Loud, N>::volume()];
+
+    cells[(N * (N - 1) + N / 2) as usize] = cell;
+
+    Chunk::new(cells, ()).without_initial_stain()
+}
+
+#[test]
+fn huge_stain_is_clamped_to_the_neighboring_chunks() {
+    let mut grid = TestGrid::<Loud, N>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, with_on_top(Loud::Shout));
+    grid.insert_chunk(IVec2::X, with_on_top(Loud::Sand));
+    grid.insert_chunk(IVec2::new(3, 0), with_on_top(Loud::Sand));
+
+    grid.chunk_mut(IVec2::ZERO).unwrap().stain_point(IVec2::new(N / 2, N - 1));
+
+    for _ in 0..3 {
+        assert!(grid.step().is_empty());
+    }
+
+    let top = N - 1;
+
+    assert_eq!(grid.get(IVec2::new(N / 2, top)), Some(&Loud::Shouted));
+    assert_eq!(grid.get(IVec2::new(N + N / 2, top)), Some(&Loud::Air), "the stain did not spill into the neighbor");
+    assert_eq!(grid.get(IVec2::new(3 * N + N / 2, top)), Some(&Loud::Sand), "the stain reached past the neighbor");
+}
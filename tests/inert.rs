@@ -0,0 +1,49 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, simulation::ChunkSleepThreshold, stain::Stainable, testing::TestGrid};
+
+#[test]
+fn sand_landing_on_bedrock_does_not_wake_it() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(3);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Bedrock, ()).without_initial_stain());
+    grid.insert_chunk(IVec2::Y, Chunk::default());
+
+    let threshold = ChunkSleepThreshold::default().0;
+
+    for step in 0..CHUNK_SIZE * 2 {
+        grid.set(IVec2::new(step % CHUNK_SIZE, CHUNK_SIZE * 2 - 1), SandCell::Sand).unwrap();
+        grid.step();
+
+        let bedrock = grid.chunk(IVec2::ZERO).unwrap();
+
+        assert!(bedrock.stained().is_empty());
+        assert_eq!(bedrock.cells_ticked(), 0);
+
+        if step as u32 >= threshold {
+            assert!(bedrock.is_asleep(threshold));
+        }
+    }
+
+    assert_eq!(grid.get(IVec2::new(0, CHUNK_SIZE)), Some(&SandCell::Sand));
+}
+
+#[test]
+fn stain_on_bedrock_alone_lets_the_chunk_sleep() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(3);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Bedrock, ()));
+
+    let threshold = ChunkSleepThreshold::default().0;
+
+    grid.chunk_mut(IVec2::ZERO).unwrap().stain_point(IVec2::splat(CHUNK_SIZE / 2));
+
+    // The tick of the stain counts as idle too.
+    for _ in 0..threshold {
+        grid.step();
+    }
+
+    assert!(grid.chunk(IVec2::ZERO).unwrap().is_asleep(threshold));
+}
@@ -0,0 +1,82 @@
+use bevy::{prelude::*, utils::HashSet};
+use powderkeg::{chunk::{Chunk, ChunkCoords}, testing::TestGrid, PowderkegSet};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+#[derive(Resource, Default)]
+struct ChangedChunks(HashSet<IVec2>);
+
+fn record_changed(chunks: Query<&ChunkCoords<CHUNK_SIZE>, Changed<Chunk<SandCell, CHUNK_SIZE>>>, mut changed: ResMut<ChangedChunks>) {
+    changed.0 = chunks.iter().map(|coords| coords.0).collect();
+}
+
+fn grid() -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(1);
+
+    grid.app_mut()
+        .init_resource::<ChangedChunks>()
+        .add_systems(Update, record_changed.after(PowderkegSet::Tick));
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::default());
+    grid.insert_chunk(IVec2::X, Chunk::default());
+
+    grid
+}
+
+fn changed(grid: &mut TestGrid<SandCell, CHUNK_SIZE>) -> HashSet<IVec2> {
+    grid.app_mut().world.resource::<ChangedChunks>().0.clone()
+}
+
+#[test]
+fn last_modified_advances_on_write_and_stays_put_when_idle() {
+    let mut grid = grid();
+
+    grid.step();
+    grid.set(IVec2::new(2, 5), SandCell::Sand).unwrap();
+
+    // Written between ticks, counting towards the tick before.
+    assert_eq!(grid.chunk(IVec2::ZERO).unwrap().last_modified(), 1);
+
+    for _ in 0..3 {
+        grid.step();
+    }
+
+    assert_eq!(grid.chunk(IVec2::ZERO).unwrap().last_modified(), 4);
+    assert_eq!(grid.chunk(IVec2::X).unwrap().last_modified(), 0);
+
+    // Once the sand has landed nothing writes to the chunk.
+    for _ in 0..CHUNK_SIZE {
+        grid.step();
+    }
+
+    let settled = grid.chunk(IVec2::ZERO).unwrap().last_modified();
+
+    assert!(settled < grid.tick());
+    assert_eq!(grid.chunk(IVec2::ZERO).unwrap().last_modified(), settled);
+}
+
+#[test]
+fn only_written_chunks_are_changed() {
+    let mut grid = grid();
+
+    // Newly inserted chunks are changed, after that a tick of nothing but air writes to neither.
+    grid.step();
+
+    assert_eq!(changed(&mut grid).len(), 2);
+
+    grid.step();
+
+    assert!(changed(&mut grid).is_empty());
+
+    grid.set(IVec2::new(CHUNK_SIZE + 2, 5), SandCell::Sand).unwrap();
+    grid.step();
+
+    assert_eq!(changed(&mut grid), HashSet::from_iter([IVec2::X]));
+
+    // Falling on its own the sand changes its chunk and nothing else.
+    grid.step();
+
+    assert_eq!(changed(&mut grid), HashSet::from_iter([IVec2::X]));
+}
@@ -0,0 +1,154 @@
+use std::convert::Infallible;
+
+use bevy::{prelude::*, utils::HashMap};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkCoords}, lighting::{LightMap, LightSettings, Luminous, PowderkegLightingPlugin}, stain::{ChangeKind, Stainable}, testing::TestGrid, PowderkegError};
+
+const N: i32 = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Lit {
+    Glow,
+    Wall,
+    #[default]
+    Air,
+}
+
+impl Cell for Lit {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+impl Renderable for Lit {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::WHITE
+    }
+}
+
+impl Luminous for Lit {
+    fn emission(&self) -> u8 {
+        match self {
+            Lit::Glow => u8::MAX,
+            _ => 0,
+        }
+    }
+
+    fn is_opaque(&self) -> bool {
+        *self == Lit::Wall
+    }
+}
+
+fn classify(old: &Lit, new: &Lit) -> Option<ChangeKind> {
+    (old != new).then_some(ChangeKind::Created)
+}
+
+fn lit_grid(cells: &[(IVec2, Lit)]) -> TestGrid<Lit, N> {
+    let mut grid = TestGrid::<Lit, N>::new(0);
+
+    grid.app_mut().add_plugins(PowderkegLightingPlugin::<Lit, N>::default());
+
+    for coords in [IVec2::ZERO, IVec2::X] {
+        let mut chunk = Chunk::full_copied(Lit::Air, ());
+
+        chunk.track_changes(classify);
+        grid.insert_chunk(coords, chunk);
+    }
+
+    for (point, cell) in cells {
+        grid.set(*point, *cell).unwrap();
+    }
+
+    grid.step();
+    grid
+}
+
+fn levels(grid: &mut TestGrid<Lit, N>) -> HashMap<IVec2, u8> {
+    let world = &mut grid.app_mut().world;
+    let mut levels = HashMap::default();
+
+    for (coords, light) in world.query::<(&ChunkCoords<N>, &LightMap<N>)>().iter(world) {
+        for y in 0..N {
+            for x in 0..N {
+                levels.insert(coords.local_to_world(IVec2::new(x, y)), light.level(IVec2::new(x, y)));
+            }
+        }
+    }
+
+    levels
+}
+
+#[test]
+fn light_falls_off_with_distance_and_stops_at_walls() {
+    let mut grid = lit_grid(&[(IVec2::new(4, 8), Lit::Glow), (IVec2::new(6, 8), Lit::Wall)]);
+    let levels = levels(&mut grid);
+    let falloff = LightSettings::default().falloff;
+
+    assert_eq!(levels[&IVec2::new(4, 8)], u8::MAX);
+    assert_eq!(levels[&IVec2::new(3, 8)], u8::MAX - falloff);
+    assert_eq!(levels[&IVec2::new(2, 8)], u8::MAX - 2 * falloff);
+
+    // The wall is lit but passes nothing on, light reaches behind it only the long way round.
+    assert_eq!(levels[&IVec2::new(6, 8)], u8::MAX - 2 * falloff);
+    assert_eq!(levels[&IVec2::new(7, 8)], u8::MAX - 5 * falloff);
+}
+
+#[test]
+fn moving_light_across_chunks_matches_lighting_from_scratch() {
+    let mut moved = lit_grid(&[(IVec2::new(N - 2, 8), Lit::Glow)]);
+
+    moved.set(IVec2::new(N - 2, 8), Lit::Air).unwrap();
+    moved.set(IVec2::new(N + 3, 5), Lit::Glow).unwrap();
+    moved.step();
+
+    let mut fresh = lit_grid(&[(IVec2::new(N + 3, 5), Lit::Glow)]);
+
+    assert_eq!(levels(&mut moved), levels(&mut fresh));
+}
+
+#[test]
+fn changing_settings_relights_every_chunk() {
+    let mut grid = lit_grid(&[(IVec2::new(4, 8), Lit::Glow)]);
+
+    grid.app_mut().insert_resource(LightSettings { falloff: 64, ambient: 0.1 });
+    grid.step();
+
+    let levels = levels(&mut grid);
+
+    assert_eq!(levels[&IVec2::new(5, 8)], u8::MAX - 64);
+    assert_eq!(levels[&IVec2::new(9, 8)], 0);
+}
+
+#[derive(Resource, Default)]
+struct ChangedMaps(usize);
+
+fn count_changed(maps: Query<(), Changed<LightMap<N>>>, mut changed: ResMut<ChangedMaps>) {
+    changed.0 = maps.iter().count();
+}
+
+#[test]
+fn light_maps_are_only_changed_when_the_light_is() {
+    let mut grid = lit_grid(&[(IVec2::new(4, 8), Lit::Glow)]);
+
+    // A steep falloff keeps the second light from reaching back into the first chunk.
+    grid.app_mut()
+        .insert_resource(LightSettings { falloff: 64, ambient: 0.1 })
+        .init_resource::<ChangedMaps>()
+        .add_systems(Last, count_changed);
+
+    grid.step();
+    grid.step();
+
+    assert_eq!(grid.app_mut().world.resource::<ChangedMaps>().0, 0);
+
+    grid.set(IVec2::new(N + 8, 8), Lit::Glow).unwrap();
+    grid.step();
+
+    assert_eq!(grid.app_mut().world.resource::<ChangedMaps>().0, 1);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::{drawing_app, record_redraws, spawn_drawn_chunk, SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, stain::Stainable};
+
+fn all_points() -> HashSet<IVec2> {
+    (0..CHUNK_SIZE).flat_map(|y| (0..CHUNK_SIZE).map(move |x| IVec2::new(x, y))).collect()
+}
+
+#[test]
+fn mark_dirty_stains_and_redraws_cells_written_through_cells_mut() {
+    let mut app = drawing_app::<SandCell>();
+    let redrawn = record_redraws::<SandCell>(&mut app);
+    let entity = spawn_drawn_chunk(&mut app, IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+
+    app.update();
+
+    let mut chunk = app.world.get_mut::<Chunk<SandCell, CHUNK_SIZE>>(entity).unwrap();
+
+    for cell in chunk.cells_mut().iter_mut().step_by(3) {
+        *cell = SandCell::Bedrock;
+    }
+
+    // Writes through `cells_mut` are not stained, so nothing is redrawn for them.
+    assert!(chunk.stained().is_empty());
+
+    app.update();
+
+    assert!(redrawn.lock().unwrap().is_empty());
+
+    app.world.get_mut::<Chunk<SandCell, CHUNK_SIZE>>(entity).unwrap().mark_dirty();
+
+    let chunk = app.world.get::<Chunk<SandCell, CHUNK_SIZE>>(entity).unwrap();
+
+    assert_eq!(chunk.stained().points().collect::<HashSet<_>>(), all_points());
+
+    app.update();
+
+    let redrawn = redrawn.lock().unwrap();
+
+    assert_eq!(redrawn.len(), all_points().len());
+    assert_eq!(redrawn.iter().copied().collect::<HashSet<_>>(), all_points());
+}
+
+#[test]
+fn mark_dirty_rect_stains_and_redraws_only_the_rect_within_the_chunk() {
+    let mut app = drawing_app::<SandCell>();
+    let redrawn = record_redraws::<SandCell>(&mut app);
+    let entity = spawn_drawn_chunk(&mut app, IVec2::ONE, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+
+    app.update();
+
+    // Reaches past the chunk's right edge.
+    app.world.get_mut::<Chunk<SandCell, CHUNK_SIZE>>(entity).unwrap().mark_dirty_rect(IRect::new(CHUNK_SIZE - 3, 2, CHUNK_SIZE + 5, 4));
+
+    let expected: HashSet<_> = (2..=4).flat_map(|y| (CHUNK_SIZE - 3..CHUNK_SIZE).map(move |x| IVec2::new(x, y))).collect();
+
+    let chunk = app.world.get::<Chunk<SandCell, CHUNK_SIZE>>(entity).unwrap();
+
+    assert_eq!(chunk.stained().points().collect::<HashSet<_>>(), expected);
+
+    app.update();
+
+    // The hook is given world points.
+    let expected: HashSet<_> = expected.into_iter().map(|local| local + IVec2::splat(CHUNK_SIZE)).collect();
+
+    assert_eq!(redrawn.lock().unwrap().iter().copied().collect::<HashSet<_>>(), expected);
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::SandCell;
+use powderkeg::{chunk::Chunk, grid::Grid, stain::Stainable};
+
+const SIZE: i32 = 4;
+
+/// Rows bottom first, `None` absent. Symmetric along neither axis.
+const PATTERN: [[Option<SandCell>; SIZE as usize]; SIZE as usize] = {
+    use SandCell::*;
+
+    [
+        [Some(Sand), Some(Sand), Some(Air), None],
+        [Some(Bedrock), Some(Air), Some(Air), Some(Air)],
+        [Some(Air), None, Some(Sand), Some(Air)],
+        [Some(Air), Some(Air), Some(Air), Some(Bedrock)],
+    ]
+};
+
+fn patterned() -> Chunk<SandCell, SIZE> {
+    Chunk::sparse(PATTERN.iter().flatten().copied().collect(), ()).without_initial_stain()
+}
+
+fn cell(chunk: &Chunk<SandCell, SIZE>, point: IVec2) -> Option<SandCell> {
+    chunk.get(point).ok().copied()
+}
+
+fn assert_fully_stained(chunk: &Chunk<SandCell, SIZE>) {
+    let all: HashSet<_> = (0..SIZE).flat_map(|y| (0..SIZE).map(move |x| IVec2::new(x, y))).collect();
+
+    assert_eq!(chunk.stained().points().collect::<HashSet<_>>(), all);
+}
+
+#[test]
+fn mirror_x_reverses_the_columns_and_stains_everything() {
+    let original = patterned();
+    let mut chunk = patterned();
+
+    chunk.mirror_x();
+
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            assert_eq!(cell(&chunk, IVec2::new(x, y)), cell(&original, IVec2::new(SIZE - 1 - x, y)), "({x}, {y})");
+        }
+    }
+
+    // The bottom row, now reading from the right.
+    assert_eq!(cell(&chunk, IVec2::ZERO), None);
+    assert_eq!(cell(&chunk, IVec2::new(3, 0)), Some(SandCell::Sand));
+    assert_fully_stained(&chunk);
+}
+
+#[test]
+fn mirror_y_reverses_the_rows_and_stains_everything() {
+    let original = patterned();
+    let mut chunk = patterned();
+
+    chunk.mirror_y();
+
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            assert_eq!(cell(&chunk, IVec2::new(x, y)), cell(&original, IVec2::new(x, SIZE - 1 - y)), "({x}, {y})");
+        }
+    }
+
+    assert_eq!(cell(&chunk, IVec2::new(3, 0)), Some(SandCell::Bedrock));
+    assert_eq!(cell(&chunk, IVec2::new(3, 3)), None);
+    assert_fully_stained(&chunk);
+}
+
+#[test]
+fn mirroring_twice_restores_the_chunk() {
+    let original = patterned();
+    let mut chunk = patterned();
+
+    chunk.mirror_x();
+    chunk.mirror_y();
+    chunk.mirror_x();
+    chunk.mirror_y();
+
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            assert_eq!(cell(&chunk, IVec2::new(x, y)), cell(&original, IVec2::new(x, y)));
+        }
+    }
+}
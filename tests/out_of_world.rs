@@ -0,0 +1,144 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkCoords}, grid::Grid, simulation::{ChunkSpawner, OutOfWorldPolicy}, stain::Stainable, testing::TestGrid, PowderkegError};
+
+const N: i32 = 8;
+
+/// Blasts clear the cells around them, pokes write to the cell on their left through `get_mut`, pinches write to the
+/// cells on either side and above right of them through `get_many_mut`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Charge {
+    Blast,
+    Poke,
+    Pinch,
+    Sand,
+    #[default]
+    Air,
+}
+
+impl Cell for Charge {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        match *input.this() {
+            Charge::Blast => {
+                for y in -1..=1 {
+                    for x in -1..=1 {
+                        input.grid.replace(input.origin + IVec2::new(x, y), Charge::Air)?;
+                    }
+                }
+            },
+            Charge::Poke => {
+                *input.grid.get_mut(input.origin + IVec2::NEG_X)? = Charge::Sand;
+                input.grid.replace(input.origin, Charge::Air)?;
+            },
+            Charge::Pinch => {
+                let [left, right, above] = input.grid.get_many_mut([input.origin + IVec2::NEG_X, input.origin + IVec2::X, input.origin + IVec2::ONE])?;
+
+                *left = Charge::Sand;
+                *right = Charge::Air;
+                *above = Charge::Air;
+            },
+            _ => {},
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-1, -1, 1, 1)
+    }
+}
+
+impl Renderable for Charge {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::BLACK
+    }
+}
+
+fn grid(policy: OutOfWorldPolicy, cell: Charge) -> TestGrid<Charge, N> {
+    let mut grid = TestGrid::<Charge, N>::new(0);
+
+    grid.app_mut().insert_resource(policy);
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(Charge::Sand, ()).without_initial_stain());
+    grid.set(IVec2::ZERO, cell).unwrap();
+
+    grid
+}
+
+fn chunk_count(grid: &mut TestGrid<Charge, N>) -> usize {
+    grid.app_mut().world.query::<&ChunkCoords<N>>().iter(&grid.app_mut().world).count()
+}
+
+#[test]
+fn skipped_writes_outside_the_world_are_dropped() {
+    let mut grid = grid(OutOfWorldPolicy::Skip, Charge::Blast);
+
+    assert!(grid.step().is_empty());
+
+    for point in [IVec2::ZERO, IVec2::X, IVec2::ONE, IVec2::Y] {
+        assert_eq!(grid.get(point), Some(&Charge::Air));
+    }
+
+    assert_eq!(grid.get(IVec2::new(2, 0)), Some(&Charge::Sand));
+    assert_eq!(chunk_count(&mut grid), 1);
+}
+
+#[test]
+fn writes_outside_the_world_are_errors() {
+    let mut grid = grid(OutOfWorldPolicy::Error, Charge::Blast);
+
+    let errors = grid.step();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].point, IVec2::ZERO);
+    assert!(matches!(errors[0].error, PowderkegError::ChunkOutOfBounds(coords) if coords == IVec2::NEG_ONE));
+
+    // The blast stopped at its first write, which was outside.
+    assert_eq!(grid.get(IVec2::ZERO), Some(&Charge::Blast));
+}
+
+#[test]
+fn get_mut_outside_the_world_follows_the_policy() {
+    let mut failing = grid(OutOfWorldPolicy::Error, Charge::Poke);
+
+    assert!(matches!(failing.step()[0].error, PowderkegError::ChunkOutOfBounds(coords) if coords == IVec2::NEG_X));
+
+    let mut grid = grid(OutOfWorldPolicy::Spawn, Charge::Poke);
+
+    grid.app_mut().insert_resource(ChunkSpawner::<Charge, N>::new(|_| Chunk::full_copied(Charge::Air, ()), |_| false));
+
+    assert!(grid.step().is_empty());
+    assert_eq!(grid.get(IVec2::ZERO), Some(&Charge::Air));
+    assert_eq!(chunk_count(&mut grid), 2);
+}
+
+#[test]
+fn get_many_mut_outside_the_world_follows_the_policy() {
+    for policy in [OutOfWorldPolicy::Error, OutOfWorldPolicy::Skip] {
+        let mut failing = grid(policy, Charge::Pinch);
+
+        assert!(matches!(failing.step()[0].error, PowderkegError::ChunkOutOfBounds(coords) if coords == IVec2::NEG_X));
+        assert_eq!(failing.get(IVec2::X), Some(&Charge::Sand));
+    }
+
+    let mut grid = grid(OutOfWorldPolicy::Spawn, Charge::Pinch);
+
+    grid.app_mut().insert_resource(ChunkSpawner::<Charge, N>::new(|_| Chunk::full_copied(Charge::Air, ()), |_| false));
+
+    assert!(grid.step().is_empty());
+
+    // The grid only reads the chunks inserted into it, the spawned one is found through the world.
+    let mut chunks = grid.app_mut().world.query::<(&ChunkCoords<N>, &Chunk<Charge, N>)>();
+    let spawned = chunks
+        .iter(&grid.app_mut().world)
+        .find(|(coords, _)| coords.0 == IVec2::NEG_X)
+        .map(|(_, chunk)| *chunk.get(IVec2::new(N - 1, 0)).unwrap());
+
+    assert_eq!(spawned, Some(Charge::Sand));
+    assert_eq!(grid.get(IVec2::X), Some(&Charge::Air));
+    assert_eq!(grid.get(IVec2::ONE), Some(&Charge::Air));
+    assert_eq!(chunk_count(&mut grid), 2);
+}
@@ -0,0 +1,66 @@
+use bevy::{math::{IRect, IVec2}, utils::HashMap};
+use powderkeg::area::{Area, StainOrder};
+use rand::{rngs::SmallRng, SeedableRng};
+
+fn overlapping() -> Area {
+    Area::Many(vec![IRect::new(0, 0, 3, 3), IRect::new(2, 2, 5, 5), IRect::new(1, 1, 2, 2)])
+}
+
+fn visits(order: StainOrder) -> HashMap<IVec2, usize> {
+    let mut visits = HashMap::default();
+
+    overlapping().apply_in_order(order, &mut SmallRng::seed_from_u64(0), |point| *visits.entry(point).or_default() += 1);
+
+    visits
+}
+
+#[test]
+fn overlapping_rects_are_visited_once_per_point() {
+    let covered = 2 * 16 - 4;
+
+    for order in [StainOrder::Random, StainOrder::RowMajor, StainOrder::BottomUp] {
+        let visits = visits(order);
+
+        assert_eq!(visits.len(), covered, "{order:?}");
+        assert!(visits.values().all(|count| *count == 1), "{order:?}");
+        assert!(visits.contains_key(&IVec2::new(2, 3)) && visits.contains_key(&IVec2::new(5, 5)), "{order:?}");
+    }
+}
+
+#[test]
+fn row_major_visits_rows_bottom_up_each_left_to_right() {
+    let mut applied = Vec::new();
+
+    overlapping().apply_in_order(StainOrder::RowMajor, &mut SmallRng::seed_from_u64(0), |point| applied.push(point));
+
+    let in_union = |point: IVec2| point.max_element() <= 3 || point.min_element() >= 2;
+    let expected: Vec<_> = (0..=5)
+        .flat_map(|y| (0..=5).map(move |x| IVec2::new(x, y)))
+        .filter(|point| in_union(*point))
+        .collect();
+
+    assert_eq!(applied, expected);
+    assert_eq!(applied[..6], [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(2, 0), IVec2::new(3, 0), IVec2::new(0, 1), IVec2::new(1, 1)]);
+    assert_eq!(applied.last(), Some(&IVec2::new(5, 5)));
+}
+
+#[test]
+fn bottom_up_never_visits_a_lower_row_after_a_higher_one() {
+    let mut applied = Vec::new();
+
+    overlapping().apply_in_order(StainOrder::BottomUp, &mut SmallRng::seed_from_u64(3), |point| applied.push(point));
+
+    assert!(applied.windows(2).all(|pair| pair[0].y <= pair[1].y), "{applied:?}");
+}
+
+#[test]
+fn points_in_order_matches_apply_in_order() {
+    let mut applied = Vec::new();
+
+    overlapping().apply_in_order(StainOrder::Random, &mut SmallRng::seed_from_u64(7), |point| applied.push(point));
+
+    let points: Vec<_> = overlapping().points_in_order(StainOrder::Random, &mut SmallRng::seed_from_u64(7)).collect();
+
+    assert_eq!(applied, points);
+    assert_eq!(overlapping().points().count(), 2 * 16 + 4);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, grid::Grid, stain::{Stainable, StainPolicy}};
+
+/// Air, bedrock and sand in diagonal stripes, unstained. Stains are kept precisely so each can be checked.
+fn mixed() -> Chunk<SandCell, CHUNK_SIZE> {
+    let cells = (0..CHUNK_SIZE * CHUNK_SIZE)
+        .map(|i| match (i % CHUNK_SIZE + i / CHUNK_SIZE) % 3 {
+            0 => SandCell::Air,
+            1 => SandCell::Bedrock,
+            _ => SandCell::Sand,
+        })
+        .collect();
+
+    Chunk::new(cells, ()).with_stain_policy(StainPolicy::Precise).without_initial_stain()
+}
+
+/// Asserts `painted` is `before` with sand over exactly the air points passing `within`, and only they are stained.
+fn assert_painted_air_only(before: &Chunk<SandCell, CHUNK_SIZE>, painted: &Chunk<SandCell, CHUNK_SIZE>, count: usize, within: impl Fn(IVec2) -> bool) {
+    let mut changed = HashSet::new();
+
+    for (point, old) in before.iter() {
+        let new = *painted.get(point).unwrap();
+
+        if within(point) && *old == SandCell::Air {
+            assert_eq!(new, SandCell::Sand, "air at {point} was not painted");
+            changed.insert(point);
+        } else {
+            assert_eq!(new, *old, "{point} was painted over");
+        }
+    }
+
+    assert!(!changed.is_empty());
+    assert_eq!(count, changed.len());
+    assert_eq!(painted.stained().points().collect::<HashSet<_>>(), changed);
+}
+
+#[test]
+fn paint_rect_only_writes_and_stains_cells_passing_only_if() {
+    let before = mixed();
+    let mut chunk = mixed();
+
+    // Reaches past the chunk's left edge, those points are skipped.
+    let rect = IRect::new(-3, 2, 6, 9);
+    let count = chunk.paint_rect(rect, SandCell::Sand, |cell| *cell == SandCell::Air);
+
+    assert_painted_air_only(&before, &chunk, count, |point| rect.contains(point));
+}
+
+#[test]
+fn paint_circle_only_writes_and_stains_cells_passing_only_if() {
+    let before = mixed();
+    let mut chunk = mixed();
+
+    let center = IVec2::new(CHUNK_SIZE - 2, 5);
+    let count = chunk.paint_circle(center, 4, SandCell::Sand, |cell| *cell == SandCell::Air);
+
+    assert_painted_air_only(&before, &chunk, count, |point| (point - center).length_squared() <= 16);
+}
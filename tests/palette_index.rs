@@ -0,0 +1,34 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{checked_palette_index, Cell, TickInput, TickSuccess}, stain::Stainable, PowderkegError};
+
+struct ThreeVariants;
+
+impl Cell for ThreeVariants {
+    type State = ();
+    type Error = Infallible;
+
+    const VARIANT_COUNT: usize = 3;
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+const LAST: usize = checked_palette_index::<ThreeVariants>(2);
+
+#[test]
+fn index_within_variant_count_is_accepted() {
+    assert_eq!(LAST, 2);
+}
+
+#[test]
+#[should_panic(expected = "palette index exceeds Cell::VARIANT_COUNT")]
+fn too_large_index_is_caught() {
+    checked_palette_index::<ThreeVariants>(std::hint::black_box(3));
+}
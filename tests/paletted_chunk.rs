@@ -0,0 +1,99 @@
+mod common;
+
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{cell::{Cell, TickInput, TickSuccess}, chunk::Chunk, grid::{DisjointGrid, Grid}, palette::PalettedChunk, stain::Stainable, PowderkegError};
+
+/// A cell holding any number, so a chunk can have as many distinct cells as it has cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Numbered(u32);
+
+impl Cell for Numbered {
+    type State = ();
+    type Error = Infallible;
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+fn sand_chunk() -> Chunk<SandCell, CHUNK_SIZE> {
+    let cells = (0..Chunk::<SandCell, CHUNK_SIZE>::volume())
+        .map(|index| match index % 3 {
+            0 => SandCell::Sand,
+            1 => SandCell::Air,
+            _ => SandCell::Bedrock,
+        })
+        .collect();
+
+    Chunk::new(cells, ())
+}
+
+#[test]
+fn round_trips_through_a_chunk() {
+    let chunk = sand_chunk();
+    let paletted = PalettedChunk::from_chunk(&chunk);
+
+    assert_eq!(paletted.palette().len(), 3);
+    assert_eq!(paletted.to_chunk().cells(), chunk.cells());
+}
+
+#[test]
+fn borrowing_a_cell_again_keeps_its_entry() {
+    let mut paletted = PalettedChunk::from_chunk(&sand_chunk());
+
+    for _ in 0..1000 {
+        *paletted.get_mut(IVec2::new(1, 1)).unwrap() = SandCell::Sand;
+    }
+
+    for _ in 0..1000 {
+        let [first, second] = paletted.get_many_mut([IVec2::new(2, 2), IVec2::new(3, 2)]).unwrap();
+
+        std::mem::swap(first, second);
+    }
+
+    assert_eq!(paletted.palette().len(), 6);
+
+    paletted.compact();
+
+    assert_eq!(paletted.palette().len(), 3);
+    assert_eq!(*paletted.get(IVec2::new(1, 1)).unwrap(), SandCell::Sand);
+}
+
+#[test]
+fn replaced_cells_reuse_unused_entries() {
+    let mut paletted = PalettedChunk::from_chunk(&Chunk::<SandCell, CHUNK_SIZE>::default());
+
+    for _ in 0..1000 {
+        *paletted.get_mut(IVec2::ZERO).unwrap() = SandCell::Sand;
+        paletted.replace(IVec2::ZERO, SandCell::Bedrock).unwrap();
+    }
+
+    assert!(paletted.palette().len() <= 3);
+    assert_eq!(*paletted.get(IVec2::ZERO).unwrap(), SandCell::Bedrock);
+    assert_eq!(*paletted.get(IVec2::ONE).unwrap(), SandCell::Air);
+}
+
+#[test]
+fn more_distinct_cells_than_fit_in_two_bytes() {
+    const SIZE: i32 = 257;
+
+    let mut paletted = PalettedChunk::from_chunk(&Chunk::<Numbered, SIZE>::default());
+    let points = (0..SIZE).flat_map(|y| (0..SIZE).map(move |x| IVec2::new(x, y)));
+
+    for (number, point) in points.clone().enumerate() {
+        *paletted.get_mut(point).unwrap() = Numbered(number as u32);
+    }
+
+    assert!(paletted.palette().len() > u16::MAX as usize);
+
+    for (number, point) in points.enumerate() {
+        assert_eq!(*paletted.get(point).unwrap(), Numbered(number as u32), "{point} holds the wrong cell");
+    }
+}
@@ -0,0 +1,79 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, stain::Stainable, testing::TestGrid, PowderkegError};
+
+/// Grows one stage a tick, seeds and trees in the first phase and sprouts in the second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Plant {
+    #[default]
+    Soil,
+    Seed,
+    Sprout,
+    Tree,
+    /// Claims a phase past [`Cell::PHASES`].
+    Weed,
+}
+
+impl Cell for Plant {
+    type Error = Infallible;
+    type State = ();
+
+    const PHASES: u32 = 2;
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        let grown = match input.this() {
+            Plant::Seed => Plant::Sprout,
+            Plant::Sprout => Plant::Tree,
+            _ => return Ok(TickSuccess::Stable),
+        };
+
+        input.grid.replace(input.origin, grown)?;
+
+        Ok(TickSuccess::Unstable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+
+    fn phase(&self) -> u32 {
+        match self {
+            Plant::Sprout => 1,
+            Plant::Weed => 5,
+            _ => 0,
+        }
+    }
+}
+
+impl Renderable for Plant {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::GREEN
+    }
+}
+
+fn planted(cell: Plant) -> TestGrid<Plant, 8> {
+    let mut grid = TestGrid::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(Plant::Soil, ()));
+    grid.set(IVec2::new(3, 3), cell).unwrap();
+
+    grid
+}
+
+#[test]
+fn a_cell_transformed_into_a_later_phase_waits_for_the_next_tick() {
+    let mut grid = planted(Plant::Seed);
+
+    assert!(grid.step().is_empty());
+    assert_eq!(grid.get(IVec2::new(3, 3)), Some(&Plant::Sprout));
+
+    assert!(grid.step().is_empty());
+    assert_eq!(grid.get(IVec2::new(3, 3)), Some(&Plant::Tree));
+}
+
+#[test]
+#[should_panic(expected = "is not below Cell::PHASES")]
+fn a_phase_past_the_last_is_caught() {
+    planted(Plant::Weed).step();
+}
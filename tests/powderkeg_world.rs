@@ -0,0 +1,85 @@
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use powderkeg::{chunk::{Chunk, ChunkCoords}, grid::line, testing::TestGrid, world::PowderkegWorld};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+fn two_chunks() -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut grid = TestGrid::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()));
+    grid.insert_chunk(IVec2::X, Chunk::full_copied(SandCell::Air, ()));
+
+    grid
+}
+
+#[test]
+fn writes_reach_across_chunks() {
+    let mut grid = two_chunks();
+
+    grid.app_mut().world.run_system_once(|mut world: PowderkegWorld<SandCell, CHUNK_SIZE>| {
+        let left = IVec2::new(CHUNK_SIZE - 1, 3);
+        let right = IVec2::new(CHUNK_SIZE, 3);
+
+        assert_eq!(world.set_cell(left, SandCell::Sand).unwrap(), SandCell::Air);
+        world.swap(left, right).unwrap();
+
+        assert_eq!(world.get_cell(left), Some(&SandCell::Air));
+        assert_eq!(world.get_cell(right), Some(&SandCell::Sand));
+        assert!(world.get_cell(IVec2::new(-1, 3)).is_none());
+
+        assert_eq!(world.paint_circle(IVec2::new(CHUNK_SIZE, 8), 2, SandCell::Bedrock, |_| true), 13);
+        assert_eq!(world.count_matching(IRect::new(0, 0, 2 * CHUNK_SIZE - 1, CHUNK_SIZE - 1), |cell| *cell == SandCell::Bedrock), 13);
+    });
+
+    assert_eq!(grid.get(IVec2::new(CHUNK_SIZE, 3)), Some(&SandCell::Sand));
+    assert_eq!(grid.get(IVec2::new(CHUNK_SIZE - 2, 8)), Some(&SandCell::Bedrock));
+}
+
+#[test]
+fn world_point_uses_the_transform_of_the_chunk_under_it() {
+    let mut grid = two_chunks();
+
+    // The right chunk is placed and scaled on its own, unlike the grid its coordinates would put it in.
+    let mut chunks = grid.app_mut().world.query::<(&ChunkCoords<CHUNK_SIZE>, &mut GlobalTransform)>();
+
+    for (coords, mut transform) in chunks.iter_mut(&mut grid.app_mut().world) {
+        if coords.0 == IVec2::X {
+            *transform = GlobalTransform::from(Transform::from_xyz(100.0, 0.0, 0.0).with_scale(Vec3::splat(2.0)));
+        }
+    }
+
+    grid.app_mut().world.run_system_once(|world: PowderkegWorld<SandCell, CHUNK_SIZE>| {
+        assert_eq!(world.world_point(Vec2::ZERO), Some(IVec2::splat(CHUNK_SIZE / 2)));
+        assert_eq!(world.world_point(Vec2::new(104.0, -4.0)), Some(IVec2::new(CHUNK_SIZE + CHUNK_SIZE / 2 + 2, CHUNK_SIZE / 2 - 2)));
+    });
+}
+
+#[test]
+fn paint_line_writes_each_cell_of_the_circles_along_it_once() {
+    let (a, b) = (IVec2::new(2, 3), IVec2::new(2 * CHUNK_SIZE - 5, 12));
+
+    let mut stroke = two_chunks();
+    let mut circles = two_chunks();
+
+    let painted = stroke.app_mut().world.run_system_once(move |mut world: PowderkegWorld<SandCell, CHUNK_SIZE>| {
+        world.paint_line(a, b, 3, SandCell::Bedrock, |cell| *cell == SandCell::Air)
+    });
+
+    circles.app_mut().world.run_system_once(move |mut world: PowderkegWorld<SandCell, CHUNK_SIZE>| {
+        for point in line(a, b) {
+            world.paint_circle(point, 3, SandCell::Bedrock, |cell| *cell == SandCell::Air);
+        }
+    });
+
+    let bedrock = |grid: &TestGrid<SandCell, CHUNK_SIZE>| -> Vec<IVec2> {
+        (0..CHUNK_SIZE)
+            .flat_map(|y| (0..2 * CHUNK_SIZE).map(move |x| IVec2::new(x, y)))
+            .filter(|point| grid.get(*point) == Some(&SandCell::Bedrock))
+            .collect()
+    };
+
+    assert_eq!(bedrock(&stroke), bedrock(&circles));
+    assert_eq!(painted, bedrock(&stroke).len());
+}
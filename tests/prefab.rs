@@ -0,0 +1,56 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, TickInput, TickSuccess}, chunk::Chunk, grid::Grid, prefab::{Prefab, PrefabRotation, PrefabTransform}, stain::Stainable, PowderkegError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Tile(u8);
+
+impl Cell for Tile {
+    type State = ();
+    type Error = Infallible;
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+/// The tiles of a 4x4 chunk, top row first so it reads the way the chunk is drawn.
+fn rows(chunk: &Chunk<Tile, 4>) -> [[u8; 4]; 4] {
+    std::array::from_fn(|row| std::array::from_fn(|x| chunk.get(IVec2::new(x as i32, 3 - row as i32)).unwrap().0))
+}
+
+#[test]
+fn stamps_a_flipped_and_rotated_prefab_leaving_masked_cells() {
+    // Drawn top row first:
+    //   3 . 4
+    //   1 2 .
+    let prefab = Prefab::new(IVec2::new(3, 2), vec![Some(Tile(1)), Some(Tile(2)), None, Some(Tile(3)), None, Some(Tile(4))]);
+    let transform = PrefabTransform::rotated(PrefabRotation::Quarter).with_flip_x(true);
+
+    let mut chunk = Chunk::<Tile, 4>::full_copied(Tile(9), ());
+
+    chunk.stamp_prefab(IVec2::ONE, &prefab, transform).unwrap();
+
+    assert_eq!(transform.footprint(prefab.size()), IVec2::new(2, 3));
+
+    // Flipped it reads `4 . 3` over `. 2 1`, a quarter turn counterclockwise puts the right column on top.
+    assert_eq!(rows(&chunk), [
+        [9, 3, 1, 9],
+        [9, 9, 2, 9],
+        [9, 4, 9, 9],
+        [9, 9, 9, 9],
+    ]);
+}
+
+#[test]
+fn stamping_past_the_grid_fails() {
+    let prefab = Prefab::new(IVec2::new(2, 1), vec![Some(Tile(1)), Some(Tile(2))]);
+    let mut chunk = Chunk::<Tile, 4>::full_copied(Tile(9), ());
+
+    assert!(chunk.stamp_prefab(IVec2::new(3, 0), &prefab, PrefabTransform::default()).is_err());
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::chunk::Chunk;
+use rand::{distributions::Distribution, Rng};
+
+/// Sand, air and bedrock equally likely.
+struct AnyCell;
+
+impl Distribution<SandCell> for AnyCell {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SandCell {
+        match rng.gen_range(0..3) {
+            0 => SandCell::Sand,
+            1 => SandCell::Air,
+            _ => SandCell::Bedrock,
+        }
+    }
+}
+
+fn cells(seed: u64, coords: IVec2) -> Vec<SandCell> {
+    Chunk::<SandCell, CHUNK_SIZE>::full_random_for_coord(seed, coords, AnyCell, ()).cells().to_vec()
+}
+
+#[test]
+fn the_same_seed_and_coords_build_the_same_chunk() {
+    for coords in [IVec2::ZERO, IVec2::new(3, -7), IVec2::new(-1000, 1000)] {
+        assert_eq!(cells(42, coords), cells(42, coords), "{coords}");
+    }
+}
+
+#[test]
+fn other_coords_or_seeds_build_other_chunks() {
+    let origin = cells(42, IVec2::ZERO);
+
+    for coords in [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::new(3, -7)] {
+        assert_ne!(cells(42, coords), origin, "{coords}");
+    }
+
+    // Swapping the axes is not the same chunk.
+    assert_ne!(cells(42, IVec2::new(2, 5)), cells(42, IVec2::new(5, 2)));
+
+    assert_ne!(cells(43, IVec2::ZERO), origin);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::{drawing_app, record_redraws, spawn_drawn_chunk, SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, stain::{Stainable, StainPolicy}, viewer::RenderChannel};
+
+#[test]
+fn fires_only_for_stained_cells() {
+    let mut app = drawing_app::<SandCell>();
+    let redrawn = record_redraws::<SandCell>(&mut app);
+    // Kept precisely, rather than growing a rect over both stains.
+    let chunk = Chunk::full_copied(SandCell::Air, ()).with_stain_policy(StainPolicy::Precise).without_initial_stain();
+    let entity = spawn_drawn_chunk(&mut app, IVec2::ZERO, chunk);
+
+    app.update();
+
+    let mut chunk = app.world.get_mut::<Chunk<SandCell, CHUNK_SIZE>>(entity).unwrap();
+
+    chunk.stain(IRect::new(2, 2, 3, 4));
+    chunk.stain_point(IVec2::new(9, 1));
+
+    app.update();
+
+    let expected: HashSet<_> = chunk_points(IRect::new(2, 2, 3, 4)).chain([IVec2::new(9, 1)]).collect();
+    let calls = std::mem::take(&mut *redrawn.lock().unwrap());
+
+    assert_eq!(calls.len(), expected.len());
+    assert_eq!(calls.into_iter().collect::<HashSet<_>>(), expected);
+}
+
+#[test]
+fn forced_redraws_skip_unstained_cells() {
+    let mut app = drawing_app::<SandCell>();
+    let redrawn = record_redraws::<SandCell>(&mut app);
+    let entity = spawn_drawn_chunk(&mut app, IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+
+    app.update();
+
+    // Every cell is redrawn in the new channel but none changed.
+    *app.world.resource_mut::<RenderChannel>() = RenderChannel::Temperature;
+
+    app.update();
+
+    assert!(redrawn.lock().unwrap().is_empty());
+
+    // Alongside a stain, only the stained cells.
+    app.world.get_mut::<Chunk<SandCell, CHUNK_SIZE>>(entity).unwrap().stain_point(IVec2::new(5, 5));
+    *app.world.resource_mut::<RenderChannel>() = RenderChannel::Color;
+
+    app.update();
+
+    assert_eq!(*redrawn.lock().unwrap(), [IVec2::new(5, 5)]);
+}
+
+fn chunk_points(rect: IRect) -> impl Iterator<Item = IVec2> {
+    (rect.min.y..=rect.max.y).flat_map(move |y| (rect.min.x..=rect.max.x).map(move |x| IVec2::new(x, y)))
+}
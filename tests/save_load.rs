@@ -0,0 +1,69 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::{Chunk, ChunkBundle, ChunkCoords}, grid::Grid, save::{load_world, save_world}};
+
+type SandChunk = Chunk<SandCell, CHUNK_SIZE>;
+
+fn chunk_with_sand_at(x: i32) -> SandChunk {
+    let mut chunk = Chunk::full_copied(SandCell::Air, ());
+
+    chunk.replace(IVec2::new(x, 3), SandCell::Sand).unwrap();
+    chunk
+}
+
+/// Every chunk's coordinates, transform and cells, sorted by coordinates.
+fn chunks(world: &mut World) -> Vec<(IVec2, Transform, Vec<SandCell>)> {
+    let mut query = world.query::<(&ChunkCoords<CHUNK_SIZE>, &Transform, &SandChunk)>();
+    let mut chunks: Vec<_> = query.iter(world).map(|(coords, transform, chunk)| (coords.0, *transform, chunk.cells().to_vec())).collect();
+
+    chunks.sort_by_key(|(coords, ..)| (coords.y, coords.x));
+    chunks
+}
+
+#[test]
+fn save_clear_and_load_restores_the_chunks_under_the_same_parent() {
+    let path = std::env::temp_dir().join(format!("powderkeg_save_load_{}.ron", std::process::id()));
+    let mut world = World::new();
+
+    let parent = world
+        .spawn(SpatialBundle::from_transform(Transform::from_xyz(5.0, -2.0, 0.0)))
+        .with_children(|children| {
+            for x in 0..3 {
+                children.spawn(ChunkBundle {
+                    chunk: chunk_with_sand_at(x),
+                    coords: ChunkCoords::<CHUNK_SIZE>(IVec2::new(x, 0)),
+                    transform: TransformBundle::from_transform(Transform::from_xyz((x * CHUNK_SIZE) as f32, 0.0, 0.0)),
+                    visibility: default(),
+                });
+            }
+        })
+        .id();
+
+    let saved = chunks(&mut world);
+
+    assert_eq!(save_world::<SandCell, CHUNK_SIZE>(&mut world, &path).unwrap(), 3);
+
+    let mut query = world.query_filtered::<Entity, With<SandChunk>>();
+    let entities: Vec<_> = query.iter(&world).collect();
+
+    world.entity_mut(entities[0]).despawn_recursive();
+
+    for entity in &entities[1..] {
+        world.get_mut::<SandChunk>(*entity).unwrap().clear();
+    }
+
+    world.get_mut::<Transform>(parent).unwrap().translation = Vec3::ZERO;
+
+    let loaded_parent = load_world::<SandCell, CHUNK_SIZE>(&mut world, &path).unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded_parent, parent);
+    assert_eq!(world.get::<Transform>(parent).unwrap().translation, Vec3::new(5.0, -2.0, 0.0));
+    assert_eq!(world.get::<Children>(parent).unwrap().len(), 3);
+    assert_eq!(chunks(&mut world), saved);
+}
@@ -0,0 +1,132 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, stain::Stainable, testing::TestGrid, PowderkegError};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+fn count<T: Renderable + PartialEq>(grid: &TestGrid<T, CHUNK_SIZE>, coords: &[IVec2], cell: T) -> usize {
+    coords
+        .iter()
+        .map(|coords| grid.chunk(*coords).unwrap().iter().filter(|(_, other)| **other == cell).count())
+        .sum()
+}
+
+#[test]
+fn sand_poured_across_seams_is_conserved() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(11);
+    let coords = [IVec2::new(-1, -1), IVec2::new(0, -1), IVec2::new(-1, 0), IVec2::new(0, 0)];
+
+    for coords in coords {
+        grid.insert_chunk(coords, Chunk::default());
+    }
+
+    let mut poured = 0;
+
+    for step in 0..CHUNK_SIZE * 4 {
+        if step < CHUNK_SIZE * 2 {
+            for x in [-2, -1, 0, 1] {
+                if grid.set(IVec2::new(x, CHUNK_SIZE - 1), SandCell::Sand).unwrap() == SandCell::Air {
+                    poured += 1;
+                }
+            }
+        }
+
+        assert!(grid.step().is_empty());
+        assert_eq!(count(&grid, &coords, SandCell::Sand), poured);
+    }
+
+    // Every column settled into a full stack reaching across the seam below.
+    for x in [-2, -1, 0, 1] {
+        assert_eq!(grid.get(IVec2::new(x, -CHUNK_SIZE)), Some(&SandCell::Sand));
+        assert_eq!(grid.get(IVec2::new(x, 0)), Some(&SandCell::Sand));
+    }
+}
+
+/// Cells that move one cell sideways each tick whatever is in the way, or set fire to the cell to their right.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Drift {
+    #[default]
+    Air,
+    Left,
+    Right,
+    Fire,
+}
+
+impl Cell for Drift {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        match *input.this() {
+            Drift::Left => input.grid.swap(input.origin, input.origin + IVec2::NEG_X)?,
+            Drift::Right => input.grid.swap(input.origin, input.origin + IVec2::X)?,
+            Drift::Fire => {
+                input.grid.replace(input.origin + IVec2::X, Drift::Fire)?;
+            },
+            Drift::Air => return Ok(TickSuccess::Stable),
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        match self {
+            Drift::Left => IRect::new(-1, 0, 0, 0),
+            Drift::Right | Drift::Fire => IRect::new(0, 0, 1, 0),
+            Drift::Air => IRect::new(0, 0, 0, 0),
+        }
+    }
+}
+
+impl Renderable for Drift {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::BLACK
+    }
+}
+
+fn drift_grid() -> TestGrid<Drift, CHUNK_SIZE> {
+    let mut grid = TestGrid::<Drift, CHUNK_SIZE>::new(5);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(Drift::Air, ()).without_initial_stain());
+    grid.insert_chunk(IVec2::X, Chunk::full_copied(Drift::Air, ()).without_initial_stain());
+
+    grid
+}
+
+#[test]
+fn head_on_swap_at_a_seam_moves_each_cell_once() {
+    let mut grid = drift_grid();
+    let seam = IVec2::new(CHUNK_SIZE, 3);
+
+    grid.set(seam + IVec2::NEG_X, Drift::Right).unwrap();
+    grid.set(seam, Drift::Left).unwrap();
+
+    assert!(grid.step().is_empty());
+
+    // The lower left cell ticks first and the cell it displaced waits for the next tick.
+    assert_eq!(grid.get(seam + IVec2::NEG_X), Some(&Drift::Left));
+    assert_eq!(grid.get(seam), Some(&Drift::Right));
+
+    let chunks = [IVec2::ZERO, IVec2::X];
+
+    assert_eq!(count(&grid, &chunks, Drift::Left), 1);
+    assert_eq!(count(&grid, &chunks, Drift::Right), 1);
+}
+
+#[test]
+fn cells_replaced_at_a_seam_wait_for_the_next_tick() {
+    let mut grid = drift_grid();
+    let seam = IVec2::new(CHUNK_SIZE, 3);
+
+    grid.set(seam + IVec2::NEG_X, Drift::Fire).unwrap();
+    grid.set(seam, Drift::Left).unwrap();
+
+    assert!(grid.step().is_empty());
+
+    // The fire that replaced the cell queued at the seam does not tick in its place.
+    assert_eq!(grid.get(seam), Some(&Drift::Fire));
+    assert_eq!(grid.get(seam + IVec2::X), Some(&Drift::Air));
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "serde")]
+
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{area::Area, cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkCoords}, grid::Grid, stain::Stainable, PowderkegError};
+use rand::{distributions::{Distribution, Standard}, rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+const N: i32 = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum Element {
+    #[default]
+    Air,
+    Sand,
+    Water(u8),
+}
+
+impl Distribution<Element> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Element {
+        match rng.gen_range(0..3) {
+            0 => Element::Air,
+            1 => Element::Sand,
+            _ => Element::Water(rng.gen()),
+        }
+    }
+}
+
+/// How many cells of water the chunk holds, standing in for any serializable chunk state.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Volume(u32);
+
+impl Cell for Element {
+    type Error = Infallible;
+    type State = Volume;
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+impl Renderable for Element {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::WHITE
+    }
+}
+
+fn points() -> impl Iterator<Item = IVec2> {
+    (0..N).flat_map(|y| (0..N).map(move |x| IVec2::new(x, y)))
+}
+
+#[test]
+fn random_chunk_round_trips_through_ron() {
+    let mut chunk = Chunk::<Element, N>::full_random(&mut SmallRng::seed_from_u64(3), Standard, Volume(17));
+
+    chunk.clear_stain();
+    chunk.stain_point(IVec2::new(2, 3));
+    chunk.stain_point(IVec2::new(12, 9));
+
+    let ron = ron::to_string(&chunk).unwrap();
+    let loaded: Chunk<Element, N> = ron::from_str(&ron).unwrap();
+
+    for point in points() {
+        assert_eq!(loaded.get(point).unwrap(), chunk.get(point).unwrap(), "{point}");
+    }
+
+    assert_eq!(*loaded.state().read(), Volume(17));
+    assert_eq!(loaded.stained().rects(), chunk.stained().rects());
+    assert!(!std::sync::Arc::ptr_eq(loaded.state(), chunk.state()));
+}
+
+#[test]
+fn coords_and_areas_round_trip_through_ron() {
+    let coords = ChunkCoords::<N>(IVec2::new(-3, 7));
+    let area = Area::Many(vec![IRect::new(0, 0, 3, 3), IRect::new(5, 1, 9, 2)]);
+
+    assert_eq!(ron::from_str::<ChunkCoords<N>>(&ron::to_string(&coords).unwrap()).unwrap().0, coords.0);
+    assert_eq!(ron::from_str::<Area>(&ron::to_string(&area).unwrap()).unwrap().rects(), area.rects());
+}
+
+#[test]
+fn chunk_of_the_wrong_size_is_rejected() {
+    let chunk = Chunk::<Element, 4>::full_copied(Element::Sand, Volume(0));
+
+    assert!(ron::from_str::<Chunk<Element, N>>(&ron::to_string(&chunk).unwrap()).is_err());
+}
+
+#[test]
+fn mostly_uniform_chunk_round_trips_through_runs_an_order_of_magnitude_smaller() {
+    const SIZE: i32 = 64;
+
+    // Sand settled along the bottom and a few drops of water, the rest of the chunk air.
+    let cells = (0..SIZE * SIZE)
+        .map(|index| match index {
+            _ if index < 3 * SIZE => Element::Sand,
+            _ if index % 997 == 0 => Element::Water(7),
+            _ => Element::Air,
+        })
+        .collect();
+
+    let chunk = Chunk::<Element, SIZE>::new(cells, Volume(0));
+
+    let runs = ron::to_string(&chunk.to_rle()).unwrap();
+    let loaded = Chunk::<Element, SIZE>::from_rle(ron::from_str::<Vec<(Element, u32)>>(&runs).unwrap(), Volume(0)).unwrap();
+
+    assert_eq!(loaded.cells(), chunk.cells());
+
+    let cells = ron::to_string(chunk.cells()).unwrap();
+
+    assert!(runs.len() * 10 < cells.len(), "{} bytes as runs, {} as cells", runs.len(), cells.len());
+    assert!(Chunk::<Element, SIZE>::from_rle([(Element::Air, 5)], Volume(0)).is_none());
+}
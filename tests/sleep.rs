@@ -0,0 +1,48 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::Chunk, simulation::ChunkSleepThreshold, testing::TestGrid};
+
+#[test]
+fn zero_threshold_never_sleeps() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    grid.app_mut().insert_resource(ChunkSleepThreshold(0));
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+
+    for _ in 0..4 {
+        grid.step();
+    }
+
+    assert!(!grid.chunk(IVec2::ZERO).unwrap().is_asleep(0));
+
+    grid.set(IVec2::new(3, 10), SandCell::Sand).unwrap();
+
+    for _ in 0..10 {
+        grid.step();
+    }
+
+    assert_eq!(grid.get(IVec2::new(3, 0)), Some(&SandCell::Sand));
+}
+
+#[test]
+fn idle_chunk_sleeps_at_the_threshold_and_wakes_when_stained() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    grid.app_mut().insert_resource(ChunkSleepThreshold(2));
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+
+    grid.step();
+
+    assert!(!grid.chunk(IVec2::ZERO).unwrap().is_asleep(2));
+
+    grid.step();
+
+    assert!(grid.chunk(IVec2::ZERO).unwrap().is_asleep(2));
+
+    grid.set(IVec2::new(3, 10), SandCell::Sand).unwrap();
+    grid.step();
+
+    assert_eq!(grid.get(IVec2::new(3, 9)), Some(&SandCell::Sand));
+}
@@ -0,0 +1,96 @@
+use std::convert::Infallible;
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, grid::Grid, stain::Stainable, testing::TestGrid, world::PowderkegWorld, PowderkegError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Counted(u8);
+
+impl Cell for Counted {
+    type State = u32;
+    type Error = Infallible;
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+impl Renderable for Counted {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::WHITE
+    }
+}
+
+#[test]
+fn chunk_snapshot_copies_the_state() {
+    let chunk = Chunk::<Counted, 4>::full_copied(Counted(1), 7);
+    let snapshot = chunk.snapshot();
+
+    *snapshot.get_state(IVec2::ZERO).unwrap().write() = 8;
+
+    assert_eq!(*chunk.state().read(), 7);
+    assert_eq!(snapshot.get(IVec2::new(3, 3)).ok(), Some(&Counted(1)));
+}
+
+#[test]
+fn chunk_snapshot_keeps_absent_cells_absent() {
+    let cells = (0..16).map(|i| (i % 2 == 0).then_some(Counted(i))).collect();
+    let chunk = Chunk::<Counted, 4>::sparse(cells, 0);
+    let snapshot = chunk.snapshot();
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let point = IVec2::new(x, y);
+
+            assert_eq!(snapshot.is_present(point), chunk.is_present(point));
+            assert_eq!(snapshot.get(point).ok(), chunk.get(point).ok());
+        }
+    }
+
+    assert_eq!(snapshot.covers().points().count(), 8);
+}
+
+#[test]
+fn restoring_a_region_recovers_its_cells_and_state() {
+    let mut chunk = Chunk::<Counted, 4>::full_copied(Counted(1), 7);
+    let region = IRect::new(1, 1, 2, 2);
+    let snapshot = chunk.snapshot_region(region);
+
+    chunk.fill_rect(IRect::new(0, 0, 3, 3), Counted(2)).unwrap();
+    *chunk.state().write() = 8;
+
+    chunk.restore(&snapshot).unwrap();
+
+    assert_eq!(chunk.snapshot_region(region), snapshot);
+    assert_eq!(*chunk.state().read(), 7);
+    assert_eq!(chunk.get(IVec2::ZERO).ok(), Some(&Counted(2)));
+}
+
+#[test]
+fn restoring_a_region_across_chunks_recovers_each_chunk() {
+    let mut grid = TestGrid::<Counted, 4>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(Counted(1), 10));
+    grid.insert_chunk(IVec2::X, Chunk::full_copied(Counted(1), 20));
+
+    let region = IRect::new(2, 0, 5, 3);
+
+    grid.app_mut().world.run_system_once(move |mut world: PowderkegWorld<Counted, 4>| {
+        let snapshot = world.snapshot_region(region);
+
+        assert_eq!(snapshot.states().count(), 2);
+
+        world.paint_circle(IVec2::new(4, 2), 2, Counted(2), |_| true);
+        *world.state_at(IVec2::ZERO).unwrap().write() = 11;
+        *world.state_at(IVec2::new(4, 0)).unwrap().write() = 21;
+
+        assert_eq!(world.restore(&snapshot), 16);
+        assert_eq!(world.snapshot_region(region), snapshot);
+        assert_eq!(*world.state_at(IVec2::ZERO).unwrap().read(), 10);
+        assert_eq!(*world.state_at(IVec2::new(4, 0)).unwrap().read(), 20);
+    });
+}
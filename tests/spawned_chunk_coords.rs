@@ -0,0 +1,40 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::{Chunk, ChunkBundle, ChunkCoords}, world::spawned_chunk_coords};
+
+fn live_coords(world: &mut World) -> HashSet<IVec2> {
+    let mut query = world.query::<&ChunkCoords<CHUNK_SIZE>>();
+    let listed: Vec<_> = spawned_chunk_coords(query.iter(world)).collect();
+    let unique: HashSet<_> = listed.iter().copied().collect();
+
+    assert_eq!(listed.len(), unique.len(), "{listed:?} lists a chunk more than once");
+
+    unique
+}
+
+#[test]
+fn lists_exactly_the_live_chunks() {
+    let mut world = World::new();
+
+    assert!(live_coords(&mut world).is_empty());
+
+    let coords = [IVec2::ZERO, IVec2::X, IVec2::new(-3, 2), IVec2::NEG_Y];
+    let entities: Vec<_> = coords
+        .iter()
+        .map(|coords| world.spawn(ChunkBundle::new(Chunk::<SandCell, CHUNK_SIZE>::full_copied(SandCell::Air, ()), ChunkCoords(*coords))).id())
+        .collect();
+
+    assert_eq!(live_coords(&mut world), coords.into_iter().collect());
+
+    world.despawn(entities[1]);
+    world.despawn(entities[2]);
+
+    assert_eq!(live_coords(&mut world), [IVec2::ZERO, IVec2::NEG_Y].into_iter().collect());
+
+    // Respawning a despawned chunk lists it again.
+    world.spawn(ChunkBundle::new(Chunk::<SandCell, CHUNK_SIZE>::full_copied(SandCell::Air, ()), ChunkCoords(IVec2::X)));
+
+    assert_eq!(live_coords(&mut world), [IVec2::ZERO, IVec2::X, IVec2::NEG_Y].into_iter().collect());
+}
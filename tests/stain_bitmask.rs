@@ -0,0 +1,57 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::SandCell;
+use powderkeg::{chunk::Chunk, stain::{Stainable, StainPolicy}};
+
+/// Not a multiple of eight, so the last word has bits past the chunk's cells.
+const SIZE: i32 = 10;
+
+fn is_set(bits: &[u64], point: IVec2) -> bool {
+    let index = (SIZE * point.y + point.x) as usize;
+
+    bits[index / 64] & (1 << (index % 64)) != 0
+}
+
+#[test]
+fn sets_exactly_the_stained_bits_within_the_chunk() {
+    let mut chunk = Chunk::<SandCell, SIZE>::full_copied(SandCell::Air, ())
+        .with_stain_policy(StainPolicy::Precise)
+        .without_initial_stain();
+
+    chunk.stain_point(IVec2::new(4, 0));
+    chunk.stain(IRect::new(1, 6, 2, 7));
+    // Sticks out past the right and bottom edges.
+    chunk.stain(IRect::new(SIZE - 2, -3, SIZE + 4, 1));
+
+    let mut expected: HashSet<IVec2> = [IVec2::new(4, 0), IVec2::new(1, 6), IVec2::new(2, 6), IVec2::new(1, 7), IVec2::new(2, 7)].into_iter().collect();
+
+    expected.extend((0..=1).flat_map(|y| [IVec2::new(SIZE - 2, y), IVec2::new(SIZE - 1, y)]));
+
+    let bits = chunk.stain_bitmask();
+
+    assert_eq!(bits.len(), 2);
+
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let point = IVec2::new(x, y);
+
+            assert_eq!(is_set(&bits, point), expected.contains(&point), "{point}");
+        }
+    }
+
+    // Nothing outside the chunk wrapped around onto another row or into the unused bits.
+    assert_eq!(bits.iter().map(|word| word.count_ones()).sum::<u32>() as usize, expected.len());
+    assert_eq!(bits[1] >> (SIZE * SIZE - 64), 0);
+}
+
+#[test]
+fn unstained_and_fully_stained_chunks() {
+    let mut chunk = Chunk::<SandCell, SIZE>::full_copied(SandCell::Air, ()).without_initial_stain();
+
+    assert!(chunk.stain_bitmask().iter().all(|word| *word == 0));
+
+    chunk.mark_dirty();
+
+    assert_eq!(chunk.stain_bitmask(), [u64::MAX, (1 << (SIZE * SIZE - 64)) - 1]);
+}
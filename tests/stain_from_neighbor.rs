@@ -0,0 +1,50 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{area::Area, chunk::Chunk, stain::Stainable};
+
+fn stained_points(chunk: &Chunk<SandCell, CHUNK_SIZE>) -> HashSet<IVec2> {
+    chunk.stained().points().collect()
+}
+
+fn unstained() -> Chunk<SandCell, CHUNK_SIZE> {
+    Chunk::full_copied(SandCell::Air, ()).without_initial_stain()
+}
+
+#[test]
+fn stain_across_a_shared_edge_marks_only_the_cells_past_it() {
+    let mut chunk = unstained();
+
+    // The neighbor on the right stained its two leftmost columns and the two past them, rows 3 to 5.
+    chunk.stain_from_neighbor(IVec2::X, &IRect::new(-2, 3, 1, 5).into());
+
+    let expected = (3..=5).flat_map(|y| [IVec2::new(CHUNK_SIZE - 2, y), IVec2::new(CHUNK_SIZE - 1, y)]).collect();
+
+    assert_eq!(stained_points(&chunk), expected);
+}
+
+#[test]
+fn stain_across_a_shared_corner_marks_only_the_corner() {
+    let mut chunk = unstained();
+
+    // The neighbor below and to the left, its stain reaching one cell past its top right corner.
+    let stain = Area::from_areas(
+        [IRect::new(CHUNK_SIZE - 1, CHUNK_SIZE - 1, CHUNK_SIZE, CHUNK_SIZE), IRect::new(0, 0, 2, 2)]
+            .into_iter()
+            .map(Area::from),
+    );
+
+    chunk.stain_from_neighbor(IVec2::NEG_ONE, &stain);
+
+    assert_eq!(stained_points(&chunk), [IVec2::ZERO].into_iter().collect());
+}
+
+#[test]
+fn stain_within_the_neighbor_marks_nothing() {
+    let mut chunk = unstained();
+
+    chunk.stain_from_neighbor(IVec2::NEG_Y, &IRect::new(0, 0, CHUNK_SIZE - 1, CHUNK_SIZE - 1).into());
+
+    assert!(chunk.stained().is_empty());
+}
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use powderkeg::{chunk::Chunk, stain::{StainPolicy, Stainable}};
+
+mod common;
+
+use common::SandCell;
+
+fn stained_with(policy: StainPolicy, points: &[IVec2]) -> Chunk<SandCell, 16> {
+    let mut chunk = Chunk::<SandCell, 16>::full_copied(SandCell::Air, ()).with_stain_policy(policy);
+
+    chunk.clear_stain();
+
+    for point in points {
+        chunk.stain_point(*point);
+    }
+
+    chunk
+}
+
+#[test]
+fn precise_is_tighter_than_bounding_for_scattered_points() {
+    let points = [IVec2::new(0, 0), IVec2::new(15, 15), IVec2::new(3, 12)];
+
+    let precise = stained_with(StainPolicy::Precise, &points).stained();
+    let bounding = stained_with(StainPolicy::Bounding, &points).stained();
+
+    assert_eq!(precise.cell_count(), 3);
+    assert_eq!(bounding.cell_count(), 256);
+
+    for point in points {
+        assert!(precise.contains(point));
+        assert!(bounding.contains(point));
+    }
+}
+
+#[test]
+fn precise_merges_stains_it_already_covers() {
+    let mut chunk = stained_with(StainPolicy::Precise, &[]);
+
+    chunk.stain(IRect::new(2, 2, 6, 6));
+    chunk.stain(IRect::new(3, 3, 4, 4));
+    chunk.stain_point(IVec2::new(5, 5));
+    chunk.stain(IRect::new(2, 2, 6, 6));
+
+    assert_eq!(chunk.stained().rects(), &[IRect::new(2, 2, 6, 6)]);
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{area::Area, chunk::Chunk, stain::Stainable, world::total_stained_cells};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+fn random_rect(rng: &mut SmallRng, extent: i32) -> IRect {
+    let min = IVec2::new(rng.gen_range(0..extent), rng.gen_range(0..extent));
+    let size = IVec2::new(rng.gen_range(0..extent / 2), rng.gen_range(0..extent / 2));
+
+    IRect::from_corners(min, min + size)
+}
+
+#[test]
+fn cell_count_of_a_coalesced_area_is_its_distinct_points() {
+    let mut rng = SmallRng::seed_from_u64(3);
+
+    for _ in 0..50 {
+        let mut area = Area::Many((0..rng.gen_range(1..12)).map(|_| random_rect(&mut rng, 20)).collect());
+
+        area.coalesce();
+
+        let distinct: HashSet<IVec2> = area.points().collect();
+
+        assert_eq!(area.cell_count(), distinct.len(), "{:?}", area.rects());
+    }
+}
+
+#[test]
+fn total_stained_cells_counts_each_stained_cell_once() {
+    let mut rng = SmallRng::seed_from_u64(5);
+    let mut chunks = Vec::new();
+
+    for _ in 0..3 {
+        let mut chunk = Chunk::<SandCell, CHUNK_SIZE>::full_copied(SandCell::Air, ()).without_initial_stain();
+
+        // Overlapping stains, some reaching past the chunk.
+        for _ in 0..6 {
+            chunk.stain(random_rect(&mut rng, CHUNK_SIZE + 4));
+        }
+
+        chunks.push(chunk);
+    }
+
+    let distinct: usize = chunks
+        .iter()
+        .map(|chunk| chunk.stained().points().collect::<HashSet<_>>().len())
+        .sum();
+
+    assert_eq!(total_stained_cells(&chunks), distinct);
+}
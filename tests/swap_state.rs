@@ -0,0 +1,95 @@
+use std::{collections::HashMap, convert::Infallible};
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, SwapStates, TickInput, TickSuccess}, chunk::Chunk, stain::Stainable, testing::TestGrid, PowderkegError};
+
+const N: i32 = 16;
+
+/// A marble falling straight down, carrying the tag its chunk keeps for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Marble {
+    Marble,
+    #[default]
+    Air,
+}
+
+#[derive(Default)]
+struct Tags(HashMap<IVec2, u32>);
+
+impl Cell for Marble {
+    type Error = Infallible;
+    type State = Tags;
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        let below = input.origin + IVec2::NEG_Y;
+
+        if *input.this() == Marble::Marble && input.grid.get(below).is_ok_and(|cell| *cell == Marble::Air) {
+            input.grid.swap_with_state(input.origin, below)?;
+            input.grid.stain_around(input.origin, 1);
+
+            return Ok(TickSuccess::Unstable);
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, -1, 0, 0)
+    }
+
+    fn swap_state(states: SwapStates<'_, Tags>, first: IVec2, second: IVec2) {
+        states.exchange(first, second, |tags, point| tags.0.remove(&point), |tags, point, tag| {
+            if let Some(tag) = tag {
+                tags.0.insert(point, tag);
+            }
+        });
+    }
+}
+
+impl Renderable for Marble {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::WHITE
+    }
+}
+
+fn tags(grid: &TestGrid<Marble, N>, coords: IVec2) -> HashMap<IVec2, u32> {
+    grid.chunk(coords).unwrap().state().read().0.clone()
+}
+
+#[test]
+fn tag_follows_marble_into_the_chunk_below() {
+    let mut grid = TestGrid::<Marble, N>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(Marble::Air, Tags::default()));
+    grid.insert_chunk(IVec2::Y, Chunk::full_copied(Marble::Air, Tags::default()));
+
+    grid.set(IVec2::new(5, N + 2), Marble::Marble).unwrap();
+    grid.chunk(IVec2::Y).unwrap().state().write().0.insert(IVec2::new(5, 2), 42);
+
+    for _ in 0..2 * N {
+        grid.step();
+    }
+
+    assert_eq!(grid.get(IVec2::new(5, 0)), Some(&Marble::Marble));
+    assert_eq!(tags(&grid, IVec2::ZERO), HashMap::from([(IVec2::new(5, 0), 42)]));
+    assert!(tags(&grid, IVec2::Y).is_empty());
+}
+
+#[test]
+fn swapping_tagged_cells_across_chunks_exchanges_their_tags() {
+    let chunks = [Chunk::<Marble, N>::full_copied(Marble::Air, Tags::default()), Chunk::full_copied(Marble::Air, Tags::default())];
+
+    chunks[0].state().write().0.insert(IVec2::new(3, N - 1), 1);
+    chunks[1].state().write().0.insert(IVec2::new(3, 0), 2);
+
+    let [below, above] = &chunks;
+
+    Marble::swap_state(
+        SwapStates::Split { first: &mut below.state().write(), second: &mut above.state().write() },
+        IVec2::new(3, N - 1),
+        IVec2::new(3, 0),
+    );
+
+    assert_eq!(below.state().read().0, HashMap::from([(IVec2::new(3, N - 1), 2)]));
+    assert_eq!(above.state().read().0, HashMap::from([(IVec2::new(3, 0), 1)]));
+}
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+use powderkeg::{chunk::Chunk, testing::TestGrid};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+fn stacked_chunks() -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut grid = TestGrid::new(0);
+
+    grid
+        .insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()))
+        .insert_chunk(IVec2::NEG_Y, Chunk::full_copied(SandCell::Air, ()));
+
+    grid
+}
+
+#[test]
+fn sand_falls_one_cell_a_step_within_and_across_chunks() {
+    let mut grid = stacked_chunks();
+
+    grid.set(IVec2::new(5, 5), SandCell::Sand).unwrap();
+    grid.set(IVec2::new(9, 0), SandCell::Sand).unwrap();
+
+    assert!(grid.step().is_empty());
+    assert_eq!(grid.tick(), 1);
+
+    assert_eq!(grid.get(IVec2::new(5, 4)), Some(&SandCell::Sand));
+    assert_eq!(grid.get(IVec2::new(5, 5)), Some(&SandCell::Air));
+    assert_eq!(grid.get(IVec2::new(9, -1)), Some(&SandCell::Sand));
+    assert_eq!(grid.get(IVec2::new(9, 0)), Some(&SandCell::Air));
+}
+
+#[test]
+fn sand_comes_to_rest_at_the_bottom() {
+    let mut grid = stacked_chunks();
+
+    grid.set(IVec2::new(5, 5), SandCell::Sand).unwrap();
+
+    for _ in 0..5 + CHUNK_SIZE {
+        assert!(grid.step().is_empty());
+    }
+
+    assert_eq!(grid.get(IVec2::new(5, -CHUNK_SIZE)), Some(&SandCell::Sand));
+
+    assert!(grid.step().is_empty());
+
+    assert_eq!(grid.get(IVec2::new(5, -CHUNK_SIZE)), Some(&SandCell::Sand));
+}
+
@@ -0,0 +1,48 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{area::StainOrder, chunk::{Chunk, ChunkCoords}, grid::Grid, simulation::tick_chunk, stain::Stainable, testing::TestGrid};
+
+/// Alternating columns of sand and air, stained throughout.
+fn populated() -> Chunk<SandCell, CHUNK_SIZE> {
+    let cells = (0..CHUNK_SIZE * CHUNK_SIZE)
+        .map(|i| if (i % CHUNK_SIZE) % 2 == 0 { SandCell::Sand } else { SandCell::Air })
+        .collect();
+
+    Chunk::new(cells, ())
+}
+
+#[test]
+fn ticking_a_chunk_leaves_its_populated_neighbor_untouched() {
+    let mut chunk = Chunk::<SandCell, CHUNK_SIZE>::full_copied(SandCell::Air, ());
+    let neighbor = populated();
+
+    // Resting on the bottom edge, so its range reaches into the neighbor below.
+    chunk.replace(IVec2::new(5, 0), SandCell::Sand).unwrap();
+    // Free to fall within the chunk.
+    chunk.replace(IVec2::new(3, 8), SandCell::Sand).unwrap();
+
+    let neighbor_cells = neighbor.cells().to_vec();
+    let neighbor_stain: HashSet<IVec2> = neighbor.stained().points().collect();
+
+    // Both chunks live in the same world, the neighbor directly below.
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+    grid.insert_chunk(IVec2::ZERO, chunk).insert_chunk(IVec2::NEG_Y, neighbor);
+
+    let mut chunk = grid.chunk_mut(IVec2::ZERO).unwrap();
+    let outcome = tick_chunk(&ChunkCoords::<CHUNK_SIZE>(IVec2::ZERO), &mut chunk, StainOrder::default(), 0, IVec2::NEG_Y, true);
+
+    assert!(outcome.errors.is_empty());
+    assert_eq!(*chunk.get(IVec2::new(3, 8)).unwrap(), SandCell::Air);
+    assert!((0..8).any(|y| *chunk.get(IVec2::new(3, y)).unwrap() == SandCell::Sand));
+
+    assert!(outcome.deferred.contains(&IVec2::new(5, 0)));
+    assert_eq!(*chunk.get(IVec2::new(5, 0)).unwrap(), SandCell::Sand);
+    assert!(chunk.is_stained(IVec2::new(5, 0)));
+
+    let neighbor = grid.chunk(IVec2::NEG_Y).unwrap();
+
+    assert_eq!(neighbor.cells(), neighbor_cells.as_slice());
+    assert_eq!(neighbor.stained().points().collect::<HashSet<_>>(), neighbor_stain);
+}
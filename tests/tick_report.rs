@@ -0,0 +1,54 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::{Chunk, ChunkBundle, ChunkCoords}, simulation::TickReport, stain::Stainable, testing::TestGrid, PowderkegError};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+/// A cell that never moves, simulated alongside the sand.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Still;
+
+impl Cell for Still {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(_: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, 0, 0, 0)
+    }
+}
+
+impl Renderable for Still {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::GRAY
+    }
+}
+
+#[test]
+fn each_cell_type_reports_its_own_ticks() {
+    let mut grid = TestGrid::<SandCell, CHUNK_SIZE>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()).without_initial_stain());
+    grid.set(IVec2::new(4, 4), SandCell::Sand).unwrap();
+
+    // A report shared by both types would be left holding whichever ticked last.
+    grid.simulate_also::<Still>().app_mut().world.spawn(ChunkBundle::new(Chunk::<Still, CHUNK_SIZE>::full_copied(Still, ()), ChunkCoords(IVec2::ZERO)));
+
+    grid.step();
+
+    let sand = grid.app_mut().world.resource::<TickReport<SandCell>>();
+
+    assert_eq!(sand.cells_ticked, 9);
+    assert_eq!(sand.cells_unstable, 1);
+
+    let still = grid.app_mut().world.resource::<TickReport<Still>>();
+
+    assert_eq!(still.cells_ticked, (CHUNK_SIZE * CHUNK_SIZE) as usize);
+    assert_eq!(still.cells_unstable, 0);
+}
@@ -0,0 +1,99 @@
+use std::{convert::Infallible, thread, time::Duration};
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, simulation::{TickReport, TickTimeBudget}, stain::Stainable, testing::TestGrid, PowderkegError};
+
+const CHUNK_SIZE: i32 = 16;
+
+/// Sand that takes a while to decide to fall.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum SlowSand {
+    Sand,
+    #[default]
+    Air,
+}
+
+impl Cell for SlowSand {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() != SlowSand::Sand {
+            return Ok(TickSuccess::Stable);
+        }
+
+        thread::sleep(Duration::from_micros(100));
+
+        let below = input.origin + IVec2::NEG_Y;
+
+        if input.grid.get(below).is_ok_and(|cell| *cell == SlowSand::Air) {
+            input.grid.swap(input.origin, below)?;
+            input.grid.stain_around(input.origin, 1);
+
+            return Ok(TickSuccess::Unstable);
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(0, -1, 0, 0)
+    }
+}
+
+impl Renderable for SlowSand {
+    fn to_color(&self, _: IVec2) -> Color {
+        match self {
+            SlowSand::Sand => Color::BEIGE,
+            SlowSand::Air => Color::BLACK,
+        }
+    }
+}
+
+fn sand_count(grid: &TestGrid<SlowSand, CHUNK_SIZE>) -> usize {
+    [IVec2::ZERO, IVec2::NEG_Y]
+        .into_iter()
+        .map(|coords| grid.chunk(coords).unwrap().cells().iter().filter(|cell| **cell == SlowSand::Sand).count())
+        .sum()
+}
+
+#[test]
+fn cells_cut_short_by_the_budget_tick_later_without_loss() {
+    let mut grid = TestGrid::<SlowSand, CHUNK_SIZE>::new(0);
+
+    let mut top = Chunk::full_copied(SlowSand::Air, ());
+    top.fill_rect(IRect::new(0, CHUNK_SIZE - 4, CHUNK_SIZE - 1, CHUNK_SIZE - 1), SlowSand::Sand).unwrap();
+
+    grid
+        .insert_chunk(IVec2::ZERO, top)
+        .insert_chunk(IVec2::NEG_Y, Chunk::full_copied(SlowSand::Air, ()));
+
+    grid.app_mut().insert_resource(TickTimeBudget(Duration::from_micros(1)));
+
+    let sand = sand_count(&grid);
+
+    assert_eq!(sand, 4 * CHUNK_SIZE as usize);
+
+    assert!(grid.step().is_empty());
+
+    // Both chunks start fully stained, the budget leaves most of them for later ticks.
+    let ticked = grid.app_mut().world.resource::<TickReport<SlowSand>>().cells_ticked;
+
+    assert!(0 < ticked && ticked < 2 * (CHUNK_SIZE * CHUNK_SIZE) as usize, "ticked {ticked} cells");
+    assert_eq!(sand_count(&grid), sand);
+
+    let settled = |grid: &TestGrid<SlowSand, CHUNK_SIZE>| {
+        (-CHUNK_SIZE..-CHUNK_SIZE + 4).all(|y| (0..CHUNK_SIZE).all(|x| grid.get(IVec2::new(x, y)) == Some(&SlowSand::Sand)))
+    };
+
+    for _ in 0..5000 {
+        if settled(&grid) {
+            break;
+        }
+
+        assert!(grid.step().is_empty());
+        assert_eq!(sand_count(&grid), sand);
+    }
+
+    assert!(settled(&grid));
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use powderkeg::{chunk::Chunk, simulation::{SimulationSchedule, WorldTopology}, testing::TestGrid};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+/// Two chunks stacked in a world wrapping vertically, with sand about to fall off the bottom.
+fn wrapping_grid(schedule: SimulationSchedule) -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut grid = TestGrid::new(0);
+
+    grid.app_mut()
+        .insert_resource(schedule)
+        .insert_resource(WorldTopology::Toroidal { min: IVec2::ZERO, max: IVec2::new(1, 2) });
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(SandCell::Air, ()));
+    grid.insert_chunk(IVec2::Y, Chunk::full_copied(SandCell::Air, ()));
+
+    grid.set(IVec2::new(5, 0), SandCell::Sand).unwrap();
+
+    grid
+}
+
+#[test]
+fn sand_falling_off_the_bottom_enters_at_the_top() {
+    for schedule in [SimulationSchedule::default(), SimulationSchedule::Checkerboard] {
+        let mut grid = wrapping_grid(schedule);
+
+        assert!(grid.step().is_empty(), "{schedule:?}");
+
+        assert_eq!(grid.get(IVec2::new(5, 0)), Some(&SandCell::Air), "{schedule:?}");
+        assert_eq!(grid.get(IVec2::new(5, 2 * CHUNK_SIZE - 1)), Some(&SandCell::Sand), "{schedule:?}");
+
+        // And keeps falling from there on.
+        grid.step();
+
+        assert_eq!(grid.get(IVec2::new(5, 2 * CHUNK_SIZE - 2)), Some(&SandCell::Sand), "{schedule:?}");
+    }
+}
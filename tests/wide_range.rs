@@ -0,0 +1,58 @@
+use std::convert::Infallible;
+
+use bevy::prelude::*;
+use powderkeg::{cell::{Cell, Renderable, TickInput, TickSuccess}, chunk::Chunk, stain::Stainable, testing::TestGrid, PowderkegError};
+
+const N: i32 = 16;
+
+/// Reaches a whole chunk either side of itself, and marks itself as ticked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Wide {
+    #[default]
+    Air,
+    Unticked,
+    Ticked,
+}
+
+impl Cell for Wide {
+    type Error = Infallible;
+    type State = ();
+
+    fn tick<G: Stainable<Cell = Self>>(input: TickInput<'_, Self, G>) -> Result<TickSuccess, PowderkegError<Self>> {
+        if *input.this() == Wide::Unticked {
+            input.grid.replace(input.origin, Wide::Ticked)?;
+        }
+
+        Ok(TickSuccess::Stable)
+    }
+
+    fn range(&self) -> IRect {
+        IRect::new(-N, 0, N, 0)
+    }
+}
+
+impl Renderable for Wide {
+    fn to_color(&self, _: IVec2) -> Color {
+        Color::WHITE
+    }
+}
+
+#[test]
+fn cells_reaching_two_chunks_wide_tick_at_either_edge_of_the_world() {
+    let mut grid = TestGrid::<Wide, N>::new(0);
+
+    grid.insert_chunk(IVec2::ZERO, Chunk::full_copied(Wide::Air, ()).without_initial_stain());
+    grid.insert_chunk(IVec2::X, Chunk::full_copied(Wide::Air, ()).without_initial_stain());
+
+    let points = [IVec2::new(3, 5), IVec2::new(N - 1, 8), IVec2::new(2 * N - 2, 5)];
+
+    for point in points {
+        grid.set(point, Wide::Unticked).unwrap();
+    }
+
+    assert!(grid.step().is_empty());
+
+    for point in points {
+        assert_eq!(grid.get(point), Some(&Wide::Ticked), "{point}");
+    }
+}
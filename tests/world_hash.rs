@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use powderkeg::{chunk::{Chunk, ChunkCoords}, grid::Grid, testing::TestGrid, world::world_state_hash};
+use rand::{distributions::{Bernoulli, Distribution}, rngs::SmallRng, SeedableRng};
+
+mod common;
+
+use common::{SandCell, CHUNK_SIZE};
+
+const COORDS: [IVec2; 2] = [IVec2::ZERO, IVec2::NEG_Y];
+
+fn poured(seed: u64) -> TestGrid<SandCell, CHUNK_SIZE> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let sand = Bernoulli::new(0.3).unwrap().map(|sand| if sand { SandCell::Sand } else { SandCell::Air });
+
+    let mut grid = TestGrid::new(seed);
+
+    for coords in COORDS {
+        grid.insert_chunk(coords, Chunk::full_random(&mut rng, &sand, ()));
+    }
+
+    for _ in 0..8 {
+        assert!(grid.step().is_empty());
+    }
+
+    grid
+}
+
+fn hash(grid: &TestGrid<SandCell, CHUNK_SIZE>) -> u64 {
+    let coords = COORDS.map(ChunkCoords::<CHUNK_SIZE>);
+
+    world_state_hash(coords.iter().map(|coords| (coords, grid.chunk(coords.0).unwrap())), false)
+}
+
+#[test]
+fn hash_is_stable_for_a_fixed_seed() {
+    assert_eq!(hash(&poured(42)), hash(&poured(42)));
+}
+
+#[test]
+fn hash_changes_with_a_single_cell() {
+    let mut grid = poured(42);
+    let before = hash(&grid);
+
+    let point = IVec2::new(3, 3);
+    let flipped = match grid.get(point) {
+        Some(SandCell::Sand) => SandCell::Air,
+        _ => SandCell::Sand,
+    };
+
+    grid.chunk_mut(IVec2::ZERO).unwrap().replace(point, flipped).unwrap();
+
+    assert_ne!(hash(&grid), before);
+}
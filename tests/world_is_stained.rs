@@ -0,0 +1,42 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{SandCell, CHUNK_SIZE};
+use powderkeg::{chunk::{Chunk, ChunkCoords}, stain::Stainable, world::world_is_stained};
+
+fn unstained() -> Chunk<SandCell, CHUNK_SIZE> {
+    Chunk::full_copied(SandCell::Air, ()).without_initial_stain()
+}
+
+#[test]
+fn matches_the_chunk_stain_and_is_false_outside_spawned_chunks() {
+    let mut origin = unstained();
+    let mut below_left = unstained();
+
+    origin.stain(IRect::new(2, 3, 5, 4));
+    below_left.stain_point(IVec2::new(CHUNK_SIZE - 1, 0));
+
+    let chunks = [
+        (ChunkCoords::<CHUNK_SIZE>(IVec2::ZERO), origin),
+        (ChunkCoords::<CHUNK_SIZE>(IVec2::NEG_ONE), below_left),
+    ];
+    let chunks = || chunks.iter().map(|(coords, chunk)| (coords, chunk));
+
+    for (coords, chunk) in chunks() {
+        for y in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let local = IVec2::new(x, y);
+
+                assert_eq!(world_is_stained(chunks(), coords.local_to_world(local)), chunk.is_stained(local), "{local} in {}", coords.0);
+            }
+        }
+    }
+
+    assert!(world_is_stained(chunks(), IVec2::new(3, 4)));
+    assert!(world_is_stained(chunks(), IVec2::new(-1, -CHUNK_SIZE)));
+
+    // The same local points in chunks that are not spawned.
+    assert!(!world_is_stained(chunks(), IVec2::new(CHUNK_SIZE + 3, 4)));
+    assert!(!world_is_stained(chunks(), IVec2::new(-1, 0)));
+    assert!(!world_is_stained(chunks(), IVec2::new(3, -CHUNK_SIZE + 4)));
+}